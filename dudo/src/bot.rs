@@ -0,0 +1,274 @@
+use std::fmt;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::bid::Bid;
+use crate::controller::{PlayerController, TurnAction};
+use crate::opponent_model::BeliefOdds;
+use crate::probability::BidOdds;
+use crate::resources::DudoRules;
+use crate::view::PlayerView;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+    /// Calls Dudo when the current bid is less likely than this.
+    fn challenge_below(self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.2,
+            Difficulty::Medium => 0.35,
+            Difficulty::Hard => 0.45,
+        }
+    }
+
+    /// Calls calza when an exact count is at least this likely.
+    fn calza_above(self) -> Option<f64> {
+        match self {
+            Difficulty::Easy => None,
+            Difficulty::Medium => Some(0.4),
+            Difficulty::Hard => Some(0.3),
+        }
+    }
+
+    /// Easy bots ignore their own dice and bid on table averages alone.
+    fn reads_own_hand(self) -> bool {
+        self != Difficulty::Easy
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Bot that works out how likely bids are from its own hand and the number
+/// of dice on the table, and raises or calls on fixed thresholds.
+pub struct HeuristicBot {
+    pub difficulty: Difficulty,
+}
+
+impl HeuristicBot {
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self { difficulty }
+    }
+}
+
+impl PlayerController for HeuristicBot {
+    fn choose_action(&mut self, view: &PlayerView) -> Result<Option<TurnAction>> {
+        let game_state = &view.game_state;
+        let odds = if self.difficulty.reads_own_hand() {
+            BidOdds::from_view(view)
+        } else {
+            BidOdds::blind(view.total_dice(), view.ones_are_wild())
+        };
+
+        let current = game_state.current_bid.map(|bid| {
+            (
+                odds.chance_at_least(bid.quantity, bid.face),
+                odds.chance_exactly(bid.quantity, bid.face),
+            )
+        });
+        let raises = odds.ranked_raises(game_state, &view.rules, view.player);
+        Ok(Some(decide(self.difficulty, &view.rules, current, raises)))
+    }
+}
+
+/// Bot that reads every opponent's bids this round as evidence about their
+/// hand, through its view's `HandBeliefs`, and plays the resulting odds on
+/// Hard thresholds.
+pub struct BayesBot;
+
+impl BayesBot {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for BayesBot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PlayerController for BayesBot {
+    fn choose_action(&mut self, view: &PlayerView) -> Result<Option<TurnAction>> {
+        let game_state = &view.game_state;
+        let odds = BeliefOdds::from_view(view);
+
+        let current = game_state.current_bid.map(|bid| {
+            (
+                odds.chance_at_least(bid.quantity, bid.face),
+                odds.chance_exactly(bid.quantity, bid.face),
+            )
+        });
+        let raises = odds.ranked_raises(game_state, &view.rules, view.player);
+        Ok(Some(decide(Difficulty::Hard, &view.rules, current, raises)))
+    }
+}
+
+/// Shared turn logic for the odds-based bots. `current` holds the chances
+/// that the bid on the table is at least and exactly right.
+fn decide(
+    difficulty: Difficulty,
+    rules: &DudoRules,
+    current: Option<(f64, f64)>,
+    raises: Vec<(Bid, f64)>,
+) -> TurnAction {
+    if let Some((at_least, exactly)) = current {
+        if let Some(threshold) = difficulty.calza_above()
+            && rules.calza
+            && exactly >= threshold
+        {
+            return TurnAction::Calza;
+        }
+        if at_least < difficulty.challenge_below() {
+            return TurnAction::Dudo;
+        }
+    }
+
+    match (raises.into_iter().next(), current) {
+        // Calling is the better gamble when every raise is a worse bet than
+        // the current bid being false.
+        (Some((_, chance)), Some((at_least, _))) if chance < 1.0 - at_least => TurnAction::Dudo,
+        (Some((bid, _)), _) => TurnAction::Bid {
+            quantity: bid.quantity,
+            face: bid.face,
+        },
+        // Every bid is already at the table maximum.
+        (None, _) => TurnAction::Dudo,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_engine::{Entity, World};
+
+    use crate::components::dice::Hand;
+    use crate::resources::{BidHistory, GamePhase, GameState, TurnOrder};
+    use crate::setup_game;
+
+    fn world_with_hands(hands: &[&[u8]]) -> (World, Vec<Entity>) {
+        let names = (0..hands.len()).map(|i| format!("Bot {i}")).collect();
+        let mut world = setup_game(names, DudoRules::perudo(), 3).unwrap();
+        let players = world.resource::<TurnOrder>().unwrap().players.clone();
+        for (&player, faces) in players.iter().zip(hands) {
+            *world.component_mut::<Hand>(player).unwrap() = Hand::from_faces(faces);
+        }
+        world.resource_mut::<GameState>().unwrap().phase = GamePhase::Bidding;
+        (world, players)
+    }
+
+    fn view(world: &World, player: Entity) -> PlayerView {
+        PlayerView::for_player(world, player).unwrap()
+    }
+
+    fn current_bid(world: &mut World, player: Entity, quantity: u8, face: u8) {
+        world.resource_mut::<GameState>().unwrap().current_bid =
+            Some(Bid::new(player, quantity, face));
+    }
+
+    #[test]
+    fn opening_bid_is_valid() {
+        let (world, players) = world_with_hands(&[&[2, 2, 5, 6, 1], &[3, 3, 4, 4, 6]]);
+        for difficulty in Difficulty::ALL {
+            let action = HeuristicBot::new(difficulty)
+                .choose_action(&view(&world, players[0]))
+                .unwrap();
+            let Some(TurnAction::Bid { quantity, face }) = action else {
+                panic!("{difficulty:?} opened with {action:?}");
+            };
+            let bid = Bid::new(players[0], quantity, face);
+            assert!(bid.is_well_formed());
+        }
+    }
+
+    #[test]
+    fn impossible_bids_are_challenged() {
+        let (mut world, players) = world_with_hands(&[&[2, 2, 5, 6, 4], &[3, 3, 4, 4, 6]]);
+        current_bid(&mut world, players[1], 9, 5);
+        for difficulty in Difficulty::ALL {
+            let action = HeuristicBot::new(difficulty)
+                .choose_action(&view(&world, players[0]))
+                .unwrap();
+            assert_eq!(action, Some(TurnAction::Dudo), "{difficulty:?}");
+        }
+    }
+
+    #[test]
+    fn safe_bids_are_raised() {
+        let (mut world, players) = world_with_hands(&[&[5, 5, 5, 1, 1], &[3, 3, 4, 4, 6]]);
+        current_bid(&mut world, players[1], 2, 5);
+        let action = HeuristicBot::new(Difficulty::Medium)
+            .choose_action(&view(&world, players[0]))
+            .unwrap();
+        let Some(TurnAction::Bid { quantity, face }) = action else {
+            panic!("expected a raise, got {action:?}");
+        };
+        let bid = Bid::new(players[0], quantity, face);
+        assert!(bid.is_valid_raise(&Bid::new(players[1], 2, 5), &DudoRules::perudo()));
+    }
+
+    #[test]
+    fn bayes_bot_trusts_a_bidder_who_keeps_naming_a_face() {
+        let (mut world, players) = world_with_hands(&[&[2, 2, 3, 4, 4], &[3, 3, 4, 4, 6]]);
+        current_bid(&mut world, players[1], 4, 6);
+        let fair = BeliefOdds::from_view(&view(&world, players[0])).chance_at_least(4, 6);
+
+        let history = world.resource_mut::<BidHistory>().unwrap();
+        for quantity in 2..=4 {
+            history.push(Bid::new(players[1], quantity, 6));
+        }
+        let informed = BeliefOdds::from_view(&view(&world, players[0])).chance_at_least(4, 6);
+        assert!(informed > fair, "{informed} <= {fair}");
+    }
+
+    #[test]
+    fn bots_play_a_full_game_with_legal_actions() {
+        use crate::DudoEvent;
+        use crate::event_systems::process_events;
+
+        let names = (0..4).map(|i| format!("Bot {i}")).collect();
+        let mut world = setup_game(names, DudoRules::perudo(), 11).unwrap();
+        world.emit_event(DudoEvent::GameReady, 0.0).unwrap();
+        process_events(&mut world).unwrap();
+
+        let mut bots: Vec<Box<dyn PlayerController>> = vec![
+            Box::new(HeuristicBot::new(Difficulty::Easy)),
+            Box::new(HeuristicBot::new(Difficulty::Medium)),
+            Box::new(HeuristicBot::new(Difficulty::Hard)),
+            Box::new(BayesBot::new()),
+        ];
+        for _ in 0..10_000 {
+            let event = match world.resource::<GameState>().unwrap().phase {
+                GamePhase::RoundStart => DudoEvent::RollDice,
+                GamePhase::GameOver => return,
+                _ => {
+                    let player = world.resource::<TurnOrder>().unwrap().current_player();
+                    let action = bots[player.id as usize]
+                        .choose_action(&view(&world, player))
+                        .unwrap()
+                        .unwrap();
+                    action.into_event(player)
+                }
+            };
+            world.emit_event(event, 0.0).unwrap();
+            process_events(&mut world).unwrap();
+        }
+        panic!("game did not finish");
+    }
+}
@@ -0,0 +1,533 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Context, Result, bail};
+use game_engine::Entity;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::bid::Bid;
+use crate::bot::{Difficulty, HeuristicBot};
+use crate::controller::{PlayerController, TurnAction};
+use crate::resources::DudoRules;
+use crate::systems::challenge::{bid_stands, count_matching};
+use crate::view::PlayerView;
+
+/// Bumped whenever the layout of `StrategyTable` changes incompatibly.
+pub const STRATEGY_VERSION: u32 = 2;
+
+/// Solving the full bid tree is only practical for a handful of dice.
+pub const MAX_CFR_DICE: u8 = 4;
+
+/// Share of opponent moves sampled uniformly during training, so decisions
+/// off the equilibrium path (such as facing a wild overbid) still get trained.
+const EXPLORATION: f64 = 0.1;
+
+/// A single heads-up round of Dudo: who has how many dice, under which rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CfrConfig {
+    pub dice: [u8; 2],
+    pub rules: DudoRules,
+}
+
+impl CfrConfig {
+    pub fn new(dice: [u8; 2], rules: DudoRules) -> Result<Self> {
+        if dice.contains(&0) || dice[0] + dice[1] > MAX_CFR_DICE {
+            bail!("CFR supports 1v1 rounds with at most {MAX_CFR_DICE} dice in total");
+        }
+        Ok(Self { dice, rules })
+    }
+
+    fn total_dice(&self) -> u8 {
+        self.dice[0] + self.dice[1]
+    }
+
+    /// Every bid that can be made in this round, lowest first under the
+    /// library's own raise ordering.
+    fn bids(&self) -> Vec<Bid> {
+        let mut bids: Vec<Bid> = (1..=self.total_dice())
+            .flat_map(|quantity| (1..=6).map(move |face| Bid::new(Entity::new(0), quantity, face)))
+            .collect();
+        bids.sort_by(|a, b| {
+            if b.is_valid_raise(a, &self.rules) {
+                std::cmp::Ordering::Less
+            } else if a.is_valid_raise(b, &self.rules) {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        });
+        bids
+    }
+}
+
+/// An action at a decision point: call Dudo or calza, or raise to a bid
+/// index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CfrAction {
+    Dudo,
+    Calza,
+    Bid(usize),
+}
+
+/// Precomputed bid list and legal moves for one configuration.
+struct Round {
+    config: CfrConfig,
+    bids: Vec<Bid>,
+}
+
+impl Round {
+    fn new(config: CfrConfig) -> Self {
+        Self {
+            bids: config.bids(),
+            config,
+        }
+    }
+
+    fn legal_actions(&self, last_bid: Option<usize>) -> Vec<CfrAction> {
+        let mut actions = Vec::new();
+        match last_bid {
+            Some(last) => {
+                actions.push(CfrAction::Dudo);
+                if self.config.rules.calza {
+                    actions.push(CfrAction::Calza);
+                }
+                let prev = self.bids[last];
+                actions.extend(
+                    (last + 1..self.bids.len())
+                        .filter(|&i| self.bids[i].is_valid_raise(&prev, &self.config.rules))
+                        .map(CfrAction::Bid),
+                );
+            }
+            None => actions.extend((0..self.bids.len()).map(CfrAction::Bid)),
+        }
+        actions
+    }
+
+    /// +1 if `player` wins the challenge on `bid`, -1 if they lose a die.
+    /// An exact calza counts as a win for the caller, since regaining a die
+    /// moves the dice count as far as the opponent losing one would; it is
+    /// worth nothing if the caller already holds the starting dice.
+    fn payoff(
+        &self,
+        rolls: &[Vec<u8>; 2],
+        kind: CfrAction,
+        bid: usize,
+        caller: usize,
+        player: usize,
+    ) -> f64 {
+        let bid = self.bids[bid];
+        let total = count_matching(
+            rolls.iter().flatten().copied(),
+            bid.face,
+            self.config.rules.wild_ones,
+        );
+        let caller_wins = if kind == CfrAction::Calza {
+            if total != bid.quantity as usize {
+                false
+            } else if self.config.dice[caller] >= self.config.rules.starting_dice {
+                return 0.0;
+            } else {
+                true
+            }
+        } else {
+            !bid_stands(&bid, total)
+        };
+        if (caller == player) == caller_wins {
+            1.0
+        } else {
+            -1.0
+        }
+    }
+}
+
+fn info_key(player: usize, roll: &[u8], history: &[usize]) -> String {
+    let roll: String = roll.iter().map(|f| f.to_string()).collect();
+    let history: Vec<String> = history.iter().map(|i| i.to_string()).collect();
+    format!("{player}|{roll}|{}", history.join("."))
+}
+
+fn sorted_roll(rng: &mut ChaCha8Rng, dice: u8) -> Vec<u8> {
+    let mut roll: Vec<u8> = (0..dice).map(|_| rng.random_range(1..7)).collect();
+    roll.sort_unstable();
+    roll
+}
+
+fn regret_matching(regrets: &[f64]) -> Vec<f64> {
+    let positive: f64 = regrets.iter().map(|r| r.max(0.0)).sum();
+    if positive > 0.0 {
+        regrets.iter().map(|r| r.max(0.0) / positive).collect()
+    } else {
+        vec![1.0 / regrets.len() as f64; regrets.len()]
+    }
+}
+
+fn sample(rng: &mut ChaCha8Rng, strategy: &[f64]) -> usize {
+    let mut roll: f64 = rng.random();
+    for (idx, p) in strategy.iter().enumerate() {
+        if roll < *p {
+            return idx;
+        }
+        roll -= p;
+    }
+    strategy.len() - 1
+}
+
+/// External-sampling Monte Carlo CFR over single heads-up rounds.
+pub struct CfrTrainer {
+    round: Round,
+    regrets: HashMap<String, Vec<f64>>,
+    strategy_sums: HashMap<String, Vec<f64>>,
+    rng: ChaCha8Rng,
+    iterations: u64,
+}
+
+impl CfrTrainer {
+    pub fn new(config: CfrConfig, seed: u64) -> Self {
+        Self {
+            round: Round::new(config),
+            regrets: HashMap::new(),
+            strategy_sums: HashMap::new(),
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            iterations: 0,
+        }
+    }
+
+    pub fn train(&mut self, iterations: u64) {
+        for _ in 0..iterations {
+            for traverser in 0..2 {
+                let rolls = [
+                    sorted_roll(&mut self.rng, self.round.config.dice[0]),
+                    sorted_roll(&mut self.rng, self.round.config.dice[1]),
+                ];
+                self.traverse(&rolls, &mut Vec::new(), traverser);
+            }
+            self.iterations += 1;
+        }
+    }
+
+    /// Expected payoff for `traverser` below `history`, updating regrets at
+    /// their decisions and the average strategy at the opponent's.
+    fn traverse(
+        &mut self,
+        rolls: &[Vec<u8>; 2],
+        history: &mut Vec<usize>,
+        traverser: usize,
+    ) -> f64 {
+        let player = history.len() % 2;
+        let actions = self.round.legal_actions(history.last().copied());
+        let key = info_key(player, &rolls[player], history);
+        let regrets = self
+            .regrets
+            .entry(key.clone())
+            .or_insert_with(|| vec![0.0; actions.len()]);
+        let strategy = regret_matching(regrets);
+
+        if player != traverser {
+            let sums = self
+                .strategy_sums
+                .entry(key)
+                .or_insert_with(|| vec![0.0; actions.len()]);
+            for (sum, p) in sums.iter_mut().zip(&strategy) {
+                *sum += p;
+            }
+            let explored: Vec<f64> = strategy
+                .iter()
+                .map(|p| (1.0 - EXPLORATION) * p + EXPLORATION / actions.len() as f64)
+                .collect();
+            let action = actions[sample(&mut self.rng, &explored)];
+            return self.play(rolls, history, traverser, player, action);
+        }
+
+        let utilities: Vec<f64> = actions
+            .iter()
+            .map(|&action| self.play(rolls, history, traverser, player, action))
+            .collect();
+        let node_utility: f64 = utilities.iter().zip(&strategy).map(|(u, p)| u * p).sum();
+
+        let regrets = self.regrets.get_mut(&key).expect("inserted above");
+        for (regret, utility) in regrets.iter_mut().zip(&utilities) {
+            *regret += utility - node_utility;
+        }
+        node_utility
+    }
+
+    fn play(
+        &mut self,
+        rolls: &[Vec<u8>; 2],
+        history: &mut Vec<usize>,
+        traverser: usize,
+        player: usize,
+        action: CfrAction,
+    ) -> f64 {
+        match action {
+            CfrAction::Dudo | CfrAction::Calza => {
+                let last = *history.last().expect("a challenge needs a bid");
+                self.round.payoff(rolls, action, last, player, traverser)
+            }
+            CfrAction::Bid(idx) => {
+                history.push(idx);
+                let utility = self.traverse(rolls, history, traverser);
+                history.pop();
+                utility
+            }
+        }
+    }
+
+    /// The average strategy so far, which is what converges to equilibrium.
+    pub fn strategy(&self) -> StrategyTable {
+        let infosets = self
+            .strategy_sums
+            .iter()
+            .map(|(key, sums)| {
+                let total: f64 = sums.iter().sum();
+                let strategy = if total > 0.0 {
+                    sums.iter().map(|s| s / total).collect()
+                } else {
+                    vec![1.0 / sums.len() as f64; sums.len()]
+                };
+                (key.clone(), strategy)
+            })
+            .collect();
+
+        StrategyTable {
+            version: STRATEGY_VERSION,
+            config: self.round.config,
+            iterations: self.iterations,
+            infosets,
+        }
+    }
+}
+
+/// Trained average strategy, keyed by information set. Each entry holds the
+/// probability of every legal action: Dudo first when allowed, then calza
+/// if the rules have it, then raises from lowest to highest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyTable {
+    pub version: u32,
+    pub config: CfrConfig,
+    pub iterations: u64,
+    pub infosets: HashMap<String, Vec<f64>>,
+}
+
+impl StrategyTable {
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let json = serde_json::to_string(self)?;
+        fs::write(path, json).with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let table: Self = serde_json::from_str(&json)
+            .with_context(|| format!("{} is not a CFR strategy table", path.display()))?;
+        if table.version != STRATEGY_VERSION {
+            bail!(
+                "strategy table version {} is not supported (this build reads version {STRATEGY_VERSION})",
+                table.version
+            );
+        }
+        Ok(table)
+    }
+}
+
+/// Plays from a trained strategy table when the table matches the round in
+/// progress, and falls back to the hard heuristic bot otherwise.
+pub struct CfrBot {
+    round: Round,
+    table: Arc<StrategyTable>,
+    rng: ChaCha8Rng,
+    fallback: HeuristicBot,
+}
+
+impl CfrBot {
+    pub fn new(table: Arc<StrategyTable>, seed: u64) -> Self {
+        Self {
+            round: Round::new(table.config),
+            table,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            fallback: HeuristicBot::new(Difficulty::Hard),
+        }
+    }
+
+    /// The table's action for `player`, if this round is one it was trained on.
+    fn table_action(&mut self, view: &PlayerView) -> Result<Option<TurnAction>> {
+        let player = view.player;
+        let turn_order = &view.turn_order;
+        if turn_order.player_count() != 2
+            || view.game_state.is_palifico()
+            || view.rules.untimed() != self.table.config.rules.untimed()
+        {
+            return Ok(None);
+        }
+
+        let bids = &view.bid_history.bids;
+        let opener = bids.first().map_or(player, |bid| bid.player);
+        let seats = if opener == turn_order.players[0] {
+            [turn_order.players[0], turn_order.players[1]]
+        } else {
+            [turn_order.players[1], turn_order.players[0]]
+        };
+        let dice = [view.dice_count(seats[0]), view.dice_count(seats[1])];
+        if dice != self.table.config.dice {
+            return Ok(None);
+        }
+
+        let mut history = Vec::new();
+        for bid in bids {
+            let Some(idx) = self
+                .round
+                .bids
+                .iter()
+                .position(|b| (b.quantity, b.face) == (bid.quantity, bid.face))
+            else {
+                return Ok(None);
+            };
+            history.push(idx);
+        }
+        let seat = history.len() % 2;
+        if seats[seat] != player {
+            return Ok(None);
+        }
+
+        let mut roll: Vec<u8> = view.hand.dice.iter().filter_map(|d| d.face).collect();
+        roll.sort_unstable();
+        let Some(strategy) = self.table.infosets.get(&info_key(seat, &roll, &history)) else {
+            return Ok(None);
+        };
+
+        let actions = self.round.legal_actions(history.last().copied());
+        Ok(Some(match actions[sample(&mut self.rng, strategy)] {
+            CfrAction::Dudo => TurnAction::Dudo,
+            CfrAction::Calza => TurnAction::Calza,
+            CfrAction::Bid(idx) => TurnAction::Bid {
+                quantity: self.round.bids[idx].quantity,
+                face: self.round.bids[idx].face,
+            },
+        }))
+    }
+}
+
+impl PlayerController for CfrBot {
+    fn choose_action(&mut self, view: &PlayerView) -> Result<Option<TurnAction>> {
+        match self.table_action(view)? {
+            Some(action) => Ok(Some(action)),
+            None => self.fallback.choose_action(view),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::dice::Hand;
+    use crate::resources::{GamePhase, GameState, TurnOrder};
+    use crate::setup_game;
+
+    fn trained(dice: [u8; 2], iterations: u64) -> StrategyTable {
+        let config = CfrConfig::new(dice, DudoRules::perudo()).unwrap();
+        let mut trainer = CfrTrainer::new(config, 1);
+        trainer.train(iterations);
+        trainer.strategy()
+    }
+
+    #[test]
+    fn bids_follow_the_library_raise_order() {
+        for rules in [DudoRules::perudo(), DudoRules::classic()] {
+            let config = CfrConfig::new([2, 2], rules).unwrap();
+            let bids = config.bids();
+            assert_eq!(bids.len(), 24);
+            for pair in bids.windows(2) {
+                assert!(pair[1].is_valid_raise(&pair[0], &rules), "{pair:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn oversized_configurations_are_rejected() {
+        assert!(CfrConfig::new([3, 2], DudoRules::perudo()).is_err());
+        assert!(CfrConfig::new([0, 1], DudoRules::perudo()).is_err());
+    }
+
+    #[test]
+    fn strategies_are_distributions() {
+        let table = trained([1, 1], 2_000);
+        assert!(!table.infosets.is_empty());
+        for (key, strategy) in &table.infosets {
+            let total: f64 = strategy.iter().sum();
+            assert!((total - 1.0).abs() < 1e-9, "{key}: {strategy:?}");
+        }
+    }
+
+    #[test]
+    fn impossible_bids_are_called() {
+        let table = trained([1, 1], 5_000);
+        let round = Round::new(table.config);
+        // Holding a 3, the opponent's claim of two 6s needs two sixes or aces.
+        let two_sixes = round
+            .bids
+            .iter()
+            .position(|b| (b.quantity, b.face) == (2, 6))
+            .unwrap();
+        let strategy = &table.infosets[&info_key(1, &[3], &[two_sixes])];
+        assert!(strategy[0] > 0.9, "{strategy:?}");
+    }
+
+    #[test]
+    fn calza_is_trained_only_when_the_rules_allow_it() {
+        for rules in [DudoRules::perudo(), DudoRules::classic()] {
+            let config = CfrConfig::new([1, 1], rules).unwrap();
+            let mut trainer = CfrTrainer::new(config, 1);
+            trainer.train(500);
+            let round = Round::new(config);
+            assert_eq!(
+                round.legal_actions(Some(0)).contains(&CfrAction::Calza),
+                rules.calza
+            );
+
+            for (key, strategy) in &trainer.strategy().infosets {
+                let last_bid = key.rsplit('|').next().unwrap().rsplit('.').next();
+                let last_bid = last_bid
+                    .filter(|b| !b.is_empty())
+                    .map(|b| b.parse().unwrap());
+                assert_eq!(strategy.len(), round.legal_actions(last_bid).len(), "{key}");
+            }
+        }
+    }
+
+    #[test]
+    fn the_table_is_used_in_timed_games_too() {
+        let rules = DudoRules {
+            turn_seconds: Some(30),
+            ..DudoRules::perudo()
+        };
+        let names = vec!["Ana".to_string(), "Ben".to_string()];
+        let mut world = setup_game(names, rules, 1).unwrap();
+        let players = world.resource::<TurnOrder>().unwrap().players.clone();
+        for player in &players {
+            *world.component_mut::<Hand>(*player).unwrap() = Hand::from_faces(&[3]);
+        }
+        world.resource_mut::<GameState>().unwrap().phase = GamePhase::Bidding;
+
+        let mut bot = CfrBot::new(Arc::new(trained([1, 1], 200)), 1);
+        let view = PlayerView::for_player(&world, players[0]).unwrap();
+        assert!(bot.table_action(&view).unwrap().is_some());
+    }
+
+    #[test]
+    fn tables_survive_a_save_and_load() {
+        let table = trained([1, 1], 200);
+        let path = std::env::temp_dir().join(format!("dudo-cfr-{}.json", std::process::id()));
+        table.save(&path).unwrap();
+        let loaded = StrategyTable::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.config, table.config);
+        assert_eq!(loaded.infosets.len(), table.infosets.len());
+    }
+}
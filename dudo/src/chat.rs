@@ -0,0 +1,306 @@
+//! Table talk: short messages and emotes sent alongside the game. Chat is
+//! not a [`DudoEvent`](crate::DudoEvent), so it never reaches the game's
+//! systems, and a game log only keeps it when told to.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::hash::Hash;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, anyhow, bail, ensure};
+use game_engine::Entity;
+use serde::{Deserialize, Serialize};
+
+use crate::resources::{ChallengeKind, ChallengeOutcome};
+
+/// Longest text message, in characters.
+pub const MAX_CHAT_LEN: usize = 200;
+
+/// The fixed set of emotes anyone can send.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Emote {
+    Laugh,
+    Think,
+    Wow,
+    Cool,
+    Cry,
+    Applause,
+}
+
+impl Emote {
+    pub const ALL: [Emote; 6] = [
+        Emote::Laugh,
+        Emote::Think,
+        Emote::Wow,
+        Emote::Cool,
+        Emote::Cry,
+        Emote::Applause,
+    ];
+
+    pub fn glyph(self) -> &'static str {
+        match self {
+            Emote::Laugh => "😂",
+            Emote::Think => "🤔",
+            Emote::Wow => "😮",
+            Emote::Cool => "😎",
+            Emote::Cry => "😭",
+            Emote::Applause => "👏",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Emote::Laugh => "laugh",
+            Emote::Think => "think",
+            Emote::Wow => "wow",
+            Emote::Cool => "cool",
+            Emote::Cry => "cry",
+            Emote::Applause => "applause",
+        }
+    }
+}
+
+impl FromStr for Emote {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Emote::ALL
+            .into_iter()
+            .find(|emote| emote.name() == s.to_ascii_lowercase())
+            .ok_or_else(|| anyhow!("unknown emote '{s}'"))
+    }
+}
+
+/// What someone said.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatBody {
+    Text(String),
+    Emote(Emote),
+}
+
+impl ChatBody {
+    pub fn validate(&self) -> Result<()> {
+        if let ChatBody::Text(text) = self {
+            ensure!(!text.trim().is_empty(), "say something");
+            ensure!(
+                text.chars().count() <= MAX_CHAT_LEN,
+                "messages are at most {MAX_CHAT_LEN} characters"
+            );
+            ensure!(
+                !text.chars().any(char::is_control),
+                "messages must be a single line"
+            );
+        }
+        Ok(())
+    }
+}
+
+/// A chat line as everyone receives it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub from: String,
+    pub body: ChatBody,
+}
+
+impl fmt::Display for ChatMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.body {
+            ChatBody::Text(text) => write!(f, "{}: {text}", self.from),
+            ChatBody::Emote(emote) => write!(f, "{} {}", self.from, emote.glyph()),
+        }
+    }
+}
+
+/// A line typed into a chat box: something to say, or a mute.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChatCommand {
+    Say(ChatBody),
+    Mute(String),
+    Unmute(String),
+}
+
+impl FromStr for ChatCommand {
+    type Err = anyhow::Error;
+
+    /// Reads `/mute NAME`, `/unmute NAME` or `/EMOTE`; anything else is text.
+    fn from_str(input: &str) -> Result<Self> {
+        let input = input.trim();
+        let Some(command) = input.strip_prefix('/') else {
+            let body = ChatBody::Text(input.to_string());
+            body.validate()?;
+            return Ok(ChatCommand::Say(body));
+        };
+        let (command, name) = command.split_once(' ').unwrap_or((command, ""));
+        let name = name.trim().to_string();
+        match command {
+            "mute" | "unmute" if name.is_empty() => bail!("/{command} needs a name"),
+            "mute" => Ok(ChatCommand::Mute(name)),
+            "unmute" => Ok(ChatCommand::Unmute(name)),
+            emote => Ok(ChatCommand::Say(ChatBody::Emote(emote.parse()?))),
+        }
+    }
+}
+
+/// Allows each sender at most `limit` messages in any `window`.
+#[derive(Debug, Clone)]
+pub struct RateLimiter<K> {
+    limit: usize,
+    window: Duration,
+    sent: HashMap<K, VecDeque<Instant>>,
+}
+
+impl<K: Eq + Hash> RateLimiter<K> {
+    pub fn new(limit: usize, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            sent: HashMap::new(),
+        }
+    }
+
+    /// Counts a message from `sender`, refusing it if they are over the limit.
+    pub fn check(&mut self, sender: K, now: Instant) -> Result<()> {
+        let sent = self.sent.entry(sender).or_default();
+        while sent
+            .front()
+            .is_some_and(|&at| now.duration_since(at) >= self.window)
+        {
+            sent.pop_front();
+        }
+        ensure!(
+            sent.len() < self.limit,
+            "slow down: at most {} messages every {}s",
+            self.limit,
+            self.window.as_secs()
+        );
+        sent.push_back(now);
+        Ok(())
+    }
+
+    pub fn forget(&mut self, sender: &K) {
+        self.sent.remove(sender);
+    }
+}
+
+impl<K: Eq + Hash> Default for RateLimiter<K> {
+    /// Five messages every ten seconds.
+    fn default() -> Self {
+        Self::new(5, Duration::from_secs(10))
+    }
+}
+
+/// Recent chat as one reader sees it, without the senders they muted.
+#[derive(Debug, Clone, Default)]
+pub struct ChatLog {
+    lines: VecDeque<ChatMessage>,
+    muted: HashSet<String>,
+}
+
+impl ChatLog {
+    /// Lines kept for display.
+    const KEEP: usize = 100;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a message unless its sender is muted, returning whether it was.
+    pub fn push(&mut self, message: ChatMessage) -> bool {
+        if self.muted.contains(&message.from) {
+            return false;
+        }
+        if self.lines.len() == Self::KEEP {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(message);
+        true
+    }
+
+    pub fn set_muted(&mut self, name: &str, muted: bool) {
+        if muted {
+            self.muted.insert(name.to_string());
+        } else {
+            self.muted.remove(name);
+        }
+    }
+
+    pub fn is_muted(&self, name: &str) -> bool {
+        self.muted.contains(name)
+    }
+
+    pub fn lines(&self) -> impl DoubleEndedIterator<Item = &ChatMessage> + ExactSizeIterator {
+        self.lines.iter()
+    }
+}
+
+/// How a bot at the table reacts to a challenge it was part of.
+pub fn reaction(outcome: &ChallengeOutcome, player: Entity) -> Option<Emote> {
+    let involved = player == outcome.challenger || player == outcome.bid.player;
+    match (outcome.kind, outcome.loser) {
+        (ChallengeKind::Calza, None) if player == outcome.challenger => Some(Emote::Cool),
+        (_, Some(loser)) if loser == player => Some(Emote::Cry),
+        (ChallengeKind::Dudo, Some(_)) if involved => Some(Emote::Laugh),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_lines_become_messages_emotes_or_mutes() {
+        assert_eq!(
+            "  six fives ".parse::<ChatCommand>().unwrap(),
+            ChatCommand::Say(ChatBody::Text("six fives".into()))
+        );
+        assert_eq!(
+            "/Laugh".parse::<ChatCommand>().unwrap(),
+            ChatCommand::Say(ChatBody::Emote(Emote::Laugh))
+        );
+        assert_eq!(
+            "/mute Ben".parse::<ChatCommand>().unwrap(),
+            ChatCommand::Mute("Ben".into())
+        );
+        assert!("/mute".parse::<ChatCommand>().is_err());
+        assert!("/dance".parse::<ChatCommand>().is_err());
+        assert!("   ".parse::<ChatCommand>().is_err());
+        assert!("x".repeat(MAX_CHAT_LEN + 1).parse::<ChatCommand>().is_err());
+    }
+
+    #[test]
+    fn senders_over_the_limit_wait_out_the_window() {
+        let mut limiter = RateLimiter::new(2, Duration::from_secs(10));
+        let start = Instant::now();
+        limiter.check("Ana", start).unwrap();
+        limiter.check("Ana", start).unwrap();
+        assert!(
+            limiter
+                .check("Ana", start + Duration::from_secs(9))
+                .is_err()
+        );
+        limiter.check("Ben", start).unwrap();
+        limiter
+            .check("Ana", start + Duration::from_secs(10))
+            .unwrap();
+    }
+
+    #[test]
+    fn muted_senders_are_left_out_of_the_log() {
+        let mut log = ChatLog::new();
+        let say = |from: &str| ChatMessage {
+            from: from.into(),
+            body: ChatBody::Emote(Emote::Wow),
+        };
+        log.set_muted("Ben", true);
+        assert!(log.push(say("Ana")));
+        assert!(!log.push(say("Ben")));
+        log.set_muted("Ben", false);
+        assert!(log.push(say("Ben")));
+        assert_eq!(log.lines().len(), 2);
+        assert_eq!(log.lines().next().unwrap().to_string(), "Ana 😮");
+    }
+}
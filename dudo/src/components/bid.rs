@@ -1,6 +1,11 @@
 use game_engine::Entity;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy)]
+use crate::resources::DudoRules;
+
+pub const ACES: u8 = 1;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Bid {
     pub player: Entity,
     pub quantity: u8,
@@ -15,4 +20,149 @@ impl Bid {
             face,
         }
     }
+
+    pub fn is_aces(&self) -> bool {
+        self.face == ACES
+    }
+
+    /// At least one die showing a real face.
+    pub fn is_well_formed(&self) -> bool {
+        self.quantity >= 1 && (1..=6).contains(&self.face)
+    }
+
+    /// Whether `self` may follow `prev` under `rules`.
+    ///
+    /// Without wild ones bids are ordered by quantity, then face. With wild
+    /// ones a bid on aces needs at least half the previous quantity (rounded
+    /// up), and leaving aces needs double the previous quantity plus one.
+    pub fn is_valid_raise(&self, prev: &Bid, rules: &DudoRules) -> bool {
+        if !self.is_well_formed() {
+            return false;
+        }
+
+        let (quantity, prev_quantity) = (self.quantity as u16, prev.quantity as u16);
+        let outranks =
+            quantity > prev_quantity || (quantity == prev_quantity && self.face > prev.face);
+
+        if !rules.wild_ones {
+            return outranks;
+        }
+
+        match (prev.is_aces(), self.is_aces()) {
+            (false, false) => outranks,
+            (false, true) => quantity >= prev_quantity.div_ceil(2),
+            (true, true) => quantity > prev_quantity,
+            (true, false) => quantity > prev_quantity * 2,
+        }
+    }
+
+    /// Whether `self` may follow `prev` in a palifico round, where the face
+    /// of the opening bid is frozen and only the quantity can go up.
+    pub fn is_valid_palifico_raise(&self, prev: &Bid) -> bool {
+        self.is_well_formed() && self.face == prev.face && self.quantity > prev.quantity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_QUANTITY: u8 = 20;
+
+    fn bid(quantity: u8, face: u8) -> Bid {
+        Bid::new(Entity::new(0), quantity, face)
+    }
+
+    fn all_bids() -> Vec<Bid> {
+        (1..=MAX_QUANTITY)
+            .flat_map(|q| (1..=6).map(move |f| bid(q, f)))
+            .collect()
+    }
+
+    fn all_rules() -> [DudoRules; 2] {
+        [DudoRules::perudo(), DudoRules::classic()]
+    }
+
+    #[test]
+    fn classic_raises_compare_quantity_then_face() {
+        let rules = DudoRules::classic();
+        assert!(bid(6, 3).is_valid_raise(&bid(5, 3), &rules));
+        assert!(bid(5, 4).is_valid_raise(&bid(5, 3), &rules));
+        assert!(!bid(5, 2).is_valid_raise(&bid(5, 3), &rules));
+        assert!(!bid(3, 1).is_valid_raise(&bid(5, 3), &rules));
+    }
+
+    #[test]
+    fn switching_to_aces_halves_rounding_up() {
+        let rules = DudoRules::perudo();
+        assert!(bid(3, 1).is_valid_raise(&bid(5, 3), &rules));
+        assert!(!bid(2, 1).is_valid_raise(&bid(5, 3), &rules));
+        assert!(bid(2, 1).is_valid_raise(&bid(4, 6), &rules));
+        assert!(!bid(1, 1).is_valid_raise(&bid(4, 6), &rules));
+    }
+
+    #[test]
+    fn leaving_aces_doubles_plus_one() {
+        let rules = DudoRules::perudo();
+        assert!(bid(7, 2).is_valid_raise(&bid(3, 1), &rules));
+        assert!(!bid(6, 6).is_valid_raise(&bid(3, 1), &rules));
+        assert!(bid(4, 1).is_valid_raise(&bid(3, 1), &rules));
+        assert!(!bid(3, 1).is_valid_raise(&bid(3, 1), &rules));
+    }
+
+    #[test]
+    fn palifico_raises_keep_the_face() {
+        assert!(bid(3, 4).is_valid_palifico_raise(&bid(2, 4)));
+        assert!(bid(3, 1).is_valid_palifico_raise(&bid(2, 1)));
+        assert!(!bid(3, 5).is_valid_palifico_raise(&bid(2, 4)));
+        assert!(!bid(2, 4).is_valid_palifico_raise(&bid(2, 4)));
+        assert!(!bid(8, 1).is_valid_palifico_raise(&bid(2, 4)));
+    }
+
+    #[test]
+    fn malformed_bids_never_raise() {
+        for rules in all_rules() {
+            for prev in all_bids() {
+                assert!(!bid(0, 3).is_valid_raise(&prev, &rules));
+                assert!(!bid(MAX_QUANTITY, 0).is_valid_raise(&prev, &rules));
+                assert!(!bid(MAX_QUANTITY, 7).is_valid_raise(&prev, &rules));
+            }
+        }
+    }
+
+    #[test]
+    fn exactly_one_of_two_distinct_bids_raises_the_other() {
+        for rules in all_rules() {
+            for a in all_bids() {
+                assert!(!a.is_valid_raise(&a, &rules), "{a:?} raises itself");
+                for b in all_bids() {
+                    if (a.quantity, a.face) == (b.quantity, b.face) {
+                        continue;
+                    }
+                    assert_ne!(
+                        a.is_valid_raise(&b, &rules),
+                        b.is_valid_raise(&a, &rules),
+                        "{a:?} vs {b:?} under {rules:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn raises_are_transitive() {
+        for rules in all_rules() {
+            let bids = all_bids();
+            for a in &bids {
+                for b in bids.iter().filter(|b| b.is_valid_raise(a, &rules)) {
+                    for c in bids.iter().filter(|c| c.is_valid_raise(b, &rules)) {
+                        assert!(
+                            c.is_valid_raise(a, &rules),
+                            "{c:?} > {b:?} > {a:?} under {rules:?}"
+                        );
+                    }
+                }
+            }
+        }
+    }
 }
@@ -1,6 +1,8 @@
 use std::fmt;
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Dice {
     pub face: Option<u8>,
 }
@@ -11,16 +13,42 @@ impl Dice {
     }
 }
 
+impl Default for Dice {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Hand {
     pub dice: Vec<Dice>,
 }
 
 impl Hand {
     pub fn new() -> Self {
+        Self::with_dice(5)
+    }
+
+    pub fn with_dice(count: u8) -> Self {
         Self {
-            dice: vec![Dice::new(); 5],
+            dice: vec![Dice::new(); count as usize],
         }
     }
+
+    pub fn from_faces(faces: &[u8]) -> Self {
+        Self {
+            dice: faces
+                .iter()
+                .map(|&face| Dice { face: Some(face) })
+                .collect(),
+        }
+    }
+}
+
+impl Default for Hand {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl fmt::Display for Hand {
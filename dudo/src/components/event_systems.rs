@@ -1,11 +1,18 @@
 use crate::DudoEvent;
+use crate::game_log::log_processed_event;
+use crate::profiles::tally_processed_event;
+use crate::systems::challenge::{CalzaSystem, ChallengeSystem};
+use crate::systems::commit_seeds::{DealerCommitSystem, SeedCommitSystem};
+use crate::systems::forfeit::ForfeitSystem;
 use crate::systems::place_bid::PlaceBidSystem;
 use crate::systems::roll_dice::RollDiceSystem;
+use crate::systems::start_game::StartGameSystem;
+use crate::systems::turn_timer::{TimeoutSystem, TurnWarningSystem};
 use game_engine::World;
 
 pub fn process_events(world: &mut World) -> anyhow::Result<()> {
-    while let Some(event) = world.pop_event::<DudoEvent>()?.map(|e| e.event) {
-        match event {
+    while let Some(queued) = world.pop_event::<DudoEvent>()? {
+        match queued.event.clone() {
             DudoEvent::BidMade {
                 player,
                 quantity,
@@ -13,10 +20,36 @@ pub fn process_events(world: &mut World) -> anyhow::Result<()> {
             } => {
                 PlaceBidSystem::run(world, player, quantity, face)?;
             }
+            DudoEvent::ChallengeMade { challenger } => {
+                ChallengeSystem::run(world, challenger)?;
+            }
+            DudoEvent::CalzaCalled { caller } => {
+                CalzaSystem::run(world, caller)?;
+            }
+            DudoEvent::GameReady => {
+                StartGameSystem::run(world)?;
+            }
             DudoEvent::RollDice => {
                 RollDiceSystem::run(world)?;
             }
+            DudoEvent::PlayerForfeited { player } => {
+                ForfeitSystem::run(world, player)?;
+            }
+            DudoEvent::TurnWarning { player, .. } => {
+                TurnWarningSystem::run(world, player)?;
+            }
+            DudoEvent::TurnTimedOut { player } => {
+                TimeoutSystem::run(world, player)?;
+            }
+            DudoEvent::DealerCommitted { commitment } => {
+                DealerCommitSystem::run(world, commitment)?;
+            }
+            DudoEvent::SeedCommitted { player, commitment } => {
+                SeedCommitSystem::run(world, player, commitment)?;
+            }
         }
+        log_processed_event(world, queued.timestamp, &queued.event)?;
+        tally_processed_event(world, &queued.event)?;
     }
     Ok(())
 }
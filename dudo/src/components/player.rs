@@ -1,3 +1,11 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+
+use crate::bot::Difficulty;
+
 pub struct Player;
 
 pub struct Gamertag {
@@ -9,3 +17,51 @@ impl Gamertag {
         Self { name: name.into() }
     }
 }
+
+/// Who takes the turns of a player entity.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Controller {
+    Human,
+    Bot(Difficulty),
+    /// Bot that models opponents' hands from their bids.
+    Bayes,
+    /// Plays from the CFR strategy table stored at `strategy`.
+    Cfr {
+        strategy: String,
+    },
+}
+
+/// Parses the names used on the command line: `human`, `easy`, `medium`,
+/// `hard`, `bayes` or `cfr:<strategy path>`.
+impl FromStr for Controller {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(strategy) = s.strip_prefix("cfr:") {
+            return Ok(Controller::Cfr {
+                strategy: strategy.to_string(),
+            });
+        }
+        Ok(match s.to_ascii_lowercase().as_str() {
+            "human" => Controller::Human,
+            "easy" => Controller::Bot(Difficulty::Easy),
+            "medium" => Controller::Bot(Difficulty::Medium),
+            "hard" => Controller::Bot(Difficulty::Hard),
+            "bayes" => Controller::Bayes,
+            _ => bail!(
+                "unknown controller '{s}' (expected human, easy, medium, hard, bayes or cfr:<path>)"
+            ),
+        })
+    }
+}
+
+impl fmt::Display for Controller {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Controller::Human => write!(f, "human"),
+            Controller::Bot(difficulty) => write!(f, "{}", difficulty.to_string().to_lowercase()),
+            Controller::Bayes => write!(f, "bayes"),
+            Controller::Cfr { strategy } => write!(f, "cfr:{strategy}"),
+        }
+    }
+}
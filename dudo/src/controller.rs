@@ -0,0 +1,35 @@
+use anyhow::Result;
+use game_engine::Entity;
+use serde::{Deserialize, Serialize};
+
+use crate::DudoEvent;
+use crate::view::PlayerView;
+
+/// What a player decides to do with their turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TurnAction {
+    Bid { quantity: u8, face: u8 },
+    Dudo,
+    Calza,
+}
+
+impl TurnAction {
+    pub fn into_event(self, player: Entity) -> DudoEvent {
+        match self {
+            TurnAction::Bid { quantity, face } => DudoEvent::BidMade {
+                player,
+                quantity,
+                face,
+            },
+            TurnAction::Dudo => DudoEvent::ChallengeMade { challenger: player },
+            TurnAction::Calza => DudoEvent::CalzaCalled { caller: player },
+        }
+    }
+}
+
+/// Decides turns for one seat at the table, whether a person or a bot.
+pub trait PlayerController {
+    /// Picks the viewing player's action for the current turn. `None` means
+    /// the controller handed the turn back without acting, e.g. to pause.
+    fn choose_action(&mut self, view: &PlayerView) -> Result<Option<TurnAction>>;
+}
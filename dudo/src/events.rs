@@ -2,6 +2,8 @@ use anyhow::Result;
 use game_engine::{Entity, GameEvent, World};
 use serde::{Deserialize, Serialize};
 
+use crate::fair_dice::Commitment;
+
 impl GameEvent for DudoEvent {}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,11 +16,38 @@ pub enum DudoEvent {
     ChallengeMade {
         challenger: Entity,
     },
+    CalzaCalled {
+        caller: Entity,
+    },
     GameReady,
     RollDice,
+    /// A player leaves the game for good, such as a networked player who
+    /// did not come back in time.
+    PlayerForfeited {
+        player: Entity,
+    },
+    /// The player to act is running out of time.
+    TurnWarning {
+        player: Entity,
+        seconds_left: u32,
+    },
+    /// The player to act ran out of time; the rules' default action is
+    /// taken for them.
+    TurnTimedOut {
+        player: Entity,
+    },
+    /// The dealer commits to its secret seed for this round's dice.
+    DealerCommitted {
+        commitment: Commitment,
+    },
+    /// A player commits to the secret seed they gave the dealer.
+    SeedCommitted {
+        player: Entity,
+        commitment: Commitment,
+    },
 }
 
-fn emit(world: &mut World, event: DudoEvent) -> Result<()> {
+pub fn emit(world: &mut World, event: DudoEvent) -> Result<()> {
     use std::time::{SystemTime, UNIX_EPOCH};
 
     let t = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs_f64();
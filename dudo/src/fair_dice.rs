@@ -0,0 +1,364 @@
+//! Commit-reveal dice, so players need not trust whoever rolls them.
+//!
+//! At `RoundStart` the dealer commits to a secret seed of its own, then
+//! every player sends the dealer a secret seed whose commitment is
+//! published. Each hand is derived from all the seeds combined, so neither
+//! the dealer nor any player can pick the dice. When a challenge reveals
+//! the hands it reveals the seeds too, and [`verify_round`] checks them
+//! against the commitments and re-derives every hand.
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{Context, Result, ensure};
+use game_engine::{Entity, World};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+
+use crate::resources::{ChallengeOutcome, GameState, TurnOrder};
+
+/// A 32-byte value written as hex.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bytes32(pub [u8; 32]);
+
+impl fmt::Display for Bytes32 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+    }
+}
+
+impl fmt::Debug for Bytes32 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+impl FromStr for Bytes32 {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        ensure!(
+            s.len() == 64 && s.is_ascii(),
+            "expected 64 hex digits, got '{s}'"
+        );
+        let mut bytes = [0; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16)
+                .with_context(|| format!("'{s}' is not hex"))?;
+        }
+        Ok(Self(bytes))
+    }
+}
+
+impl Serialize for Bytes32 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Bytes32 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// A secret contribution to a round's dice.
+pub type Seed = Bytes32;
+
+/// The published hash of a [`Seed`].
+pub type Commitment = Bytes32;
+
+/// SHA-256 of each part, length-prefixed so parts cannot run together.
+pub fn hash(parts: &[&[u8]]) -> Bytes32 {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part);
+    }
+    Bytes32(hasher.finalize().into())
+}
+
+impl Bytes32 {
+    pub fn random() -> Self {
+        Self(rand::random())
+    }
+
+    /// What a seed's owner publishes before the seed itself.
+    pub fn commitment(&self) -> Commitment {
+        hash(&[b"dudo-seed", &self.0])
+    }
+}
+
+/// The commitments everyone can see for the round being dealt.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoundCommitments {
+    pub round: u32,
+    pub dealer: Option<Commitment>,
+    /// In the order the players committed.
+    pub players: Vec<(Entity, Commitment)>,
+}
+
+impl RoundCommitments {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `round` is still waiting for the dealer's commitment.
+    pub fn needs_dealer(&self, round: u32) -> bool {
+        self.round != round || self.dealer.is_none()
+    }
+
+    pub fn has_committed(&self, player: Entity) -> bool {
+        self.players
+            .iter()
+            .any(|&(committed, _)| committed == player)
+    }
+}
+
+/// The secrets behind a round's commitments. Only the dealer holds these,
+/// until a challenge reveals them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoundSeeds {
+    pub round: u32,
+    pub dealer: Option<Seed>,
+    pub players: Vec<(Entity, Seed)>,
+}
+
+impl RoundSeeds {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts `round` with a fresh dealer seed, returning its commitment.
+    pub fn new_round(&mut self, round: u32) -> Commitment {
+        let dealer = Seed::random();
+        *self = Self {
+            round,
+            dealer: Some(dealer),
+            players: Vec::new(),
+        };
+        dealer.commitment()
+    }
+
+    /// Keeps the seed `player` contributed.
+    pub fn add(&mut self, player: Entity, seed: Seed) {
+        self.players.retain(|(p, _)| *p != player);
+        self.players.push((player, seed));
+    }
+
+    /// Every seed known for `round`.
+    pub fn proof(&self, round: u32) -> Option<RoundProof> {
+        Some(RoundProof {
+            round,
+            dealer: self.dealer.filter(|_| self.round == round)?,
+            players: self.players.clone(),
+        })
+    }
+}
+
+/// The seeds this round's hands are dealt from, if every player still in
+/// the game committed to one and the seeds are known.
+pub fn committed_proof(world: &World) -> Result<Option<RoundProof>> {
+    let round = world.resource::<GameState>()?.round;
+    let commitments = world.resource::<RoundCommitments>()?;
+    let players = &world.resource::<TurnOrder>()?.players;
+    if commitments.needs_dealer(round) || !players.iter().all(|&p| commitments.has_committed(p)) {
+        return Ok(None);
+    }
+    let proof = world.resource::<RoundSeeds>()?.proof(round);
+    Ok(proof.filter(|proof| {
+        players
+            .iter()
+            .all(|&player| proof.players.iter().any(|(p, _)| *p == player))
+    }))
+}
+
+/// A round's seeds, revealed with its hands so anyone can check the deal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoundProof {
+    pub round: u32,
+    pub dealer: Seed,
+    pub players: Vec<(Entity, Seed)>,
+}
+
+impl RoundProof {
+    /// All seeds hashed together, in seat order.
+    fn combined(&self) -> Bytes32 {
+        let mut players = self.players.clone();
+        players.sort_by_key(|(player, _)| player.id);
+        let round = self.round.to_le_bytes();
+        let mut parts: Vec<&[u8]> = vec![b"dudo-round", &round, &self.dealer.0];
+        let ids: Vec<[u8; 8]> = players.iter().map(|(p, _)| p.id.to_le_bytes()).collect();
+        for ((_, seed), id) in players.iter().zip(&ids) {
+            parts.push(id);
+            parts.push(&seed.0);
+        }
+        hash(&parts)
+    }
+
+    /// The `count` dice `player` is dealt this round.
+    pub fn hand(&self, player: Entity, count: usize) -> Vec<u8> {
+        let seed = hash(&[&self.combined().0, &player.id.to_le_bytes()]);
+        let mut rng = ChaCha8Rng::from_seed(seed.0);
+        (0..count).map(|_| rng.random_range(1..7)).collect()
+    }
+}
+
+/// Something about a revealed round that does not add up.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Mismatch {
+    /// The challenge came without the round's seeds.
+    NoProof,
+    /// The seeds are for a different round than the commitments.
+    WrongRound { committed: u32, revealed: u32 },
+    /// The dealer's seed does not match its commitment.
+    DealerSeed,
+    /// A player's seed does not match their commitment, or is missing.
+    Seed { player: Entity },
+    /// A revealed hand is not the one the seeds deal.
+    Hand { player: Entity },
+}
+
+/// Checks a challenge's revealed hands against the round's commitments,
+/// returning every mismatch found. An empty list means the deal was fair.
+pub fn verify_round(commitments: &RoundCommitments, outcome: &ChallengeOutcome) -> Vec<Mismatch> {
+    let Some(proof) = &outcome.proof else {
+        return vec![Mismatch::NoProof];
+    };
+    let mut mismatches = Vec::new();
+    if proof.round != commitments.round {
+        mismatches.push(Mismatch::WrongRound {
+            committed: commitments.round,
+            revealed: proof.round,
+        });
+    }
+    if commitments.dealer != Some(proof.dealer.commitment()) {
+        mismatches.push(Mismatch::DealerSeed);
+    }
+    for &(player, commitment) in &commitments.players {
+        let revealed = proof.players.iter().find(|(p, _)| *p == player);
+        if revealed.is_none_or(|(_, seed)| seed.commitment() != commitment) {
+            mismatches.push(Mismatch::Seed { player });
+        }
+    }
+    for &(player, _) in &proof.players {
+        if !commitments.has_committed(player) {
+            mismatches.push(Mismatch::Seed { player });
+        }
+    }
+    for (player, faces) in &outcome.revealed {
+        let mut dealt = proof.hand(*player, faces.len());
+        let mut shown = faces.clone();
+        dealt.sort_unstable();
+        shown.sort_unstable();
+        if dealt != shown {
+            mismatches.push(Mismatch::Hand { player: *player });
+        }
+    }
+    mismatches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DudoEvent;
+    use crate::event_systems::process_events;
+    use crate::resources::DudoRules;
+    use crate::setup_game;
+
+    /// A three-player game dealt from seeds, then challenged.
+    fn challenged_round() -> (RoundCommitments, ChallengeOutcome) {
+        let names = vec!["Ana".to_string(), "Ben".to_string(), "Cid".to_string()];
+        let mut world = setup_game(names, DudoRules::perudo(), 5).unwrap();
+        let play = |world: &mut game_engine::World, event| {
+            world.emit_event(event, 0.0).unwrap();
+            process_events(world).unwrap();
+        };
+        play(&mut world, DudoEvent::GameReady);
+
+        let seeds = world.resource_mut::<RoundSeeds>().unwrap();
+        let commitment = seeds.new_round(1);
+        play(&mut world, DudoEvent::DealerCommitted { commitment });
+        for player in world.resource::<TurnOrder>().unwrap().players.clone() {
+            let seed = Seed::random();
+            world
+                .resource_mut::<RoundSeeds>()
+                .unwrap()
+                .add(player, seed);
+            let commitment = seed.commitment();
+            play(&mut world, DudoEvent::SeedCommitted { player, commitment });
+        }
+        play(&mut world, DudoEvent::RollDice);
+        let bidder = world.resource::<TurnOrder>().unwrap().current_player();
+        play(
+            &mut world,
+            DudoEvent::BidMade {
+                player: bidder,
+                quantity: 1,
+                face: 2,
+            },
+        );
+        let commitments = world.resource::<RoundCommitments>().unwrap().clone();
+        let challenger = world.resource::<TurnOrder>().unwrap().current_player();
+        play(&mut world, DudoEvent::ChallengeMade { challenger });
+        let outcome = world
+            .resource::<GameState>()
+            .unwrap()
+            .last_challenge
+            .clone();
+        (commitments, outcome.unwrap())
+    }
+
+    #[test]
+    fn seeds_round_trip_as_hex() {
+        let seed = Seed::random();
+        let json = serde_json::to_string(&seed).unwrap();
+        assert_eq!(json.len(), 66);
+        assert_eq!(serde_json::from_str::<Seed>(&json).unwrap(), seed);
+        assert!("xyz".parse::<Seed>().is_err());
+    }
+
+    #[test]
+    fn an_honest_deal_verifies() {
+        let (commitments, outcome) = challenged_round();
+        assert_eq!(verify_round(&commitments, &outcome), vec![]);
+    }
+
+    #[test]
+    fn tampering_with_hands_or_seeds_is_flagged() {
+        let (commitments, outcome) = challenged_round();
+        let (ben, _) = outcome.revealed[1];
+
+        let mut swapped = outcome.clone();
+        let faces = &mut swapped.revealed[1].1;
+        faces[0] = faces[0] % 6 + 1;
+        assert_eq!(
+            verify_round(&commitments, &swapped),
+            vec![Mismatch::Hand { player: ben }]
+        );
+
+        let mut reseeded = outcome.clone();
+        let (player, seed) = &mut reseeded.proof.as_mut().unwrap().players[1];
+        let player = *player;
+        *seed = Seed::random();
+        let flagged = verify_round(&commitments, &reseeded);
+        assert!(flagged.contains(&Mismatch::Seed { player }));
+
+        let mut dealer = outcome.clone();
+        dealer.proof.as_mut().unwrap().dealer = Seed::random();
+        assert!(verify_round(&commitments, &dealer).contains(&Mismatch::DealerSeed));
+
+        let unproven = ChallengeOutcome {
+            proof: None,
+            ..outcome
+        };
+        assert_eq!(
+            verify_round(&commitments, &unproven),
+            vec![Mismatch::NoProof]
+        );
+    }
+}
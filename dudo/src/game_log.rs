@@ -0,0 +1,180 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use game_engine::{Entity, World};
+use serde::{Deserialize, Serialize};
+
+use crate::DudoEvent;
+use crate::chat::ChatMessage;
+use crate::components::dice::Hand;
+use crate::components::player::Gamertag;
+use crate::resources::{ChallengeOutcome, DudoRules, GameMetadata, GameState, TurnOrder};
+
+/// One line of a game log. The first entry of every log is `GameStarted`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum LogEntry {
+    GameStarted {
+        players: Vec<String>,
+        rules: DudoRules,
+        seed: u64,
+    },
+    Event {
+        timestamp: f64,
+        event: DudoEvent,
+    },
+    HandsRolled {
+        round: u32,
+        hands: Vec<(Entity, Vec<u8>)>,
+    },
+    ChallengeResolved(ChallengeOutcome),
+    /// Table talk, only in logs that were asked to keep it. Replays skip it.
+    Chat {
+        timestamp: f64,
+        message: ChatMessage,
+    },
+}
+
+/// Append-only record of everything that happened in a game, written out as
+/// JSON Lines when backed by a file.
+pub struct EventLog {
+    writer: Option<Box<dyn Write>>,
+    record_chat: bool,
+    pub entries: Vec<LogEntry>,
+}
+
+impl EventLog {
+    pub fn in_memory() -> Self {
+        Self {
+            writer: None,
+            record_chat: false,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::create(path)
+            .with_context(|| format!("failed to create game log {}", path.display()))?;
+        Ok(Self {
+            writer: Some(Box::new(BufWriter::new(file))),
+            record_chat: false,
+            entries: Vec::new(),
+        })
+    }
+
+    /// Keeps chat in the log too; by default it is left out.
+    pub fn with_chat(mut self) -> Self {
+        self.record_chat = true;
+        self
+    }
+
+    /// Records a chat message if this log keeps chat.
+    pub fn record_chat(&mut self, timestamp: f64, message: &ChatMessage) -> Result<()> {
+        if !self.record_chat {
+            return Ok(());
+        }
+        self.record(LogEntry::Chat {
+            timestamp,
+            message: message.clone(),
+        })
+    }
+
+    pub fn record(&mut self, entry: LogEntry) -> Result<()> {
+        if let Some(writer) = &mut self.writer {
+            serde_json::to_writer(&mut *writer, &entry)?;
+            writeln!(writer)?;
+            writer.flush()?;
+        }
+        self.entries.push(entry);
+        Ok(())
+    }
+}
+
+/// Starts `log` with the game's setup and makes the world record into it.
+pub fn attach_event_log(world: &mut World, mut log: EventLog) -> Result<()> {
+    let mut players = Vec::new();
+    for &player in &world.resource::<TurnOrder>()?.players {
+        players.push(world.component::<Gamertag>(player)?.name.clone());
+    }
+
+    log.record(LogEntry::GameStarted {
+        players,
+        rules: *world.resource::<DudoRules>()?,
+        seed: world.resource::<GameMetadata>()?.seed,
+    })?;
+    world.insert_resource(log);
+    Ok(())
+}
+
+/// Records a processed event and whatever it revealed, if the world has a log.
+pub fn log_processed_event(world: &mut World, timestamp: f64, event: &DudoEvent) -> Result<()> {
+    if world.resource::<EventLog>().is_err() {
+        return Ok(());
+    }
+
+    let mut entries = vec![LogEntry::Event {
+        timestamp,
+        event: event.clone(),
+    }];
+    match event {
+        DudoEvent::RollDice => entries.push(LogEntry::HandsRolled {
+            round: world.resource::<GameState>()?.round,
+            hands: current_hands(world)?,
+        }),
+        DudoEvent::ChallengeMade { .. } | DudoEvent::CalzaCalled { .. } => {
+            if let Some(outcome) = &world.resource::<GameState>()?.last_challenge {
+                entries.push(LogEntry::ChallengeResolved(outcome.clone()));
+            }
+        }
+        _ => {}
+    }
+
+    let log = world.resource_mut::<EventLog>()?;
+    for entry in entries {
+        log.record(entry)?;
+    }
+    Ok(())
+}
+
+/// Records table talk if the world has a log that keeps chat.
+pub fn log_chat(world: &mut World, message: &ChatMessage) -> Result<()> {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let Ok(log) = world.resource_mut::<EventLog>() else {
+        return Ok(());
+    };
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs_f64();
+    log.record_chat(timestamp, message)
+}
+
+pub fn read_log(path: impl AsRef<Path>) -> Result<Vec<LogEntry>> {
+    let path = path.as_ref();
+    let file =
+        File::open(path).with_context(|| format!("failed to open game log {}", path.display()))?;
+    parse_log(BufReader::new(file))
+}
+
+pub fn parse_log(reader: impl BufRead) -> Result<Vec<LogEntry>> {
+    let mut entries = Vec::new();
+    for (idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry = serde_json::from_str(&line)
+            .with_context(|| format!("invalid log entry on line {}", idx + 1))?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+fn current_hands(world: &World) -> Result<Vec<(Entity, Vec<u8>)>> {
+    let mut hands = Vec::new();
+    for &player in &world.resource::<TurnOrder>()?.players {
+        let hand = world.component::<Hand>(player)?;
+        hands.push((player, hand.dice.iter().filter_map(|d| d.face).collect()));
+    }
+    Ok(hands)
+}
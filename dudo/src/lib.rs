@@ -1,7 +1,23 @@
+pub mod bot;
+pub mod cfr;
+pub mod chat;
 pub mod components;
+pub mod controller;
 pub mod events;
+pub mod fair_dice;
+pub mod game_log;
+pub mod net;
+pub mod opponent_model;
+pub mod peer_dice;
+pub mod probability;
+pub mod profiles;
+pub mod replay;
 pub mod resources;
+pub mod save;
+pub mod simulate;
 pub mod systems;
+pub mod tui;
+pub mod view;
 
 pub use components::*;
 pub use events::DudoEvent;
@@ -11,21 +27,35 @@ use anyhow::Result;
 use game_engine::{Entity, EventQueue, World};
 
 use crate::components::dice::Hand;
-use crate::components::player::{Gamertag, Player};
-use crate::resources::{BidHistory, GameState, TurnOrder};
+use crate::components::player::{Controller, Gamertag, Player};
+use crate::fair_dice::{RoundCommitments, RoundSeeds};
+use crate::resources::{
+    BidHistory, DiceRng, DudoRules, GameMetadata, GameState, TableOptions, TurnClock, TurnOrder,
+};
 
-pub fn setup_game(player_names: Vec<String>) -> Result<World> {
+pub fn setup_game(player_names: Vec<String>, rules: DudoRules, seed: u64) -> Result<World> {
     let mut world = World::new();
     world.insert_resource(EventQueue::<DudoEvent>::new());
     world.insert_resource(GameState::new());
+    world.insert_resource(rules);
+    world.insert_resource(DiceRng::seeded(seed));
+    world.insert_resource(GameMetadata { seed });
+    world.insert_resource(TableOptions::default());
     world.insert_resource(BidHistory::new());
+    world.insert_resource(TurnClock::new());
+    world.insert_resource(RoundCommitments::new());
+    world.insert_resource(RoundSeeds::new());
 
-    let players = add_players(&mut world, player_names)?;
+    let players = add_players(&mut world, player_names, rules.starting_dice)?;
     world.insert_resource(TurnOrder::new(players));
     Ok(world)
 }
 
-fn add_players(world: &mut World, player_names: Vec<String>) -> Result<Vec<Entity>> {
+fn add_players(
+    world: &mut World,
+    player_names: Vec<String>,
+    starting_dice: u8,
+) -> Result<Vec<Entity>> {
     let mut players = Vec::new();
 
     for name in player_names.iter() {
@@ -33,7 +63,8 @@ fn add_players(world: &mut World, player_names: Vec<String>) -> Result<Vec<Entit
             .spawn()
             .with(Player)?
             .with(Gamertag::new(name))?
-            .with(Hand::new())?
+            .with(Hand::with_dice(starting_dice))?
+            .with(Controller::Human)?
             .build();
 
         players.push(player);
@@ -1,38 +1,535 @@
-use crate::components::bid::{Bid, BidEntry, BidHistory};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::collections::HashMap;
+use std::io;
+use std::process::ExitCode;
+use std::time::Instant;
 
-// use game_core::systems::RollDiceSystem;
 use game_engine::{Entity, World};
 
-use anyhow::Result;
+use anyhow::{Context, Result, bail};
+use clap::error::ErrorKind;
+use clap::{Args, CommandFactory, Parser, Subcommand};
 use colored::Colorize;
-use inquire::{Select, Text};
+use inquire::{Confirm, InquireError, Select, Text};
 
-use dudo::{DudoEvent, event_systems::process_events, resources::GamePhase, setup_game};
-use rand::random_range;
+use dudo::{
+    DudoEvent,
+    bid::Bid,
+    bot::Difficulty,
+    cfr::{CfrConfig, CfrTrainer},
+    chat::ChatCommand,
+    controller::{PlayerController, TurnAction},
+    dice::Hand,
+    event_systems::process_events,
+    events::emit,
+    fair_dice::{Mismatch, RoundCommitments, verify_round},
+    game_log::{EventLog, LogEntry, attach_event_log},
+    net::client::Client,
+    net::lobby::{TableSettings, TableSummary},
+    net::protocol::{ClientMessage, ServerMessage},
+    net::server::{Server, ServerConfig},
+    player::{Controller, Gamertag},
+    probability::BidOdds,
+    profiles::{PlayerDb, Profile, attach_tally},
+    replay::Replay,
+    resources::{
+        ChallengeKind, ChallengeOutcome, DudoRules, GameMetadata, GamePhase, GameState,
+        TableOptions, TimeoutAction, TurnOrder,
+    },
+    save::{load_game, save_game},
+    setup_game,
+    simulate::{StrategyCache, Tournament, bot_for},
+    systems::turn_timer::{TurnTimerSystem, WARNING_SECS, revealed_hands},
+    tui,
+    view::{PlayerView, PublicSeat, SpectatorView},
+};
 
-fn main() -> Result<()> {
+enum PlayerAction {
+    InspectDice,
+    Hint,
+    TableReads,
+    MakeBid { quantity: u8, face: u8 },
+    CallBluff,
+    CallCalza,
+    Chat,
+    BackToMenu,
+}
+
+const DEFAULT_SAVE_PATH: &str = "dudo-save.json";
+const DEFAULT_CFR_PATH: &str = "dudo-cfr.json";
+const DEFAULT_PROFILES_PATH: &str = "dudo-players.json";
+
+/// Exit codes beyond clap's own 2 for bad arguments, following sysexits.h.
+mod exit {
+    pub const FAILURE: u8 = 1;
+    pub const DATA_ERR: u8 = 65;
+    pub const NO_INPUT: u8 = 66;
+    pub const UNAVAILABLE: u8 = 69;
+    pub const INTERRUPTED: u8 = 130;
+}
+
+#[derive(Parser)]
+#[command(
+    name = "dudo",
+    about = "Dudo (Liar's Dice) in the terminal",
+    after_help = "Exit codes: 0 success, 1 failure, 2 bad arguments, 65 invalid save or log file, \
+                  66 file not found, 69 server unavailable, 130 cancelled."
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Start a game, skipping the setup menus when seats are given
+    Play(PlayArgs),
+    /// Print the rules
+    Rules,
+    /// Step through a recorded game log
+    Replay {
+        path: String,
+        /// Print every step instead of stepping interactively
+        #[arg(long)]
+        print: bool,
+    },
+    /// Play bot-only games and report how each bot did
+    Simulate {
+        #[arg(long, default_value_t = 100)]
+        games: u32,
+        /// Comma-separated bots: easy, medium, hard, bayes or cfr:<path>
+        #[arg(long, value_delimiter = ',', required = true)]
+        bots: Vec<Controller>,
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Rules preset: perudo or classic
+        #[arg(long, default_value = "perudo")]
+        rules: DudoRules,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Train a heads-up CFR strategy table
+    TrainCfr {
+        /// Dice per player, e.g. 2v1
+        #[arg(long)]
+        dice: String,
+        #[arg(long, default_value_t = 100_000)]
+        iterations: u64,
+        #[arg(long, default_value = DEFAULT_CFR_PATH)]
+        out: String,
+    },
+    /// Host a game over the network
+    Serve {
+        #[arg(long, default_value = "0.0.0.0:7777")]
+        addr: String,
+        /// Seats for players joining over the network
+        #[arg(long, default_value_t = 2)]
+        seats: usize,
+        /// Comma-separated bots seated after the players
+        #[arg(long, value_delimiter = ',')]
+        bots: Vec<Controller>,
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Rules preset: perudo or classic
+        #[arg(long, default_value = "perudo")]
+        rules: DudoRules,
+        /// Seconds each player has to act (default: no limit)
+        #[arg(long)]
+        turn_seconds: Option<u32>,
+        /// What happens when time runs out: raise, dudo or lose-die
+        #[arg(long, default_value = "raise")]
+        on_timeout: TimeoutAction,
+        /// Password for the server's table
+        #[arg(long)]
+        password: Option<String>,
+        /// Let spectators see every hand this many seconds late
+        #[arg(long)]
+        omniscient_delay: Option<u64>,
+        /// Deal from seeds the players commit to, so they can verify their dice
+        #[arg(long)]
+        verified_dice: bool,
+        /// Also serve the web client and WebSocket connections here, e.g. 0.0.0.0:8080
+        #[arg(long)]
+        web: Option<String>,
+        /// Shut down after the first game instead of reopening the table
+        #[arg(long)]
+        once: bool,
+        /// Seconds a dropped player's seat is held before they forfeit
+        #[arg(long, default_value_t = 60)]
+        grace: u64,
+        /// Bot that plays for dropped players meanwhile; without one the game pauses on their turn
+        #[arg(long)]
+        stand_in: Option<Controller>,
+    },
+    /// Join a game over the network
+    Join {
+        /// Server address, e.g. 192.168.1.20:7777
+        addr: String,
+        #[arg(long)]
+        name: Option<String>,
+        /// Sit at the open table with this name
+        #[arg(long, conflicts_with = "create")]
+        table: Option<String>,
+        /// Open a new table with this name and host it
+        #[arg(long)]
+        create: Option<String>,
+        /// Password for the table being joined or created
+        #[arg(long)]
+        password: Option<String>,
+        /// Most players a created table seats
+        #[arg(long, default_value_t = 6, requires = "create")]
+        max: usize,
+        /// Comma-separated bots for a created table: easy, medium, hard or bayes
+        #[arg(long, value_delimiter = ',', requires = "create")]
+        bots: Vec<Controller>,
+        /// Rules preset for a created table: perudo or classic
+        #[arg(long, default_value = "perudo", requires = "create")]
+        rules: DudoRules,
+        /// Seconds each player at a created table has to act
+        #[arg(long, requires = "create")]
+        turn_seconds: Option<u32>,
+        /// What happens when time runs out: raise, dudo or lose-die
+        #[arg(long, default_value = "raise", requires = "create")]
+        on_timeout: TimeoutAction,
+        /// Let spectators of a created table see every hand this many seconds late
+        #[arg(long, requires = "create")]
+        omniscient_delay: Option<u64>,
+        /// Deal a created table's dice from seeds the players commit to
+        #[arg(long, requires = "create")]
+        verified_dice: bool,
+    },
+    /// Watch a game over the network without playing
+    Watch {
+        /// Server address, e.g. 192.168.1.20:7777
+        addr: String,
+        #[arg(long, default_value = "Spectator")]
+        name: String,
+        /// Watch the table with this name
+        #[arg(long)]
+        table: Option<String>,
+        /// Password for the table
+        #[arg(long)]
+        password: Option<String>,
+    },
+}
+
+#[derive(Args)]
+struct PlayArgs {
+    /// Comma-separated names of human players
+    #[arg(long, value_delimiter = ',')]
+    players: Vec<String>,
+    /// Number of bots to seat after the players
+    #[arg(long, default_value_t = 0)]
+    bots: usize,
+    /// Which bot fills the bot seats: easy, medium, hard, bayes or cfr:<path>
+    #[arg(long, default_value = "medium")]
+    bot_level: Controller,
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Rules preset: perudo or classic
+    #[arg(long, default_value = "perudo")]
+    rules: DudoRules,
+    /// Seconds each player has to act (default: no limit)
+    #[arg(long)]
+    turn_seconds: Option<u32>,
+    /// What happens when time runs out: raise, dudo or lose-die
+    #[arg(long, default_value = "raise")]
+    on_timeout: TimeoutAction,
+    /// Where to record the game log (default dudo-<seed>.jsonl)
+    #[arg(long)]
+    log: Option<String>,
+    /// Keep the table chat in the game log
+    #[arg(long)]
+    record_chat: bool,
+    /// Where player profiles and ratings are kept
+    #[arg(long, default_value = DEFAULT_PROFILES_PATH)]
+    profiles: String,
+    /// Play in the full-screen terminal UI
+    #[arg(long)]
+    tui: bool,
+    /// Hide each hand until its owner has the device, for sharing one screen
+    #[arg(long)]
+    hot_seat: bool,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("{} {err:#}", "error:".red().bold());
+            ExitCode::from(exit_code_for(&err))
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<ExitCode> {
+    match cli.command {
+        None => menu_loop(None)?,
+        Some(Command::Play(args)) => play(args)?,
+        Some(Command::Rules) => show_rules(),
+        Some(Command::Replay { path, print }) => replay(&path, print)?,
+        Some(Command::Simulate {
+            games,
+            bots,
+            seed,
+            rules,
+            json,
+        }) => simulate(games, bots, seed.unwrap_or_else(rand::random), rules, json)?,
+        Some(Command::TrainCfr {
+            dice,
+            iterations,
+            out,
+        }) => train_cfr(&dice, iterations, &out)?,
+        Some(Command::Serve {
+            addr,
+            seats,
+            bots,
+            seed,
+            rules,
+            turn_seconds,
+            on_timeout,
+            password,
+            omniscient_delay,
+            verified_dice,
+            web,
+            once,
+            grace,
+            stand_in,
+        }) => {
+            let mut table = TableSettings::new("Main");
+            table.min_players = seats;
+            table.max_players = seats;
+            table.bots = bots;
+            table.rules = DudoRules {
+                turn_seconds,
+                on_timeout,
+                ..rules
+            };
+            table.password = password;
+            table.omniscient_delay = omniscient_delay;
+            table.verified_dice = verified_dice;
+            let mut config = ServerConfig::new(seed.unwrap_or_else(rand::random));
+            config.tables.push(table);
+            config.single_game = once;
+            config.reconnect.grace = std::time::Duration::from_secs(grace);
+            config.reconnect.stand_in = stand_in;
+            serve(&addr, web.as_deref(), config)?;
+        }
+        Some(Command::Join {
+            addr,
+            name,
+            table,
+            create,
+            password,
+            max,
+            bots,
+            rules,
+            turn_seconds,
+            on_timeout,
+            omniscient_delay,
+            verified_dice,
+        }) => {
+            let choice = match create {
+                Some(name) => {
+                    let mut settings = TableSettings::new(name);
+                    settings.max_players = max;
+                    settings.min_players = settings.min_players.min(max);
+                    settings.bots = bots;
+                    settings.rules = DudoRules {
+                        turn_seconds,
+                        on_timeout,
+                        ..rules
+                    };
+                    settings.password = password.clone();
+                    settings.omniscient_delay = omniscient_delay;
+                    settings.verified_dice = verified_dice;
+                    TableChoice::Create(settings)
+                }
+                None => TableChoice::Join(table),
+            };
+            join(&addr, name, choice, password)?;
+        }
+        Some(Command::Watch {
+            addr,
+            name,
+            table,
+            password,
+        }) => watch(&addr, &name, table.as_deref(), password)?,
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn exit_code_for(err: &anyhow::Error) -> u8 {
+    for cause in err.chain() {
+        if let Some(InquireError::OperationCanceled | InquireError::OperationInterrupted) =
+            cause.downcast_ref()
+        {
+            return exit::INTERRUPTED;
+        }
+        if let Some(io) = cause.downcast_ref::<io::Error>() {
+            match io.kind() {
+                io::ErrorKind::NotFound => return exit::NO_INPUT,
+                io::ErrorKind::ConnectionRefused => return exit::UNAVAILABLE,
+                _ => {}
+            }
+        }
+        if cause.is::<serde_json::Error>() {
+            return exit::DATA_ERR;
+        }
+    }
+    exit::FAILURE
+}
+
+fn menu_loop(mut suspended: Option<World>) -> Result<()> {
     loop {
         show_title();
-        if !main_menu()? {
-            break;
+        if !main_menu(&mut suspended)? {
+            return Ok(());
         }
     }
+}
 
-    Ok(())
+fn play(args: PlayArgs) -> Result<()> {
+    let rules = DudoRules {
+        turn_seconds: args.turn_seconds,
+        on_timeout: args.on_timeout,
+        ..args.rules
+    };
+    let seats = args.players.len() + args.bots;
+    if seats == 0 {
+        let players = get_player_names()?;
+        let options = TableOptions {
+            hot_seat: args.hot_seat || ask_hot_seat(&players)?,
+            tui: args.tui,
+        };
+        let world = new_game(
+            players,
+            rules,
+            options,
+            args.seed,
+            args.log,
+            args.record_chat,
+            &args.profiles,
+        )?;
+        return menu_loop(run_game(world)?);
+    }
+    if !(2..=6).contains(&seats) {
+        let mut command = Cli::command();
+        command
+            .find_subcommand_mut("play")
+            .expect("play is a subcommand")
+            .error(
+                ErrorKind::ValueValidation,
+                format!("a game needs 2-6 seats, got {seats}"),
+            )
+            .exit();
+    }
+
+    let mut players: Vec<_> = args
+        .players
+        .into_iter()
+        .map(|name| (name, Controller::Human))
+        .collect();
+    for i in 0..args.bots {
+        players.push((
+            format!("Bot {} ({})", i + 1, args.bot_level),
+            args.bot_level.clone(),
+        ));
+    }
+    let options = TableOptions {
+        hot_seat: args.hot_seat,
+        tui: args.tui,
+    };
+    let world = new_game(
+        players,
+        rules,
+        options,
+        args.seed,
+        args.log,
+        args.record_chat,
+        &args.profiles,
+    )?;
+    match run_game(world)? {
+        Some(world) => menu_loop(Some(world)),
+        None => Ok(()),
+    }
+}
+
+/// Plays in whichever front end the game was started with.
+fn run_game(world: World) -> Result<Option<World>> {
+    if world.resource::<TableOptions>()?.tui {
+        tui::run(world)
+    } else {
+        game_loop(world)
+    }
 }
 
-fn main_menu() -> Result<bool> {
-    let menu = vec!["Start", "Rules", "Quit"];
+/// `suspended` holds a game the players left through "Back to Menu".
+fn main_menu(suspended: &mut Option<World>) -> Result<bool> {
+    let mut menu = Vec::new();
+    if suspended.is_some() {
+        menu.extend(["Resume", "Save game"]);
+    }
+    menu.extend(["Start", "Load game", "Stats", "Rules", "Quit"]);
     let menu_choice = Select::new("Main Menu", menu).prompt()?;
 
     match menu_choice {
+        "Resume" => {
+            if let Some(world) = suspended.take() {
+                *suspended = run_game(world)?;
+            }
+            Ok(true)
+        }
+        "Save game" => {
+            if let Some(world) = suspended {
+                let path = Text::new("Save to:")
+                    .with_default(DEFAULT_SAVE_PATH)
+                    .prompt()?;
+                save_game(world, &path)?;
+                println!("{}", format!("💾 Game saved to {path}").bright_green());
+            }
+            Ok(true)
+        }
         "Start" => {
-            game_loop()?;
+            let players = get_player_names()?;
+            let options = TableOptions {
+                hot_seat: ask_hot_seat(&players)?,
+                ..TableOptions::default()
+            };
+            let world = new_game(
+                players,
+                DudoRules::perudo(),
+                options,
+                None,
+                None,
+                false,
+                DEFAULT_PROFILES_PATH,
+            )?;
+            *suspended = run_game(world)?;
+            Ok(true)
+        }
+        "Load game" => {
+            let path = Text::new("Load from:")
+                .with_default(DEFAULT_SAVE_PATH)
+                .prompt()?;
+            match load_game(&path) {
+                Ok(mut world) => {
+                    attach_tally(&mut world, PlayerDb::open(DEFAULT_PROFILES_PATH)?)?;
+                    println!("{}", format!("📂 Loaded {path}").bright_green());
+                    *suspended = run_game(world)?;
+                }
+                Err(err) => println!("{}", format!("Could not load game: {err:#}").red()),
+            }
+            Ok(true)
+        }
+        "Stats" => {
+            show_stats(DEFAULT_PROFILES_PATH)?;
             Ok(true)
         }
         "Rules" => {
-            show_rules()?;
+            show_rules();
+            println!("\n{}", "Press Enter to return...".dimmed());
+            Text::new("").prompt()?;
             Ok(true)
         }
         "Quit" => {
@@ -43,182 +540,591 @@ fn main_menu() -> Result<bool> {
     }
 }
 
-fn game_loop() -> Result<()> {
-    let players = get_player_names()?;
-    let mut world = setup_game(players)?;
+fn new_game(
+    players: Vec<(String, Controller)>,
+    rules: DudoRules,
+    options: TableOptions,
+    seed: Option<u64>,
+    log_path: Option<String>,
+    record_chat: bool,
+    profiles_path: &str,
+) -> Result<World> {
+    let seed = seed.unwrap_or_else(rand::random);
+    let names = players.iter().map(|(name, _)| name.clone()).collect();
+    let mut world = setup_game(names, rules, seed)?;
+    world.insert_resource(options);
+    let entities = world.resource::<TurnOrder>()?.players.clone();
+    for (entity, (_, controller)) in entities.into_iter().zip(players) {
+        world.insert_component(entity, controller)?;
+    }
+    let log_path = log_path.unwrap_or_else(|| format!("dudo-{seed}.jsonl"));
+    let mut log = EventLog::create(&log_path)?;
+    if record_chat {
+        log = log.with_chat();
+    }
+    attach_event_log(&mut world, log)?;
+    attach_tally(&mut world, PlayerDb::open(profiles_path)?)?;
+    emit(&mut world, DudoEvent::GameReady)?;
+    process_events(&mut world)?;
+
+    let starting_player = world.resource::<TurnOrder>()?.current_player();
+    let starting_name = &world.component::<Gamertag>(starting_player)?.name;
+    println!("{}", format!("🌱 Game seed: {seed}").dimmed());
+    println!("{}", format!("📝 Recording to {log_path}").dimmed());
+    println!("{}", format!("🎯 {} starts!", starting_name).bright_green());
+    Ok(world)
+}
 
+/// Shows the leaderboard, then any profile the player picks from it.
+fn show_stats(path: &str) -> Result<()> {
+    let db = PlayerDb::open(path)?;
+    let leaderboard = db.leaderboard();
+    println!("\n{}", "🏅 Leaderboard".bright_yellow().bold());
+    if leaderboard.is_empty() {
+        println!("{}", "No games recorded yet.".dimmed());
+        println!("\n{}", "Press Enter to return...".dimmed());
+        Text::new("").prompt()?;
+        return Ok(());
+    }
+    println!(
+        "{}",
+        format!(
+            "{:>4}  {:<24} {:>6} {:>6} {:>5}",
+            "#", "Player", "Rating", "Games", "Wins"
+        )
+        .dimmed()
+    );
+    for (rank, profile) in leaderboard.iter().enumerate() {
+        println!(
+            "{:>4}  {:<24} {:>6.0} {:>6} {:>5}",
+            rank + 1,
+            profile.name,
+            profile.rating,
+            profile.games_played,
+            profile.wins
+        );
+    }
+
+    const BACK: &str = "Back";
+    let mut choices: Vec<&str> = leaderboard.iter().map(|p| p.name.as_str()).collect();
+    choices.push(BACK);
+    loop {
+        let choice = Select::new("View a profile:", choices.clone()).prompt()?;
+        match db.profile(choice) {
+            Some(profile) if choice != BACK => show_profile(profile),
+            _ => return Ok(()),
+        }
+    }
+}
+
+fn show_profile(profile: &Profile) {
+    println!("\n{}", format!("👤 {}", profile.name).bright_cyan().bold());
+    println!("  Rating:                {:.0}", profile.rating);
+    println!("  Games played:          {}", profile.games_played);
+    println!(
+        "  Wins:                  {} ({:.0}%)",
+        profile.wins,
+        profile.win_rate() * 100.0
+    );
+    println!("  Bluffs called:         {}", profile.bluffs_called);
+    println!("  Successful challenges: {}", profile.successful_challenges);
+    println!(
+        "  Dice lost per round:   {:.2}",
+        profile.average_dice_lost()
+    );
+}
+
+/// Plays until the game ends, or hands the world back if the players pause.
+fn game_loop(mut world: World) -> Result<Option<World>> {
+    let mut controllers = build_controllers(&world)?;
     loop {
         let phase = world.resource::<GameState>()?.phase;
-        if phase == GamePhase::RoundStart {
-            println!("\n{}", "🎲 Rolling dice...".bright_yellow());
-            emit(world, DudoEvent::RollDice)?;
+        match phase {
+            GamePhase::RoundStart => {
+                println!("\n{}", "🎲 Rolling dice...".bright_yellow());
+                emit(&mut world, DudoEvent::RollDice)?;
+                announce_palifico(&world)?;
+            }
+            GamePhase::Bidding => {
+                if !play_turn(&mut world, &mut controllers)? {
+                    return Ok(Some(world));
+                }
+            }
+            GamePhase::GameOver => {
+                let winner = world.resource::<TurnOrder>()?.current_player();
+                let gamertag = world.component::<Gamertag>(winner)?;
+                println!(
+                    "\n{}",
+                    format!("🏆 {} wins the game!", gamertag.name)
+                        .bright_green()
+                        .bold()
+                );
+                return Ok(None);
+            }
+            GamePhase::Challenge | GamePhase::RoundEnd => {}
         }
 
-        process_events(&mut world);
+        process_events(&mut world)?;
     }
+}
+
+/// Returns `false` when the player heads back to the main menu.
+fn play_turn(world: &mut World, controllers: &mut Controllers) -> Result<bool> {
+    let player = world.resource::<TurnOrder>()?.current_player();
+    let name = world.component::<Gamertag>(player)?.name.clone();
+    let game_state = world.resource::<GameState>()?;
 
-    world.insert_resource(BidHistory { bids: Vec::new() })?;
-    let starting_idx = random_range(0..players.len());
-    let starting_name = world.get_component::<Gamertag>(players[starting_idx])?;
     println!(
-        "{}",
-        format!("🎯 {} starts!", starting_name.name).bright_green()
+        "\n{}",
+        format!("─── {}'s Turn ───", name).bright_green().bold()
     );
+    if let Some(bid) = &game_state.current_bid {
+        println!("Current bid: {} dice showing {}", bid.quantity, bid.face);
+        if game_state.is_palifico() {
+            println!(
+                "{}",
+                format!("🔒 Face is locked to {}", bid.face).bright_magenta()
+            );
+        }
+    }
+
+    let rules = *world.resource::<DudoRules>()?;
 
-    // game_loop(&mut world, &mut players, starting_idx)?;
+    let controller = controllers
+        .get_mut(&player)
+        .ok_or_else(|| anyhow::anyhow!("{name} has no controller"))?;
+    // Start the turn's clock, then charge it for however long the choice took.
+    TurnTimerSystem::run(world, 0.0)?;
+    let started = Instant::now();
+    let Some(action) = controller.choose_action(&PlayerView::for_player(world, player)?)? else {
+        return Ok(false);
+    };
+    let timer = TurnTimerSystem::run(world, started.elapsed().as_secs_f64())?;
+    if let Some(event @ DudoEvent::TurnWarning { .. }) = &timer {
+        emit(world, event.clone())?;
+        process_events(world)?;
+    }
+    if let Some(event @ DudoEvent::TurnTimedOut { .. }) = timer {
+        let game_state = world.resource::<GameState>()?;
+        let round = game_state.round;
+        let fallback = match rules.on_timeout {
+            TimeoutAction::LoseDie => "loses a die",
+            TimeoutAction::CallDudo if game_state.current_bid.is_some() => "calls Dudo",
+            _ if game_state.minimum_raise(player, &rules).is_none() => "calls Dudo",
+            TimeoutAction::MinimumRaise | TimeoutAction::CallDudo => "makes the minimum raise",
+        };
+        println!(
+            "{}",
+            format!("⌛ Time's up! {name} answered too late and {fallback}.").bright_red()
+        );
+        emit(world, event.clone())?;
+        process_events(world)?;
+        if revealed_hands(world, &event, round)? {
+            show_challenge(world)?;
+        }
+        return Ok(true);
+    }
+
+    match action {
+        TurnAction::Bid { quantity, face } => println!(
+            "{}",
+            format!("✅ {} bids {} × {}", name, quantity, face).green()
+        ),
+        TurnAction::Dudo => println!("{}", format!("🗣️  {} calls Dudo!", name).bright_red()),
+        TurnAction::Calza => println!("{}", format!("🗣️  {} calls Calza!", name).bright_cyan()),
+    }
+    emit(world, action.into_event(player))?;
+    process_events(world)?;
+    if !matches!(action, TurnAction::Bid { .. }) {
+        show_challenge(world)?;
+    }
+
+    Ok(true)
+}
+
+type Controllers = HashMap<Entity, Box<dyn PlayerController>>;
+
+/// Builds one controller per player, kept for the whole session so bots can
+/// carry state between turns.
+fn build_controllers(world: &World) -> Result<Controllers> {
+    let seed = world.resource::<GameMetadata>()?.seed;
+    let hot_seat = world.resource::<TableOptions>()?.hot_seat;
+    let mut controllers = Controllers::new();
+    let mut strategies = StrategyCache::new();
+    for (&player, controller) in world.query_component::<Controller>()? {
+        let built: Box<dyn PlayerController> = match controller {
+            Controller::Human => Box::new(HumanController {
+                hot_seat,
+                chat: false,
+                typed: None,
+                turn_started: None,
+            }),
+            bot => bot_for(bot, seed.wrapping_add(player.id), &mut strategies)?,
+        };
+        controllers.insert(player, built);
+    }
+    Ok(controllers)
+}
+
+/// Takes turns through the terminal menus.
+struct HumanController {
+    /// Hide the screen between turns so players sharing it can't peek.
+    hot_seat: bool,
+    /// Offer the table chat; a line typed into it hands the turn back
+    /// unplayed, waiting in `typed`.
+    chat: bool,
+    typed: Option<ChatCommand>,
+    /// When the turn being chosen began, to show what is left of a timed
+    /// turn. A chat line keeps the clock running.
+    turn_started: Option<Instant>,
+}
+
+impl PlayerController for HumanController {
+    fn choose_action(&mut self, view: &PlayerView) -> Result<Option<TurnAction>> {
+        let game_state = &view.game_state;
+        let rules = &view.rules;
+        let started = *self.turn_started.get_or_insert_with(Instant::now);
+        if self.hot_seat {
+            hand_over(view)?;
+        }
+
+        loop {
+            if let Some(limit) = rules.turn_seconds {
+                show_time_left(limit, started);
+            }
+            let has_bid = game_state.current_bid.is_some();
+            let action = match get_player_action(has_bid, rules.calza, self.chat)? {
+                PlayerAction::InspectDice => {
+                    println!("{}", view.hand);
+                    continue;
+                }
+                PlayerAction::Hint => {
+                    show_hint(view);
+                    continue;
+                }
+                PlayerAction::TableReads => {
+                    show_table_reads(view);
+                    continue;
+                }
+                PlayerAction::MakeBid { quantity, face } => {
+                    let bid = Bid::new(view.player, quantity, face);
+                    if game_state.is_valid_next_bid(&bid, rules) {
+                        Some(TurnAction::Bid { quantity, face })
+                    } else {
+                        let message = if game_state.is_palifico() {
+                            "Palifico: raise the quantity and keep the face!"
+                        } else {
+                            "Bid must be higher!"
+                        };
+                        println!("{}", message.red());
+                        continue;
+                    }
+                }
+                PlayerAction::CallBluff => Some(TurnAction::Dudo),
+                PlayerAction::CallCalza => Some(TurnAction::Calza),
+                PlayerAction::Chat => {
+                    let line = Text::new("Say (/mute NAME, /unmute NAME or /EMOTE):").prompt()?;
+                    match line.parse::<ChatCommand>() {
+                        Ok(command) => {
+                            self.typed = Some(command);
+                            return Ok(None);
+                        }
+                        Err(err) => {
+                            println!("{}", format!("{err:#}").red());
+                            continue;
+                        }
+                    }
+                }
+                PlayerAction::BackToMenu => None,
+            };
+            self.turn_started = None;
+            if self.hot_seat {
+                clear_screen();
+            }
+            return Ok(action);
+        }
+    }
+}
+
+/// The clock is only checked once an answer is in, so the player is shown
+/// what is left each time the menu comes round.
+fn show_time_left(limit: u32, started: Instant) {
+    let left = limit as f64 - started.elapsed().as_secs_f64();
+    if left <= 0.0 {
+        println!(
+            "{}",
+            "⌛ Time's up: your answer will come too late.".bright_red()
+        );
+    } else if left <= WARNING_SECS as f64 {
+        println!(
+            "{}",
+            format!("⏱ {}s left to act!", left.ceil()).bright_red()
+        );
+    } else {
+        println!(
+            "{}",
+            format!("⏱ {}s left to act, counted until you answer", left.ceil()).bright_blue()
+        );
+    }
+}
+
+/// Hot-seat hand-off: blanks the screen until `player` confirms they have
+/// the device, then recaps the public state of the round for them.
+fn hand_over(view: &PlayerView) -> Result<()> {
+    let name = view.name(view.player);
+    clear_screen();
+    println!(
+        "\n{}",
+        format!("🔄 Pass the device to {name}")
+            .bright_yellow()
+            .bold()
+    );
+    Text::new(&format!("{name}, press Enter when nobody else is looking"))
+        .with_default("")
+        .prompt()?;
+    clear_screen();
+
+    println!("{}", format!("─── {name}'s Turn ───").bright_green().bold());
+    let bids = &view.bid_history.bids;
+    if bids.is_empty() {
+        println!("No bids yet this round.");
+    }
+    for bid in bids {
+        let bidder = view.name(bid.player);
+        println!("  {bidder} bid {} × {}", bid.quantity, bid.face);
+    }
+    Ok(())
+}
+
+/// Clears the visible screen and the scrollback, so earlier hands are gone.
+fn clear_screen() {
+    print!("\x1B[2J\x1B[3J\x1B[H");
+    let _ = io::Write::flush(&mut io::stdout());
+}
+
+fn show_hint(view: &PlayerView) {
+    let odds = BidOdds::from_view(view);
+    let game_state = &view.game_state;
+    let rules = &view.rules;
+
+    println!("\n{}", "💡 HINT".bright_yellow().bold());
+    if let Some(bid) = game_state.current_bid {
+        println!(
+            "  Current bid holds: {:.0}%",
+            odds.chance_at_least(bid.quantity, bid.face) * 100.0
+        );
+        if rules.calza {
+            println!(
+                "  Exactly right (calza): {:.0}%",
+                odds.chance_exactly(bid.quantity, bid.face) * 100.0
+            );
+        }
+    }
+    println!("  Safest bids:");
+    for (bid, chance) in odds
+        .ranked_raises(game_state, rules, view.player)
+        .into_iter()
+        .take(3)
+    {
+        println!(
+            "    {} × {} → {:.0}%",
+            bid.quantity,
+            bid.face,
+            chance * 100.0
+        );
+    }
+}
+
+/// What the opponent model has read into each bidder's hand this round.
+fn show_table_reads(view: &PlayerView) {
+    let beliefs = view.beliefs();
+    let wild_ones = view.ones_are_wild();
+
+    println!("\n{}", "🔍 TABLE READS".bright_cyan().bold());
+    println!("  Expected dice counting towards each face:");
+    println!(
+        "  {:<16} {:>5} {:>5} {:>5} {:>5} {:>5} {:>5}",
+        "", "⚀", "⚁", "⚂", "⚃", "⚄", "⚅"
+    );
+    for &player in &view.turn_order.players {
+        let Some(posterior) = beliefs.posterior(player) else {
+            continue;
+        };
+        let name = view.name(player);
+        let expected = posterior.expected_counts(wild_ones);
+        print!("  {name:<16}");
+        for count in expected {
+            print!(" {count:>5.2}");
+        }
+        println!(
+            "  ({} dice, {} bids)",
+            posterior.dice(),
+            posterior.bids_seen()
+        );
+    }
+}
+
+fn announce_palifico(world: &World) -> Result<()> {
+    let Some(player) = world.resource::<GameState>()?.palifico else {
+        return Ok(());
+    };
+    let gamertag = world.component::<Gamertag>(player)?;
+
+    println!(
+        "\n{}",
+        format!("🔒 PALIFICO! {} is down to one die.", gamertag.name)
+            .bright_magenta()
+            .bold()
+    );
+    println!("  • Ones are not wild this round");
+    println!("  • The face of the opening bid cannot be changed");
     Ok(())
 }
 
-// fn game_loop(world: &mut World, players: &mut Vec<Entity>, starting_idx: usize) -> Result<()> {
-//     let mut current_idx = starting_idx;
-//     let mut current_bid: Option<Bid> = None;
-
-//     loop {
-//         let player = players[current_idx];
-//         let gamertag = world.get_component::<Gamertag>(player)?;
-
-//         println!(
-//             "\n{}",
-//             format!("─── {}'s Turn ───", gamertag.name)
-//                 .bright_green()
-//                 .bold()
-//         );
-//         if let Some(bid) = &current_bid {
-//             println!("Current bid: {} dice showing {}", bid.quantity, bid.face);
-//         }
-
-//         let action = get_player_action(current_bid.is_some())?;
-
-//         match action {
-//             PlayerAction::InspectDice => {
-//                 println!("{}", world.get_component::<Hand>(player)?);
-//                 continue;
-//             }
-//             PlayerAction::MakeBid(bid) => {
-//                 if let Some(prev_bid) = &current_bid {
-//                     if !is_higher_bid(&bid, prev_bid) {
-//                         println!("{}", "Bid must be higher!".red());
-//                         continue;
-//                     }
-//                 }
-//                 current_bid = Some(bid);
-//                 println!(
-//                     "{}",
-//                     format!("✅ {} bids {} × {}", gamertag.name, bid.quantity, bid.face).green()
-//                 );
-//             }
-//             PlayerAction::CallBluff => {
-//                 if current_bid.is_none() {
-//                     println!("{}", "No bid to challenge!".red());
-//                     continue;
-//                 }
-//                 resolve_challenge(world, players, &current_bid.unwrap())?;
-//                 break;
-//             }
-//         }
-
-//         current_idx = (current_idx + 1) % players.len();
-//     }
-
-//     Ok(())
-// }
-
-fn get_player_action(has_bid: bool) -> Result<PlayerAction> {
-    let actions = if has_bid {
-        vec!["Inspect Dice", "Raise Bid", "Call Bluff"]
+fn get_player_action(has_bid: bool, calza_allowed: bool, chat: bool) -> Result<PlayerAction> {
+    let mut actions = if has_bid && calza_allowed {
+        vec![
+            "Inspect Dice",
+            "Hint",
+            "Table Reads",
+            "Raise Bid",
+            "Call Bluff",
+            "Call Calza",
+        ]
+    } else if has_bid {
+        vec![
+            "Inspect Dice",
+            "Hint",
+            "Table Reads",
+            "Raise Bid",
+            "Call Bluff",
+        ]
     } else {
-        vec!["Inspect Dice", "Make First Bid"]
+        vec!["Inspect Dice", "Hint", "Make First Bid"]
     };
+    if chat {
+        actions.push("Chat");
+    }
+    actions.push("Back to Menu");
 
     let choice = Select::new("Choose action:", actions).prompt()?;
 
     match choice {
         "Inspect Dice" => Ok(PlayerAction::InspectDice),
+        "Hint" => Ok(PlayerAction::Hint),
+        "Table Reads" => Ok(PlayerAction::TableReads),
         "Raise Bid" | "Make First Bid" => {
-            let bid = get_bid_from_player()?;
-            Ok(PlayerAction::MakeBid(bid))
+            let (quantity, face) = get_bid_from_player()?;
+            Ok(PlayerAction::MakeBid { quantity, face })
         }
         "Call Bluff" => Ok(PlayerAction::CallBluff),
+        "Call Calza" => Ok(PlayerAction::CallCalza),
+        "Chat" => Ok(PlayerAction::Chat),
+        "Back to Menu" => Ok(PlayerAction::BackToMenu),
         _ => Ok(PlayerAction::InspectDice),
     }
 }
 
-fn get_bid_from_player() -> Result<Bid> {
+fn get_bid_from_player() -> Result<(u8, u8)> {
     let quantity = Text::new("How many dice?").prompt()?.parse::<u8>()?;
 
     let face = Text::new("What face value (1-6)?")
         .prompt()?
         .parse::<u8>()?;
 
-    if face < 1 || face > 6 {
-        println!("{}", "Face must be 1-6!".red());
+    if !(1..=6).contains(&face) || quantity == 0 {
+        println!("{}", "Bid at least one die with a face of 1-6!".red());
         return get_bid_from_player();
     }
 
-    Ok(Bid { quantity, face })
+    Ok((quantity, face))
 }
 
-fn is_higher_bid(new_bid: &Bid, prev_bid: &Bid) -> bool {
-    if new_bid.quantity > prev_bid.quantity {
-        return true;
-    }
-    if new_bid.quantity == prev_bid.quantity && new_bid.face > prev_bid.face {
-        return true;
-    }
-    false
-}
-
-fn resolve_challenge(world: &World, players: &[Entity], bid: &Bid) -> Result<()> {
-    println!(
-        "\n{}",
-        "⚔️  CHALLENGE! Revealing all dice...".bright_red().bold()
-    );
-
-    let mut total_count = 0;
-
-    for &player in players {
-        let gamertag = world.get_component::<Gamertag>(player)?;
-        let hand = world.get_component::<Hand>(player)?;
+fn show_challenge(world: &World) -> Result<()> {
+    let Some(outcome) = &world.resource::<GameState>()?.last_challenge else {
+        return Ok(());
+    };
+    let bid = outcome.bid;
 
-        let count = hand
-            .dice
-            .iter()
-            .filter(|d| d.face == Some(bid.face))
-            .count();
+    let headline = match outcome.kind {
+        ChallengeKind::Dudo => "⚔️  CHALLENGE! Revealing all dice...",
+        ChallengeKind::Calza => "🎯 CALZA! Revealing all dice...",
+    };
+    println!("\n{}", headline.bright_red().bold());
 
-        total_count += count;
-        println!(
-            "{}: {} ({} × {})",
-            gamertag.name.yellow(),
-            hand,
-            count,
-            bid.face
-        );
+    for (player, faces) in &outcome.revealed {
+        let gamertag = world.component::<Gamertag>(*player)?;
+        println!("{}: {}", gamertag.name.yellow(), Hand::from_faces(faces));
     }
 
     println!(
         "\n{}",
-        format!("Total: {} dice showing {}", total_count, bid.face)
+        format!("Total: {} dice showing {}", outcome.total, bid.face)
             .bright_cyan()
             .bold()
     );
     println!("Bid was: {} dice showing {}", bid.quantity, bid.face);
 
-    if total_count >= bid.quantity as usize {
-        println!("{}", "✅ Bid was correct! Challenger loses.".bright_green());
-        // TODO: Remove die from challenger
-    } else {
-        println!("{}", "❌ Bid was too high! Bidder loses.".bright_red());
-        // TODO: Remove die from previous bidder
+    let caller = &world.component::<Gamertag>(outcome.challenger)?.name;
+    match (outcome.kind, outcome.loser) {
+        (ChallengeKind::Calza, None) => println!(
+            "{}",
+            format!("🎯 Exactly right! {caller} regains a die.").bright_green()
+        ),
+        (ChallengeKind::Calza, Some(_)) => println!(
+            "{}",
+            format!("❌ Not exact! {caller} loses a die.").bright_red()
+        ),
+        (ChallengeKind::Dudo, Some(loser)) if loser == outcome.challenger => println!(
+            "{}",
+            format!("✅ Bid was correct! {caller} loses a die.").bright_green()
+        ),
+        (ChallengeKind::Dudo, _) => println!(
+            "{}",
+            format!(
+                "❌ Bid was too high! {} loses a die.",
+                world.component::<Gamertag>(outcome.bid.player)?.name
+            )
+            .bright_red()
+        ),
+    }
+    if let Some(loser) = outcome.loser
+        && world.component::<Hand>(loser)?.dice.is_empty()
+    {
+        let loser = &world.component::<Gamertag>(loser)?.name;
+        println!("{}", format!("💀 {loser} is out of dice!").red());
     }
 
-    println!("\n{}", "Press Enter to continue...".dimmed());
-    Text::new("").prompt()?;
+    // Bot-only tables run straight through, e.g. for scripted demos.
+    if world
+        .query_component::<Controller>()?
+        .values()
+        .any(|controller| *controller == Controller::Human)
+    {
+        println!("\n{}", "Press Enter to continue...".dimmed());
+        Text::new("").prompt()?;
+    }
 
     Ok(())
 }
 
-fn add_players(world: &mut World, players: &mut Vec<Entity>) -> Result<()> {
+/// Offers hot-seat privacy when more than one person shares the screen.
+fn ask_hot_seat(players: &[(String, Controller)]) -> Result<bool> {
+    let humans = players
+        .iter()
+        .filter(|(_, controller)| *controller == Controller::Human)
+        .count();
+    if humans < 2 {
+        return Ok(false);
+    }
+    Ok(
+        Confirm::new("Hot-seat mode (hide each hand between turns)?")
+            .with_default(true)
+            .prompt()?,
+    )
+}
+
+fn get_player_names() -> Result<Vec<(String, Controller)>> {
     let player_count = Text::new("How many players (2-6)?")
         .with_default("3")
         .prompt()?
@@ -226,50 +1132,185 @@ fn add_players(world: &mut World, players: &mut Vec<Entity>) -> Result<()> {
 
     if !(2..7).contains(&player_count) {
         println!("{}", "Must be 2-6 players!".red());
-        return play_game();
+        return get_player_names(); // Retry on invalid input
     }
+
+    let mut players = Vec::new();
     for i in 0..player_count {
+        let seat = Select::new(
+            &format!("Seat {}:", i + 1),
+            vec!["Add player", "Add bot", "Add Bayesian bot", "Add CFR bot"],
+        )
+        .prompt()?;
+        if seat == "Add Bayesian bot" {
+            players.push((format!("Bot {} (Bayes)", i + 1), Controller::Bayes));
+            continue;
+        }
+        if seat == "Add CFR bot" {
+            let strategy = Text::new("Strategy table:")
+                .with_default(DEFAULT_CFR_PATH)
+                .prompt()?;
+            players.push((format!("Bot {} (CFR)", i + 1), Controller::Cfr { strategy }));
+            continue;
+        }
+        if seat == "Add bot" {
+            let difficulty = Select::new("Bot difficulty:", Difficulty::ALL.to_vec()).prompt()?;
+            players.push((
+                format!("Bot {} ({difficulty})", i + 1),
+                Controller::Bot(difficulty),
+            ));
+            continue;
+        }
+
         let name = Text::new(&format!("Player {} name:", i + 1))
-            .with_default(&format!("Player{}:", i + 1))
+            .with_default(&format!("Player {}", i + 1))
             .prompt()?;
-
-        let player = world
-            .spawn()
-            .with(Player)?
-            .with(Gamertag::new(name))?
-            .with(Hand::new())?
-            .build();
-
-        players.push(player);
+        players.push((name, Controller::Human));
     }
 
     println!("\n{}", "✅ All players added!".bright_green());
+    Ok(players)
+}
 
+fn train_cfr(dice: &str, iterations: u64, path: &str) -> Result<()> {
+    let (first, second) = dice
+        .split_once('v')
+        .ok_or_else(|| anyhow::anyhow!("dice must look like 1v1 or 2v1"))?;
+    let config = CfrConfig::new([first.parse()?, second.parse()?], DudoRules::perudo())?;
+
+    println!(
+        "{}",
+        format!("🧠 Training {dice} for {iterations} iterations...").bright_cyan()
+    );
+    let mut trainer = CfrTrainer::new(config, rand::random());
+    trainer.train(iterations);
+    let table = trainer.strategy();
+    table.save(path)?;
+    println!(
+        "{}",
+        format!(
+            "✅ Saved {} information sets to {path}",
+            table.infosets.len()
+        )
+        .bright_green()
+    );
     Ok(())
 }
 
-// This should be in main.rs (UI/input gathering)
-fn get_player_names() -> Result<Vec<String>> {
-    let player_count = Text::new("How many players (2-6)?")
-        .with_default("3")
-        .prompt()?
-        .parse::<usize>()?;
+fn simulate(
+    games: u32,
+    entrants: Vec<Controller>,
+    seed: u64,
+    rules: DudoRules,
+    json: bool,
+) -> Result<()> {
+    let report = Tournament::new(entrants, games, seed, rules)?.run()?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{report}");
+    }
+    Ok(())
+}
 
-    if !(2..7).contains(&player_count) {
-        println!("{}", "Must be 2-6 players!".red());
-        return get_player_names(); // Retry on invalid input
+fn replay(path: &str, print: bool) -> Result<()> {
+    let mut replay = Replay::open(path)?;
+    println!("\n{}", format!("📼 Replaying {path}").bright_cyan().bold());
+
+    if print {
+        loop {
+            show_replay_step(&replay, &replay.world()?)?;
+            if !replay.step_forward() {
+                return Ok(());
+            }
+        }
     }
 
-    let mut names = Vec::new();
-    for i in 0..player_count {
-        let name = Text::new(&format!("Player {} name:", i + 1))
-            .with_default(&format!("Player {}", i + 1))
-            .prompt()?;
-        names.push(name);
+    loop {
+        let world = replay.world()?;
+        show_replay_step(&replay, &world)?;
+
+        let choice = Select::new("Replay:", vec!["Forward", "Back", "Quit"]).prompt()?;
+        match choice {
+            "Forward" => {
+                if !replay.step_forward() {
+                    println!("{}", "End of the log.".dimmed());
+                }
+            }
+            "Back" => {
+                if !replay.step_back() {
+                    println!("{}", "Start of the log.".dimmed());
+                }
+            }
+            _ => return Ok(()),
+        }
     }
+}
 
-    println!("\n{}", "✅ All players added!".bright_green());
-    Ok(names)
+fn show_replay_step(replay: &Replay, world: &World) -> Result<()> {
+    let name = |player| -> Result<String> { Ok(world.component::<Gamertag>(player)?.name.clone()) };
+
+    println!(
+        "\n{}",
+        format!("─── Step {}/{} ───", replay.position(), replay.len()).bright_green()
+    );
+    match replay.current_entry() {
+        LogEntry::GameStarted { players, seed, .. } => {
+            println!("Game started: {} (seed {seed})", players.join(", "));
+        }
+        LogEntry::Event { event, .. } => match event {
+            DudoEvent::BidMade {
+                player,
+                quantity,
+                face,
+            } => println!("{} bids {quantity} × {face}", name(*player)?),
+            DudoEvent::ChallengeMade { challenger } => {
+                println!("{} calls Dudo!", name(*challenger)?)
+            }
+            DudoEvent::CalzaCalled { caller } => println!("{} calls Calza!", name(*caller)?),
+            DudoEvent::GameReady => println!("Players are ready"),
+            DudoEvent::RollDice => println!("Dice are rolled"),
+            DudoEvent::PlayerForfeited { player } => println!("{} forfeits", name(*player)?),
+            DudoEvent::TurnWarning {
+                player,
+                seconds_left,
+            } => println!("{} has {seconds_left}s left", name(*player)?),
+            DudoEvent::TurnTimedOut { player } => println!("{} runs out of time", name(*player)?),
+            DudoEvent::DealerCommitted { commitment } => {
+                println!("The dealer commits to seed {commitment}")
+            }
+            DudoEvent::SeedCommitted { player, commitment } => {
+                println!("{} commits to seed {commitment}", name(*player)?)
+            }
+        },
+        LogEntry::HandsRolled { round, .. } => println!("Round {round} hands dealt"),
+        LogEntry::ChallengeResolved(outcome) => {
+            println!(
+                "{} dice showing {} (bid was {})",
+                outcome.total, outcome.bid.face, outcome.bid.quantity
+            );
+            if outcome.proof.is_some() {
+                report_verification(world.resource::<RoundCommitments>()?, outcome, |player| {
+                    name(player).unwrap_or_else(|_| "?".to_string())
+                });
+            }
+        }
+        LogEntry::Chat { message, .. } => println!("💬 {message}"),
+    }
+
+    let game_state = world.resource::<GameState>()?;
+    println!("Round {} · {:?}", game_state.round, game_state.phase);
+    if let Some(bid) = game_state.current_bid {
+        println!("Current bid: {} dice showing {}", bid.quantity, bid.face);
+    }
+    for &player in &world.resource::<TurnOrder>()?.players {
+        println!(
+            "  {}: {}",
+            name(player)?.yellow(),
+            world.component::<Hand>(player)?
+        );
+    }
+    Ok(())
 }
 
 fn show_title() {
@@ -278,7 +1319,7 @@ fn show_title() {
     println!("{}", "═══════════════════════".bright_cyan());
 }
 
-fn show_rules() -> Result<()> {
+fn show_rules() {
     println!("\n{}", "📖 DUDO (Liar’s Dice) Rules 🎲🤥".blue().bold());
 
     println!("\n{}", "🎲 SETUP".yellow().bold());
@@ -291,6 +1332,11 @@ fn show_rules() -> Result<()> {
     println!("    - More dice with same face (\"Six 3s\" beats \"Five 3s\")");
     println!("    - Same dice with higher face (\"Five 4s\" beats \"Five 3s\")");
 
+    println!("\n{}", "🂡 ACES".yellow().bold());
+    println!("  • Ones are wild and count towards every other face");
+    println!("  • Bidding on ones needs at least half the quantity (\"Five 3s\" → \"Three 1s\")");
+    println!("  • Leaving ones needs double plus one (\"Three 1s\" → \"Seven 4s\")");
+
     println!("\n{}", "⚔️  YOUR TURN".yellow().bold());
     println!(
         "  • {} Raise the bid (push it higher)",
@@ -301,22 +1347,466 @@ fn show_rules() -> Result<()> {
         "OPTION 2:".bright_red()
     );
 
+    println!(
+        "  • {} Call CALZA! (claim the bid is exactly right)",
+        "OPTION 3:".bright_cyan()
+    );
+
     println!("\n{}", "🔍 WHEN DUDO IS CALLED".yellow().bold());
     println!("  • All players reveal their dice");
     println!("  • Count the total matching dice");
     println!("  • {} → Caller loses a die", "Bid was TRUE".green());
     println!("  • {} → Bidder loses a die", "Bid was FALSE".red());
 
+    println!("\n{}", "🎯 WHEN CALZA IS CALLED".yellow().bold());
+    println!(
+        "  • {} → Caller regains a die (up to 5)",
+        "Bid was EXACT".green()
+    );
+    println!("  • {} → Caller loses a die", "Bid was NOT EXACT".red());
+
+    println!("\n{}", "🔒 PALIFICO".yellow().bold());
+    println!("  • The first time a player drops to one die, the next round is palifico");
+    println!("  • Ones are not wild, and the opening face cannot be changed");
+
     println!("\n{}", "🏆 WINNING".yellow().bold());
     println!("  • Lose all your dice → You're out!");
     println!("  • Last player with dice wins");
-
-    println!("\n{}", "Press Enter to return...".dimmed());
-    Text::new("").prompt()?;
-    Ok(())
 }
 
 fn quit() -> Result<()> {
     println!("{}", "Thanks for playing! 👋".bright_green());
     Ok(())
 }
+
+fn serve(addr: &str, web: Option<&str>, config: ServerConfig) -> Result<()> {
+    let tables: Vec<String> = config.tables.iter().map(|t| t.name.clone()).collect();
+    let mut server = Server::bind(addr, config)?;
+    if let Some(web) = web {
+        server = server.with_web(web)?;
+    }
+    println!(
+        "Hosting on {} with table(s): {}",
+        server.local_addr()?,
+        tables.join(", ")
+    );
+    if let Some(web) = server.web_addr()? {
+        println!("Web client at http://{web}/");
+    }
+    server.run()?;
+    println!("The game is over.");
+    Ok(())
+}
+
+/// Where `join` sits down.
+enum TableChoice {
+    /// An open table, by name, or picked from a menu if `None`.
+    Join(Option<String>),
+    Create(TableSettings),
+}
+
+fn join(
+    addr: &str,
+    name: Option<String>,
+    choice: TableChoice,
+    password: Option<String>,
+) -> Result<()> {
+    let name = match name {
+        Some(name) => name,
+        None => Text::new("Your name:").prompt()?,
+    };
+    let mut client = Client::connect(addr, &name)?;
+    let table = match choice {
+        TableChoice::Create(settings) => client.create_table(settings)?,
+        TableChoice::Join(wanted) => {
+            let open: Vec<TableSummary> = client
+                .tables()?
+                .into_iter()
+                .filter(|table| !table.started && table.players < table.max_players)
+                .collect();
+            if open.is_empty() {
+                bail!("there are no open tables; open one with --create NAME");
+            }
+            let table = pick_table(open, wanted.as_deref())?;
+            let password = match password {
+                Some(password) => Some(password),
+                None if table.password => Some(Text::new("Table password:").prompt()?),
+                None => None,
+            };
+            client.join_table(table.id, password.as_deref())?
+        }
+    };
+    println!("Sitting at {} on {addr}.", table.name.bold());
+    client.ready(true)?;
+    println!("Ready. Waiting for the other players...");
+
+    // The same menus as a local game, fed by the server's view of our seat.
+    let mut human = HumanController {
+        hot_seat: false,
+        chat: true,
+        typed: None,
+        turn_started: None,
+    };
+    let mut view: Option<PlayerView> = None;
+    let mut new_round = false;
+    loop {
+        let message = match client.recv() {
+            Ok(message) => message,
+            Err(err) if client.seat().is_some() => {
+                println!("{}", format!("Connection lost: {err:#}").red());
+                let session = client.session().to_string();
+                client = reconnect(addr, &name, &session)?;
+                continue;
+            }
+            Err(err) => return Err(err),
+        };
+        match message {
+            ServerMessage::Lobby { players, host, .. } => {
+                let names: Vec<String> = players
+                    .iter()
+                    .map(|p| {
+                        let mark = if p.ready { "✓" } else { "…" };
+                        format!("{} {mark}", p.name)
+                    })
+                    .collect();
+                println!("At the table: {}", names.join(", "));
+                let everyone_ready = players.iter().all(|p| p.ready);
+                if host
+                    && everyone_ready
+                    && players.len() >= table.min_players
+                    && Confirm::new("Everyone is ready. Start the game?")
+                        .with_default(true)
+                        .prompt()?
+                {
+                    client.start_game()?;
+                }
+            }
+            ServerMessage::Seat { .. } => println!("{}", "🎲 The game is starting!".green()),
+            ServerMessage::Event { event } => match (&view, event) {
+                (_, DudoEvent::RollDice) => new_round = true,
+                (Some(view), event) => {
+                    describe_remote_event(&view.seats, Some(view.player), &event)
+                }
+                _ => {}
+            },
+            ServerMessage::Resumed {
+                view: latest,
+                missed,
+                ..
+            } => {
+                println!("{}", "Reconnected. Back in your seat.".green());
+                for event in &missed {
+                    describe_remote_event(&latest.seats, Some(latest.player), event);
+                }
+                announce_remote_round(&latest);
+                view = Some(*latest);
+                if !prompt_remote_turn(&mut client, &mut human, view.as_ref())? {
+                    return Ok(());
+                }
+            }
+            ServerMessage::Disconnected { player, grace_secs } => {
+                let name = view.as_ref().map_or("?", |view| view.name(player));
+                println!("{name} lost their connection; their seat is held for {grace_secs}s.");
+            }
+            ServerMessage::Reconnected { player } => {
+                let name = view.as_ref().map_or("?", |view| view.name(player));
+                println!("{name} is back.");
+            }
+            ServerMessage::Reveal { outcome } => {
+                println!("\n{}", "⚔️  Revealing all dice...".bright_red().bold());
+                for (player, faces) in &outcome.revealed {
+                    let name = view.as_ref().map_or("?", |view| view.name(*player));
+                    println!("{}: {}", name.yellow(), Hand::from_faces(faces));
+                }
+                println!("Total: {} dice showing {}", outcome.total, outcome.bid.face);
+                if let Some(view) = &view
+                    && view.commitments.dealer.is_some()
+                {
+                    report_verification(&view.commitments, &outcome, |player| {
+                        view.name(player).to_string()
+                    });
+                }
+            }
+            ServerMessage::View { view: latest } => {
+                if std::mem::take(&mut new_round) {
+                    announce_remote_round(&latest);
+                }
+                view = Some(*latest);
+                if !prompt_remote_turn(&mut client, &mut human, view.as_ref())? {
+                    return Ok(());
+                }
+            }
+            ServerMessage::Chat { message } => println!("{}", format!("💬 {message}").cyan()),
+            ServerMessage::Error { message } => {
+                println!("{}", message.red());
+                if !prompt_remote_turn(&mut client, &mut human, view.as_ref())? {
+                    return Ok(());
+                }
+            }
+            ServerMessage::GameOver { name, .. } => {
+                println!(
+                    "\n{}",
+                    format!("🏆 {name} wins the game!").bright_green().bold()
+                );
+                return Ok(());
+            }
+            ServerMessage::Welcome { .. }
+            | ServerMessage::Rejected { .. }
+            | ServerMessage::Tables { .. }
+            | ServerMessage::Joined { .. }
+            | ServerMessage::Left { .. }
+            | ServerMessage::Watching { .. }
+            | ServerMessage::Spectate { .. } => {}
+        }
+    }
+}
+
+/// Follows a table as a spectator until its game ends.
+fn watch(addr: &str, name: &str, wanted: Option<&str>, password: Option<String>) -> Result<()> {
+    let mut client = Client::connect(addr, name)?;
+    let tables = client.tables()?;
+    if tables.is_empty() {
+        bail!("there are no tables to watch");
+    }
+    let table = pick_table(tables, wanted)?;
+    let password = match password {
+        Some(password) => Some(password),
+        None if table.password => Some(Text::new("Table password:").prompt()?),
+        None => None,
+    };
+    let table = client.watch_table(table.id, password.as_deref())?;
+    println!("Watching {} on {addr}.", table.name.bold());
+    if let Some(delay) = table.omniscient_delay {
+        println!("Every hand is shown {delay}s late.");
+    }
+
+    let mut seats: Vec<PublicSeat> = Vec::new();
+    let mut round = 0;
+    let mut hands_shown = 0;
+    loop {
+        match client.recv()? {
+            // Hands are shown once per round, when its bidding starts.
+            ServerMessage::Spectate { view } if view.is_omniscient() => {
+                let state = &view.game_state;
+                if state.phase == GamePhase::Bidding && state.round > hands_shown {
+                    hands_shown = state.round;
+                    show_spectator_hands(&view);
+                }
+            }
+            ServerMessage::Spectate { view } => {
+                if view.game_state.round != round {
+                    round = view.game_state.round;
+                    println!(
+                        "\n{}",
+                        format!("═══ Round {round} ═══").bright_blue().bold()
+                    );
+                    for seat in &view.seats {
+                        println!("  {:<16} {} dice", seat.name, seat.dice);
+                    }
+                }
+                seats = view.seats;
+            }
+            ServerMessage::Event { event } => describe_remote_event(&seats, None, &event),
+            ServerMessage::Reveal { outcome } => {
+                println!("\n{}", "⚔️  Revealing all dice...".bright_red().bold());
+                for (player, faces) in &outcome.revealed {
+                    let name = seats
+                        .iter()
+                        .find(|seat| seat.player == *player)
+                        .map_or("?", |seat| seat.name.as_str());
+                    println!("{}: {}", name.yellow(), Hand::from_faces(faces));
+                }
+                println!("Total: {} dice showing {}", outcome.total, outcome.bid.face);
+            }
+            ServerMessage::GameOver { name, .. } => {
+                println!(
+                    "\n{}",
+                    format!("🏆 {name} wins the game!").bright_green().bold()
+                );
+                return Ok(());
+            }
+            ServerMessage::Left { .. } => {
+                println!("The table closed.");
+                return Ok(());
+            }
+            ServerMessage::Chat { message } => println!("{}", format!("💬 {message}").cyan()),
+            ServerMessage::Error { message } => println!("{}", message.red()),
+            _ => {}
+        }
+    }
+}
+
+fn show_spectator_hands(view: &SpectatorView) {
+    println!(
+        "{}",
+        format!("👁 Round {} hands", view.game_state.round).bright_magenta()
+    );
+    for (player, hand) in &view.hands {
+        println!("  {:<16} {hand}", view.name(*player));
+    }
+}
+
+/// Tries to get our seat back after the connection dropped.
+fn reconnect(addr: &str, name: &str, session: &str) -> Result<Client> {
+    const ATTEMPTS: u32 = 10;
+    let mut attempt = 1;
+    loop {
+        println!("Reconnecting ({attempt}/{ATTEMPTS})...");
+        match Client::resume(addr, name, session) {
+            Ok(client) => return Ok(client),
+            Err(err) if attempt == ATTEMPTS => return Err(err),
+            Err(_) => std::thread::sleep(std::time::Duration::from_secs(2)),
+        }
+        attempt += 1;
+    }
+}
+
+/// Prints another player's move. `me` is our own seat, if we have one.
+fn describe_remote_event(seats: &[PublicSeat], me: Option<Entity>, event: &DudoEvent) {
+    let name = |player| {
+        seats
+            .iter()
+            .find(|seat| seat.player == player)
+            .map_or("?", |seat| seat.name.as_str())
+    };
+    match *event {
+        DudoEvent::BidMade {
+            player,
+            quantity,
+            face,
+        } => println!("{} bids {quantity} × {face}", name(player)),
+        DudoEvent::ChallengeMade { challenger } => println!("{} calls Dudo!", name(challenger)),
+        DudoEvent::CalzaCalled { caller } => println!("{} calls Calza!", name(caller)),
+        DudoEvent::PlayerForfeited { player } => {
+            println!("{} forfeits and leaves the game.", name(player))
+        }
+        DudoEvent::TurnWarning {
+            player,
+            seconds_left,
+        } if Some(player) == me => {
+            println!("{}", format!("⏱ {seconds_left}s left to act!").bright_red())
+        }
+        DudoEvent::TurnTimedOut { player } => println!("{} ran out of time.", name(player)),
+        DudoEvent::DealerCommitted { .. } => {
+            println!(
+                "{}",
+                "🔐 The dealer has committed to this round's dice.".dimmed()
+            )
+        }
+        DudoEvent::GameReady
+        | DudoEvent::RollDice
+        | DudoEvent::TurnWarning { .. }
+        | DudoEvent::SeedCommitted { .. } => {}
+    }
+}
+
+/// Checks a revealed round against its seed commitments and says whether
+/// the dice were dealt fairly.
+fn report_verification(
+    commitments: &RoundCommitments,
+    outcome: &ChallengeOutcome,
+    name: impl Fn(Entity) -> String,
+) {
+    let mismatches = verify_round(commitments, outcome);
+    if mismatches.is_empty() {
+        println!("{}", "✅ Every hand matches the committed seeds.".green());
+        return;
+    }
+    for mismatch in mismatches {
+        let problem = match mismatch {
+            Mismatch::NoProof => "the seeds were not revealed".to_string(),
+            Mismatch::WrongRound {
+                committed,
+                revealed,
+            } => format!("seeds for round {revealed} were revealed for round {committed}"),
+            Mismatch::DealerSeed => "the dealer's seed does not match its commitment".to_string(),
+            Mismatch::Seed { player } => format!("{}'s seed does not match", name(player)),
+            Mismatch::Hand { player } => {
+                format!("{}'s hand is not the one the seeds deal", name(player))
+            }
+        };
+        println!("{}", format!("❌ Unverified dice: {problem}").bright_red());
+    }
+}
+
+/// Finds a table by name, or lets the player pick one.
+fn pick_table(tables: Vec<TableSummary>, wanted: Option<&str>) -> Result<TableSummary> {
+    if let Some(wanted) = wanted {
+        return tables
+            .into_iter()
+            .find(|table| table.name.eq_ignore_ascii_case(wanted))
+            .with_context(|| format!("no table named '{wanted}'"));
+    }
+    let labels: Vec<String> = tables.iter().map(describe_table).collect();
+    let choice = Select::new("Choose a table:", labels).raw_prompt()?;
+    Ok(tables[choice.index].clone())
+}
+
+fn describe_table(table: &TableSummary) -> String {
+    let mut label = format!(
+        "{} ({}/{} players",
+        table.name, table.players, table.max_players
+    );
+    if table.bots > 0 {
+        label.push_str(&format!(", {} bot(s)", table.bots));
+    }
+    if !table.rules.wild_ones {
+        label.push_str(", classic");
+    }
+    label.push(')');
+    if table.verified_dice {
+        label.push_str(" ✅");
+    }
+    if table.password {
+        label.push_str(" 🔒");
+    }
+    label
+}
+
+fn announce_remote_round(view: &PlayerView) {
+    println!(
+        "\n{}",
+        format!("═══ Round {} ═══", view.game_state.round)
+            .bright_blue()
+            .bold()
+    );
+    for seat in &view.seats {
+        println!("  {:<16} {} dice", seat.name, seat.dice);
+    }
+    println!("Your dice: {}", view.hand);
+}
+
+/// Asks for an action if it is our turn at a networked table. Returns
+/// `false` if the player chose to leave.
+fn prompt_remote_turn(
+    client: &mut Client,
+    human: &mut HumanController,
+    view: Option<&PlayerView>,
+) -> Result<bool> {
+    let Some(view) = view.filter(|view| view.is_my_turn()) else {
+        return Ok(true);
+    };
+    if let Some(bid) = view.game_state.current_bid {
+        println!("Current bid: {} × {}", bid.quantity, bid.face);
+    }
+    loop {
+        match human.choose_action(view)? {
+            Some(action) => {
+                client.act(action)?;
+                return Ok(true);
+            }
+            None => match human.typed.take() {
+                Some(command) => client.send(&chat_message(command))?,
+                None => return Ok(false),
+            },
+        }
+    }
+}
+
+fn chat_message(command: ChatCommand) -> ClientMessage {
+    match command {
+        ChatCommand::Say(body) => ClientMessage::Chat { body },
+        ChatCommand::Mute(name) => ClientMessage::Mute { name, muted: true },
+        ChatCommand::Unmute(name) => ClientMessage::Mute { name, muted: false },
+    }
+}
@@ -0,0 +1,174 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+
+use anyhow::{Context, Result, bail};
+use game_engine::Entity;
+
+use crate::DudoEvent;
+use crate::controller::TurnAction;
+use crate::fair_dice::Seed;
+use crate::net::lobby::{TableId, TableSettings, TableSummary};
+use crate::net::protocol::{ClientMessage, PROTOCOL_VERSION, ServerMessage, encode};
+
+/// A blocking connection to a `Server`.
+pub struct Client {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    seat: Option<Entity>,
+    session: String,
+}
+
+impl Client {
+    /// Connects and says hello, failing if the server rejects us.
+    pub fn connect(addr: impl ToSocketAddrs, name: &str) -> Result<Self> {
+        Self::hello(addr, name, None)
+    }
+
+    /// Reconnects with the session token from an earlier connection. If the
+    /// server still holds our seat, a `resumed` message follows.
+    pub fn resume(addr: impl ToSocketAddrs, name: &str, session: &str) -> Result<Self> {
+        Self::hello(addr, name, Some(session.to_string()))
+    }
+
+    fn hello(addr: impl ToSocketAddrs, name: &str, session: Option<String>) -> Result<Self> {
+        let stream = TcpStream::connect(addr).context("failed to connect to the server")?;
+        let mut client = Self {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+            seat: None,
+            session: String::new(),
+        };
+        client.send(&ClientMessage::Hello {
+            version: PROTOCOL_VERSION,
+            name: name.to_string(),
+            session,
+        })?;
+        match client.recv()? {
+            ServerMessage::Welcome { session, .. } => {
+                client.session = session;
+                Ok(client)
+            }
+            ServerMessage::Rejected { reason } => bail!("the server rejected us: {reason}"),
+            other => bail!("expected a welcome, got {other:?}"),
+        }
+    }
+
+    /// The token that gets our seat back after a dropped connection.
+    pub fn session(&self) -> &str {
+        &self.session
+    }
+
+    /// The tables currently open on the server.
+    pub fn tables(&mut self) -> Result<Vec<TableSummary>> {
+        self.send(&ClientMessage::ListTables)?;
+        loop {
+            match self.recv()? {
+                ServerMessage::Tables { tables } => return Ok(tables),
+                ServerMessage::Error { message } => bail!(message),
+                _ => {}
+            }
+        }
+    }
+
+    /// Opens a table and sits down at it as its host.
+    pub fn create_table(&mut self, settings: TableSettings) -> Result<TableSummary> {
+        self.send(&ClientMessage::CreateTable { table: settings })?;
+        self.wait_for_join()
+    }
+
+    pub fn join_table(&mut self, table: TableId, password: Option<&str>) -> Result<TableSummary> {
+        self.send(&ClientMessage::JoinTable {
+            table,
+            password: password.map(str::to_string),
+        })?;
+        self.wait_for_join()
+    }
+
+    /// Watches a table without taking a seat.
+    pub fn watch_table(&mut self, table: TableId, password: Option<&str>) -> Result<TableSummary> {
+        self.send(&ClientMessage::WatchTable {
+            table,
+            password: password.map(str::to_string),
+        })?;
+        loop {
+            match self.recv()? {
+                ServerMessage::Watching { table } => return Ok(table),
+                ServerMessage::Error { message } => bail!(message),
+                _ => {}
+            }
+        }
+    }
+
+    fn wait_for_join(&mut self) -> Result<TableSummary> {
+        loop {
+            match self.recv()? {
+                ServerMessage::Joined { table } => return Ok(table),
+                ServerMessage::Error { message } => bail!(message),
+                _ => {}
+            }
+        }
+    }
+
+    pub fn ready(&mut self, ready: bool) -> Result<()> {
+        self.send(&ClientMessage::Ready { ready })
+    }
+
+    /// Asks to start the game at the table we host.
+    pub fn start_game(&mut self) -> Result<()> {
+        self.send(&ClientMessage::StartGame)
+    }
+
+    pub fn leave_table(&mut self) -> Result<()> {
+        self.send(&ClientMessage::LeaveTable)
+    }
+
+    /// Our seat, once the game has started.
+    pub fn seat(&self) -> Option<Entity> {
+        self.seat
+    }
+
+    /// Waits at the table until the game starts, skipping lobby updates.
+    pub fn wait_for_seat(&mut self) -> Result<Entity> {
+        loop {
+            if let ServerMessage::Seat { seat } = self.recv()? {
+                return Ok(seat);
+            }
+        }
+    }
+
+    pub fn send(&mut self, message: &ClientMessage) -> Result<()> {
+        writeln!(self.writer, "{}", encode(message))?;
+        Ok(())
+    }
+
+    /// Sends a turn for our own seat.
+    pub fn act(&mut self, action: TurnAction) -> Result<()> {
+        let seat = self.seat.context("the game has not started")?;
+        self.send(&ClientMessage::Event {
+            event: action.into_event(seat),
+        })
+    }
+
+    /// Blocks until the next message from the server.
+    pub fn recv(&mut self) -> Result<ServerMessage> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            bail!("the server closed the connection");
+        }
+        let message = serde_json::from_str(&line)?;
+        match message {
+            ServerMessage::Seat { seat } | ServerMessage::Resumed { seat, .. } => {
+                self.seat = Some(seat)
+            }
+            ServerMessage::GameOver { .. } => self.seat = None,
+            // A verified table waits for our share of the round's seed.
+            ServerMessage::Event {
+                event: DudoEvent::DealerCommitted { .. },
+            } if self.seat.is_some() => self.send(&ClientMessage::Seed {
+                seed: Seed::random(),
+            })?,
+            _ => {}
+        }
+        Ok(message)
+    }
+}
@@ -0,0 +1,1242 @@
+//! The server's lobby: named clients, the tables they can open and join,
+//! and routing of each client's requests to its table.
+
+use std::collections::{BTreeMap, HashMap};
+use std::time::Instant;
+
+use anyhow::{Context, Result, anyhow, bail, ensure};
+use serde::{Deserialize, Serialize};
+
+use crate::chat::{ChatBody, RateLimiter};
+use crate::components::player::Controller;
+use crate::net::protocol::{ClientMessage, PROTOCOL_VERSION, ServerMessage};
+use crate::net::server::{ConnId, Outbox};
+use crate::net::table::{ReconnectPolicy, Table};
+use crate::resources::DudoRules;
+
+pub type TableId = u64;
+
+/// Longest table or player name the lobby accepts.
+const MAX_NAME_LEN: usize = 32;
+
+/// How a table is set up when it is opened.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableSettings {
+    pub name: String,
+    /// Players needed before the game can start.
+    #[serde(default = "default_min_players")]
+    pub min_players: usize,
+    #[serde(default = "default_max_players")]
+    pub max_players: usize,
+    #[serde(default)]
+    pub rules: DudoRules,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Seated after the players when the game starts.
+    #[serde(default)]
+    pub bots: Vec<Controller>,
+    /// Seconds after which spectators see every hand. Without it they only
+    /// see the public table.
+    #[serde(default)]
+    pub omniscient_delay: Option<u64>,
+    /// Deals every round from seeds the players commit to, so they can
+    /// check their dice after a challenge instead of trusting the server.
+    #[serde(default)]
+    pub verified_dice: bool,
+}
+
+fn default_min_players() -> usize {
+    2
+}
+
+fn default_max_players() -> usize {
+    6
+}
+
+impl TableSettings {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            min_players: default_min_players(),
+            max_players: default_max_players(),
+            rules: DudoRules::default(),
+            password: None,
+            bots: Vec::new(),
+            omniscient_delay: None,
+            verified_dice: false,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let name = self.name.trim();
+        ensure!(!name.is_empty(), "a table needs a name");
+        ensure!(
+            name.chars().count() <= MAX_NAME_LEN,
+            "table names are at most {MAX_NAME_LEN} characters"
+        );
+        ensure!(
+            (1..=self.max_players).contains(&self.min_players),
+            "a table needs between 1 and {} players to start, not {}",
+            self.max_players,
+            self.min_players
+        );
+        let bots = self.bots.len();
+        ensure!(
+            self.min_players + bots >= 2,
+            "a game needs at least 2 players"
+        );
+        ensure!(
+            self.max_players + bots <= 6,
+            "a table seats at most 6 players and bots"
+        );
+        ensure!(
+            !self.bots.contains(&Controller::Human),
+            "bots cannot be human"
+        );
+        Ok(())
+    }
+}
+
+/// A table as shown in the lobby's list.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableSummary {
+    pub id: TableId,
+    pub name: String,
+    pub players: usize,
+    pub min_players: usize,
+    pub max_players: usize,
+    pub bots: usize,
+    pub rules: DudoRules,
+    /// Whether joining needs a password.
+    pub password: bool,
+    pub started: bool,
+    pub spectators: usize,
+    /// Seconds spectators wait to see every hand, if they may at all.
+    pub omniscient_delay: Option<u64>,
+    pub verified_dice: bool,
+}
+
+/// A player waiting at a table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LobbyPlayer {
+    pub name: String,
+    pub ready: bool,
+    pub host: bool,
+}
+
+/// Every table on the server, each running its own game.
+pub struct Lobby {
+    seed: u64,
+    next_table: TableId,
+    reconnect: ReconnectPolicy,
+    /// Connected clients that said hello.
+    names: HashMap<ConnId, String>,
+    /// Kept for clients that dropped out of a game, until they come back
+    /// or forfeit.
+    at_table: HashMap<ConnId, TableId>,
+    /// Spectators and the table each one watches.
+    watching: HashMap<ConnId, TableId>,
+    /// Each session token and the connection that last used it.
+    sessions: HashMap<String, ConnId>,
+    tokens: HashMap<ConnId, String>,
+    tables: BTreeMap<TableId, Table>,
+    games_finished: usize,
+    chat_limit: RateLimiter<ConnId>,
+}
+
+impl Lobby {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            next_table: 1,
+            reconnect: ReconnectPolicy::default(),
+            names: HashMap::new(),
+            at_table: HashMap::new(),
+            watching: HashMap::new(),
+            sessions: HashMap::new(),
+            tokens: HashMap::new(),
+            tables: BTreeMap::new(),
+            games_finished: 0,
+            chat_limit: RateLimiter::default(),
+        }
+    }
+
+    /// Sets how tables opened from now on treat players who drop out.
+    pub fn with_reconnect(mut self, reconnect: ReconnectPolicy) -> Self {
+        self.reconnect = reconnect;
+        self
+    }
+
+    /// Opens a table. With no `host` the server owns it: it starts once
+    /// everyone is ready and reopens after each game.
+    pub fn open_table(&mut self, settings: TableSettings, host: Option<ConnId>) -> Result<TableId> {
+        settings.validate()?;
+        let id = self.next_table;
+        self.next_table += 1;
+        let seed = self.seed.wrapping_add(id);
+        let table = Table::new(id, settings, seed, host, self.reconnect.clone());
+        self.tables.insert(id, table);
+        Ok(id)
+    }
+
+    pub fn table(&self, id: TableId) -> Option<&Table> {
+        self.tables.get(&id)
+    }
+
+    pub fn tables(&self) -> Vec<TableSummary> {
+        self.tables.values().map(Table::summary).collect()
+    }
+
+    /// Games played to the end since the server started.
+    pub fn games_finished(&self) -> usize {
+        self.games_finished
+    }
+
+    /// Handles one request. Anything the client got wrong is answered with
+    /// an `error` and changes nothing.
+    pub fn handle(&mut self, out: &mut Outbox, conn: ConnId, message: ClientMessage, now: Instant) {
+        let result = match message {
+            ClientMessage::Hello {
+                version,
+                name,
+                session,
+            } => self.hello(out, conn, version, name, session, now),
+            _ if !self.names.contains_key(&conn) => Err(anyhow!("say hello first")),
+            ClientMessage::ListTables => {
+                out.send(
+                    conn,
+                    &ServerMessage::Tables {
+                        tables: self.tables(),
+                    },
+                );
+                Ok(())
+            }
+            ClientMessage::CreateTable { table } => self.create(out, conn, table),
+            ClientMessage::JoinTable { table, password } => {
+                self.join(out, conn, table, password.as_deref())
+            }
+            ClientMessage::Ready { ready } => self
+                .current_table(conn)
+                .and_then(|table| table.set_ready(out, conn, ready)),
+            ClientMessage::LeaveTable => self.leave(out, conn),
+            ClientMessage::WatchTable { table, password } => {
+                self.watch(out, conn, table, password.as_deref())
+            }
+            ClientMessage::StartGame => self
+                .current_table(conn)
+                .and_then(|table| table.start(out, conn)),
+            ClientMessage::Event { event } => self
+                .current_table(conn)
+                .and_then(|table| table.event(out, conn, event)),
+            ClientMessage::Seed { seed } => self
+                .current_table(conn)
+                .and_then(|table| table.seed(out, conn, seed)),
+            ClientMessage::Chat { body } => self.chat(out, conn, body, now),
+            ClientMessage::Mute { name, muted } => self
+                .current_table(conn)
+                .and_then(|table| table.mute(conn, &name, muted)),
+        };
+        if let Err(err) = result {
+            out.send(
+                conn,
+                &ServerMessage::Error {
+                    message: format!("{err:#}"),
+                },
+            );
+        }
+        self.finish_games(out);
+    }
+
+    /// Handles a client whose connection closed. A seat in a game is held
+    /// for it; anywhere else it is forgotten.
+    pub fn disconnect(&mut self, out: &mut Outbox, conn: ConnId, now: Instant) {
+        self.names.remove(&conn);
+        self.chat_limit.forget(&conn);
+        if let Some(id) = self.watching.remove(&conn)
+            && let Some(table) = self.tables.get_mut(&id)
+        {
+            table.unwatch(out, conn);
+        }
+        let Some(&id) = self.at_table.get(&conn) else {
+            self.forget_session(conn);
+            return;
+        };
+        let Some(table) = self.tables.get_mut(&id) else {
+            return;
+        };
+        // An error here came from a stand-in's turn; the seat is held anyway.
+        if table.drop_member(out, conn, now).unwrap_or(true) {
+            self.finish_games(out);
+            return;
+        }
+        let close = table.is_empty() && (table.is_hosted() || table.is_started());
+        self.at_table.remove(&conn);
+        self.forget_session(conn);
+        if close {
+            self.close_table(out, id);
+        }
+    }
+
+    /// Forfeits players who did not come back in time. A table whose clock
+    /// or stand-ins fail cannot go on, so it is closed with the reason.
+    pub fn tick(&mut self, out: &mut Outbox, now: Instant) {
+        let mut expired = Vec::new();
+        let mut failed = Vec::new();
+        for table in self.tables.values_mut() {
+            match table.tick(out, now) {
+                Ok(conns) => expired.extend(conns),
+                Err(err) => failed.push((table.id(), err)),
+            }
+        }
+        for conn in expired {
+            self.at_table.remove(&conn);
+            self.forget_session(conn);
+        }
+        for (id, err) in failed {
+            let table = &self.tables[&id];
+            out.send_all(
+                table.conns().chain(table.spectators().iter().copied()),
+                &ServerMessage::Error {
+                    message: format!("table {id} was closed: {err:#}"),
+                },
+            );
+            self.dismiss_table(out, id);
+        }
+        self.finish_games(out);
+    }
+
+    fn hello(
+        &mut self,
+        out: &mut Outbox,
+        conn: ConnId,
+        version: u32,
+        name: String,
+        session: Option<String>,
+        now: Instant,
+    ) -> Result<()> {
+        ensure!(!self.names.contains_key(&conn), "already said hello");
+        if version != PROTOCOL_VERSION {
+            let reason = format!(
+                "protocol version {version} is not supported (server speaks {PROTOCOL_VERSION})"
+            );
+            out.send(conn, &ServerMessage::Rejected { reason });
+            out.close(conn);
+            return Ok(());
+        }
+        let name = name.trim();
+        ensure!(!name.is_empty(), "a player needs a name");
+        ensure!(
+            name.chars().count() <= MAX_NAME_LEN,
+            "player names are at most {MAX_NAME_LEN} characters"
+        );
+
+        let held = session.and_then(|token| {
+            let old = *self.sessions.get(&token)?;
+            Some((token, old))
+        });
+        if let Some((token, old)) = held {
+            // The old connection may linger half-open; this one replaces it.
+            if self.names.contains_key(&old) {
+                self.disconnect(out, old, now);
+                out.close(old);
+            }
+            if let Some(id) = self.at_table.remove(&old) {
+                self.names.insert(conn, name.to_string());
+                self.tokens.remove(&old);
+                self.tokens.insert(conn, token.clone());
+                self.sessions.insert(token.clone(), conn);
+                self.at_table.insert(conn, id);
+                out.send(
+                    conn,
+                    &ServerMessage::Welcome {
+                        version: PROTOCOL_VERSION,
+                        session: token,
+                    },
+                );
+                let table = self.tables.get_mut(&id).context("no such table")?;
+                return table.reconnect(out, old, conn);
+            }
+        }
+
+        let token = format!("{:032x}", rand::random::<u128>());
+        self.names.insert(conn, name.to_string());
+        self.sessions.insert(token.clone(), conn);
+        self.tokens.insert(conn, token.clone());
+        out.send(
+            conn,
+            &ServerMessage::Welcome {
+                version: PROTOCOL_VERSION,
+                session: token,
+            },
+        );
+        out.send(
+            conn,
+            &ServerMessage::Tables {
+                tables: self.tables(),
+            },
+        );
+        Ok(())
+    }
+
+    fn forget_session(&mut self, conn: ConnId) {
+        if let Some(token) = self.tokens.remove(&conn) {
+            self.sessions.remove(&token);
+        }
+    }
+
+    fn create(&mut self, out: &Outbox, conn: ConnId, settings: TableSettings) -> Result<()> {
+        ensure!(!self.at_table.contains_key(&conn), "leave your table first");
+        // Strategy paths name files on the server, so only its operator may
+        // seat CFR bots.
+        if settings
+            .bots
+            .iter()
+            .any(|bot| matches!(bot, Controller::Cfr { .. }))
+        {
+            bail!("CFR bots can only be seated by the server");
+        }
+        let password = settings.password.clone();
+        let id = self.open_table(settings, Some(conn))?;
+        self.join(out, conn, id, password.as_deref())
+            .inspect_err(|_| {
+                self.tables.remove(&id);
+            })
+    }
+
+    fn join(
+        &mut self,
+        out: &Outbox,
+        conn: ConnId,
+        id: TableId,
+        password: Option<&str>,
+    ) -> Result<()> {
+        ensure!(!self.at_table.contains_key(&conn), "leave your table first");
+        ensure!(!self.watching.contains_key(&conn), "stop watching first");
+        let name = self.names.get(&conn).context("say hello first")?;
+        let table = self.tables.get_mut(&id).context("no such table")?;
+        table.join(out, conn, name, password)?;
+        self.at_table.insert(conn, id);
+        Ok(())
+    }
+
+    fn watch(
+        &mut self,
+        out: &Outbox,
+        conn: ConnId,
+        id: TableId,
+        password: Option<&str>,
+    ) -> Result<()> {
+        ensure!(!self.at_table.contains_key(&conn), "leave your table first");
+        ensure!(!self.watching.contains_key(&conn), "stop watching first");
+        let table = self.tables.get_mut(&id).context("no such table")?;
+        table.watch(out, conn, password)?;
+        self.watching.insert(conn, id);
+        Ok(())
+    }
+
+    fn leave(&mut self, out: &Outbox, conn: ConnId) -> Result<()> {
+        if let Some(id) = self.watching.remove(&conn) {
+            let table = self.tables.get_mut(&id).context("no such table")?;
+            table.unwatch(out, conn);
+            out.send(
+                conn,
+                &ServerMessage::Tables {
+                    tables: self.tables(),
+                },
+            );
+            return Ok(());
+        }
+        let id = *self.at_table.get(&conn).context("you are not at a table")?;
+        let table = self.tables.get_mut(&id).context("no such table")?;
+        ensure!(!table.is_started(), "the game is in progress");
+        table.leave(out, conn);
+        self.at_table.remove(&conn);
+        if table.is_empty() && table.is_hosted() {
+            self.close_table(out, id);
+        }
+        out.send(
+            conn,
+            &ServerMessage::Tables {
+                tables: self.tables(),
+            },
+        );
+        Ok(())
+    }
+
+    fn chat(&mut self, out: &Outbox, conn: ConnId, body: ChatBody, now: Instant) -> Result<()> {
+        ensure!(
+            !self.watching.contains_key(&conn),
+            "spectators cannot chat with the players"
+        );
+        let id = *self.at_table.get(&conn).context("you are not at a table")?;
+        self.chat_limit.check(conn, now)?;
+        let table = self.tables.get(&id).context("no such table")?;
+        table.chat(out, conn, body)
+    }
+
+    fn current_table(&mut self, conn: ConnId) -> Result<&mut Table> {
+        let id = self.at_table.get(&conn).context("you are not at a table")?;
+        self.tables.get_mut(id).context("no such table")
+    }
+
+    /// Sends the players of finished games back to the lobby.
+    fn finish_games(&mut self, out: &Outbox) {
+        let finished: Vec<TableId> = self
+            .tables
+            .values()
+            .filter(|table| table.is_over())
+            .map(Table::id)
+            .collect();
+        for id in finished {
+            self.games_finished += 1;
+            self.dismiss_table(out, id);
+        }
+    }
+
+    /// Sends everyone at a table back to the lobby and closes it.
+    fn dismiss_table(&mut self, out: &Outbox, id: TableId) {
+        let table = &self.tables[&id];
+        let conns: Vec<ConnId> = table.conns().collect();
+        let spectators = table.spectators().to_vec();
+        for &conn in &conns {
+            self.at_table.remove(&conn);
+            if !self.names.contains_key(&conn) {
+                self.forget_session(conn);
+            }
+        }
+        self.close_table(out, id);
+        out.send_all(
+            conns.into_iter().chain(spectators),
+            &ServerMessage::Tables {
+                tables: self.tables(),
+            },
+        );
+    }
+
+    /// Removes a table, sending its spectators away and reopening the
+    /// server's own tables for a new game.
+    fn close_table(&mut self, out: &Outbox, id: TableId) {
+        let Some(table) = self.tables.remove(&id) else {
+            return;
+        };
+        for &conn in table.spectators() {
+            self.watching.remove(&conn);
+            out.send(conn, &ServerMessage::Left { table: id });
+        }
+        if !table.is_hosted() {
+            // The settings were validated when the table first opened.
+            let _ = self.open_table(table.settings().clone(), None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::{self, Receiver};
+
+    use std::time::Duration;
+
+    use game_engine::Entity;
+
+    use super::*;
+    use crate::DudoEvent;
+    use crate::bot::Difficulty;
+    use crate::chat::ChatBody;
+    use crate::fair_dice::{RoundCommitments, Seed, verify_round};
+    use crate::net::table::SEED_WAIT;
+    use crate::resources::{GamePhase, GameState, TimeoutAction, TurnOrder};
+    use crate::view::PlayerView;
+
+    /// A lobby with in-memory connections and a clock the test moves.
+    struct Harness {
+        lobby: Lobby,
+        out: Outbox,
+        inboxes: HashMap<ConnId, Receiver<String>>,
+        now: Instant,
+    }
+
+    impl Harness {
+        fn new() -> Self {
+            Self::with_reconnect(ReconnectPolicy::default())
+        }
+
+        fn with_reconnect(reconnect: ReconnectPolicy) -> Self {
+            Self {
+                lobby: Lobby::new(5).with_reconnect(reconnect),
+                out: Outbox::new(),
+                inboxes: HashMap::new(),
+                now: Instant::now(),
+            }
+        }
+
+        fn connect(&mut self, conn: ConnId, name: &str) {
+            self.hello(conn, name, None);
+        }
+
+        /// Says hello, returns the session token.
+        fn hello(&mut self, conn: ConnId, name: &str, session: Option<String>) -> String {
+            let (outgoing, inbox) = mpsc::channel();
+            self.out.insert(conn, outgoing);
+            self.inboxes.insert(conn, inbox);
+            self.send(
+                conn,
+                ClientMessage::Hello {
+                    version: PROTOCOL_VERSION,
+                    name: name.into(),
+                    session,
+                },
+            );
+            self.lobby.tokens[&conn].clone()
+        }
+
+        fn send(&mut self, conn: ConnId, message: ClientMessage) {
+            self.lobby.handle(&mut self.out, conn, message, self.now);
+        }
+
+        fn drop_conn(&mut self, conn: ConnId) {
+            self.lobby.disconnect(&mut self.out, conn, self.now);
+            self.out.close(conn);
+        }
+
+        fn wait(&mut self, secs: u64) {
+            self.now += Duration::from_secs(secs);
+            self.lobby.tick(&mut self.out, self.now);
+        }
+
+        fn drain(&self, conn: ConnId) -> Vec<ServerMessage> {
+            self.inboxes[&conn]
+                .try_iter()
+                .map(|line| serde_json::from_str(&line).unwrap())
+                .collect()
+        }
+
+        /// Sends a request and returns the error it was answered with.
+        fn refused(&mut self, conn: ConnId, message: ClientMessage) -> String {
+            self.drain(conn);
+            self.send(conn, message);
+            self.drain(conn)
+                .into_iter()
+                .find_map(|message| match message {
+                    ServerMessage::Error { message } => Some(message),
+                    _ => None,
+                })
+                .expect("the request should be refused")
+        }
+
+        fn create(&mut self, conn: ConnId, settings: TableSettings) -> TableId {
+            self.send(conn, ClientMessage::CreateTable { table: settings });
+            self.drain(conn)
+                .into_iter()
+                .find_map(|message| match message {
+                    ServerMessage::Joined { table } => Some(table.id),
+                    _ => None,
+                })
+                .expect("the table should open")
+        }
+    }
+
+    fn join(table: TableId, password: Option<&str>) -> ClientMessage {
+        ClientMessage::JoinTable {
+            table,
+            password: password.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn the_host_starts_once_everyone_is_ready() {
+        let mut h = Harness::new();
+        h.connect(1, "Ana");
+        h.connect(2, "Ben");
+        let table = h.create(1, TableSettings::new("Friday"));
+        h.send(2, join(table, None));
+
+        assert_eq!(
+            h.refused(1, ClientMessage::StartGame),
+            "not everyone is ready"
+        );
+        h.send(1, ClientMessage::Ready { ready: true });
+        h.send(2, ClientMessage::Ready { ready: true });
+        assert_eq!(
+            h.refused(2, ClientMessage::StartGame),
+            "only the host can start the game"
+        );
+
+        let lobby = h.drain(1);
+        let Some(ServerMessage::Lobby { players, host, .. }) = lobby.last() else {
+            panic!("expected a lobby update, got {lobby:?}");
+        };
+        assert!(host);
+        assert!(players.iter().all(|p| p.ready));
+
+        h.send(1, ClientMessage::StartGame);
+        for conn in [1, 2] {
+            let seen = h.drain(conn);
+            assert!(seen.iter().any(|m| matches!(m, ServerMessage::Seat { .. })));
+            assert!(seen.iter().any(|m| matches!(
+                m,
+                ServerMessage::Event {
+                    event: DudoEvent::GameReady
+                }
+            )));
+        }
+        assert!(h.lobby.tables()[0].started);
+        assert_eq!(
+            h.refused(2, ClientMessage::LeaveTable),
+            "the game is in progress"
+        );
+    }
+
+    #[test]
+    fn passwords_and_limits_are_enforced() {
+        let mut h = Harness::new();
+        for (conn, name) in [(1, "Ana"), (2, "Ben"), (3, "Cid")] {
+            h.connect(conn, name);
+        }
+        let mut settings = TableSettings::new("Secret");
+        settings.max_players = 2;
+        settings.password = Some("hunter2".into());
+        let table = h.create(1, settings);
+
+        assert_eq!(h.refused(2, join(table, None)), "wrong password");
+        assert_eq!(h.refused(2, join(table, Some("nope"))), "wrong password");
+        h.send(2, join(table, Some("hunter2")));
+        assert_eq!(
+            h.refused(3, join(table, Some("hunter2"))),
+            "that table is full"
+        );
+
+        let summary = &h.lobby.tables()[0];
+        assert!(summary.password);
+        assert_eq!(summary.players, 2);
+
+        let mut cfr = TableSettings::new("Solver");
+        cfr.bots = vec![Controller::Cfr {
+            strategy: "/etc/passwd".into(),
+        }];
+        assert_eq!(
+            h.refused(3, ClientMessage::CreateTable { table: cfr }),
+            "CFR bots can only be seated by the server"
+        );
+        let mut crowded = TableSettings::new("Crowded");
+        crowded.bots = vec![Controller::Bot(Difficulty::Easy)];
+        assert_eq!(
+            h.refused(3, ClientMessage::CreateTable { table: crowded }),
+            "a table seats at most 6 players and bots"
+        );
+    }
+
+    #[test]
+    fn leaving_passes_the_host_on_and_closes_empty_tables() {
+        let mut h = Harness::new();
+        h.connect(1, "Ana");
+        h.connect(2, "Ben");
+        let table = h.create(1, TableSettings::new("Friday"));
+        h.send(2, join(table, None));
+
+        h.send(1, ClientMessage::LeaveTable);
+        let seen = h.drain(2);
+        let Some(ServerMessage::Lobby { players, host, .. }) = seen.last() else {
+            panic!("expected a lobby update, got {seen:?}");
+        };
+        assert!(host);
+        assert_eq!(players.len(), 1);
+
+        h.drop_conn(2);
+        assert!(h.lobby.tables().is_empty());
+    }
+
+    #[test]
+    fn each_table_runs_its_own_game() {
+        let mut h = Harness::new();
+        h.connect(1, "Ana");
+        h.connect(2, "Ben");
+        let mut tables = Vec::new();
+        for (conn, name) in [(1, "First"), (2, "Second")] {
+            let mut settings = TableSettings::new(name);
+            settings.min_players = 1;
+            settings.max_players = 1;
+            settings.bots = vec![Controller::Bot(Difficulty::Easy)];
+            tables.push(h.create(conn, settings));
+            h.send(conn, ClientMessage::Ready { ready: true });
+            h.send(conn, ClientMessage::StartGame);
+        }
+
+        let names = |table| {
+            let world = h.lobby.table(table).unwrap().world().unwrap();
+            PlayerView::for_player(world, Entity::new(0))
+                .unwrap()
+                .seats
+                .into_iter()
+                .map(|seat| seat.name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(tables[0]), ["Ana", "Bot 1 (easy)"]);
+        assert_eq!(names(tables[1]), ["Ben", "Bot 1 (easy)"]);
+
+        for message in h.drain(1) {
+            if let ServerMessage::View { view } = message {
+                assert!(view.seats.iter().all(|seat| seat.name != "Ben"));
+            }
+        }
+    }
+
+    /// Seats Ana and Ben at a started table with an easy bot. Returns the
+    /// seat whose turn it is and the other one, as `(conn, seat)`.
+    fn started_game(h: &mut Harness) -> ((ConnId, Entity), (ConnId, Entity)) {
+        started_game_with(h, |_| {})
+    }
+
+    fn started_game_with(
+        h: &mut Harness,
+        configure: impl FnOnce(&mut TableSettings),
+    ) -> ((ConnId, Entity), (ConnId, Entity)) {
+        h.connect(1, "Ana");
+        h.connect(2, "Ben");
+        let mut settings = TableSettings::new("Friday");
+        settings.max_players = 2;
+        configure(&mut settings);
+        settings.bots = vec![Controller::Bot(Difficulty::Easy)];
+        let table = h.create(1, settings);
+        h.send(2, join(table, None));
+        h.send(1, ClientMessage::Ready { ready: true });
+        h.send(2, ClientMessage::Ready { ready: true });
+        h.send(1, ClientMessage::StartGame);
+
+        let world = h.lobby.table(table).unwrap().world().unwrap();
+        let current = PlayerView::for_player(world, Entity::new(0))
+            .unwrap()
+            .current_player();
+        let seat = |conn: ConnId| Entity::new(conn - 1);
+        if current == seat(1) {
+            ((1, seat(1)), (2, seat(2)))
+        } else {
+            ((2, seat(2)), (1, seat(1)))
+        }
+    }
+
+    #[test]
+    fn a_dropped_player_gets_their_seat_back_with_what_they_missed() {
+        let mut h = Harness::new();
+        let ((active, bidder), (absent, seat)) = started_game(&mut h);
+        let token = h.lobby.tokens[&absent].clone();
+        h.drop_conn(absent);
+        assert!(h.drain(active).iter().any(|m| matches!(
+            m,
+            ServerMessage::Disconnected { player, grace_secs: 60 } if *player == seat
+        )));
+
+        h.send(
+            active,
+            ClientMessage::Event {
+                event: DudoEvent::BidMade {
+                    player: bidder,
+                    quantity: 1,
+                    face: 2,
+                },
+            },
+        );
+        h.wait(30);
+
+        let resumed = h.hello(7, "Ana again", Some(token.clone()));
+        assert_eq!(resumed, token);
+        let seen = h.drain(7);
+        let Some(ServerMessage::Resumed {
+            seat: back, missed, ..
+        }) = seen.get(1)
+        else {
+            panic!("expected to resume, got {seen:?}");
+        };
+        assert_eq!(*back, seat);
+        assert!(matches!(missed.first(), Some(DudoEvent::BidMade { .. })));
+        assert!(h.drain(active).iter().any(|m| matches!(
+            m,
+            ServerMessage::Reconnected { player } if *player == seat
+        )));
+        h.wait(60);
+        assert!(!h.lobby.table(1).unwrap().is_over());
+    }
+
+    #[test]
+    fn a_timeout_after_the_highest_bid_calls_it_and_the_game_goes_on() {
+        let mut h = Harness::new();
+        h.connect(1, "Ana");
+        h.connect(2, "Ben");
+        let mut settings = TableSettings::new("Friday");
+        settings.max_players = 2;
+        settings.rules.turn_seconds = Some(30);
+        let table = h.create(1, settings);
+        h.send(2, join(table, None));
+        h.send(1, ClientMessage::Ready { ready: true });
+        h.send(2, ClientMessage::Ready { ready: true });
+        h.send(1, ClientMessage::StartGame);
+
+        // Nothing outbids 255 aces, so the idle player calls it instead.
+        let world = h.lobby.table(table).unwrap().world().unwrap();
+        let bidder = world.resource::<TurnOrder>().unwrap().current_player();
+        h.send(
+            bidder.id + 1,
+            ClientMessage::Event {
+                event: DudoEvent::BidMade {
+                    player: bidder,
+                    quantity: u8::MAX,
+                    face: 1,
+                },
+            },
+        );
+        h.drain(1);
+        h.drain(2);
+        h.wait(1);
+        h.wait(31);
+
+        let table = h.lobby.table(table).unwrap();
+        assert!(!table.is_over());
+        let outcome = table
+            .world()
+            .unwrap()
+            .resource::<GameState>()
+            .unwrap()
+            .last_challenge
+            .clone()
+            .unwrap();
+        assert_ne!(outcome.challenger, bidder);
+        assert_eq!(outcome.loser, Some(bidder));
+        for conn in [1, 2] {
+            assert!(
+                !h.drain(conn)
+                    .iter()
+                    .any(|message| matches!(message, ServerMessage::Error { .. }))
+            );
+        }
+    }
+
+    #[test]
+    fn a_table_whose_clock_fails_is_closed_with_the_reason() {
+        let mut h = Harness::new();
+        h.connect(1, "Ana");
+        h.connect(2, "Ben");
+        let mut settings = TableSettings::new("Friday");
+        settings.max_players = 2;
+        settings.rules.turn_seconds = Some(30);
+        settings.rules.on_timeout = TimeoutAction::LoseDie;
+        let table = h.create(1, settings);
+        h.send(2, join(table, None));
+        h.send(1, ClientMessage::Ready { ready: true });
+        h.send(2, ClientMessage::Ready { ready: true });
+        h.send(1, ClientMessage::StartGame);
+
+        // A seat with no dice cannot lose one: no client can cause this.
+        let world = h.lobby.tables.get_mut(&table).unwrap().world_mut().unwrap();
+        let stranger = world.create_entity();
+        let turn_order = world.resource_mut::<TurnOrder>().unwrap();
+        let current = turn_order.current_index;
+        turn_order.players[current] = stranger;
+        h.drain(1);
+        h.drain(2);
+        h.wait(1);
+        h.wait(31);
+
+        assert!(h.lobby.table(table).is_none());
+        for conn in [1, 2] {
+            let errors: Vec<String> = h
+                .drain(conn)
+                .into_iter()
+                .filter_map(|message| match message {
+                    ServerMessage::Error { message } => Some(message),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(errors.len(), 1, "{errors:?}");
+            assert!(errors[0].contains(&format!("table {table} was closed")));
+        }
+        h.create(1, TableSettings::new("Again"));
+    }
+
+    #[test]
+    fn players_who_stay_away_forfeit_after_the_grace_period() {
+        let mut h = Harness::with_reconnect(ReconnectPolicy::new(Duration::from_secs(30)));
+        let ((active, _), (absent, seat)) = started_game(&mut h);
+        let token = h.lobby.tokens[&absent].clone();
+        h.drop_conn(absent);
+        h.wait(29);
+        h.drain(active);
+        h.wait(1);
+
+        assert!(h.drain(active).iter().any(|m| matches!(
+            m,
+            ServerMessage::Event {
+                event: DudoEvent::PlayerForfeited { player }
+            } if *player == seat
+        )));
+        let fresh = h.hello(7, "Late", Some(token.clone()));
+        assert_ne!(fresh, token);
+        assert!(
+            h.drain(7)
+                .iter()
+                .all(|m| !matches!(m, ServerMessage::Resumed { .. }))
+        );
+    }
+
+    #[test]
+    fn a_stand_in_plays_while_a_player_is_away() {
+        let mut h = Harness::with_reconnect(ReconnectPolicy {
+            stand_in: Some(Controller::Bot(Difficulty::Easy)),
+            ..ReconnectPolicy::default()
+        });
+        let ((active, seat), (other, _)) = started_game(&mut h);
+        h.drain(other);
+        h.drop_conn(active);
+
+        assert!(h.drain(other).iter().any(|m| matches!(
+            m,
+            ServerMessage::Event {
+                event: DudoEvent::BidMade { player, .. }
+            } if *player == seat
+        )));
+    }
+
+    #[test]
+    fn slow_players_are_warned_then_timed_out() {
+        let mut h = Harness::new();
+        let ((active, seat), (other, _)) =
+            started_game_with(&mut h, |settings| settings.rules.turn_seconds = Some(30));
+        h.wait(0);
+        h.drain(active);
+        h.wait(19);
+        assert!(h.drain(active).is_empty());
+        h.wait(1);
+        assert!(h.drain(active).iter().any(|m| matches!(
+            m,
+            ServerMessage::Event {
+                event: DudoEvent::TurnWarning { player, seconds_left: 10 }
+            } if *player == seat
+        )));
+        assert!(
+            h.refused(
+                other,
+                ClientMessage::Event {
+                    event: DudoEvent::TurnTimedOut { player: seat }
+                }
+            )
+            .contains("only the server")
+        );
+
+        h.wait(10);
+        assert!(h.drain(active).iter().any(|m| matches!(
+            m,
+            ServerMessage::Event {
+                event: DudoEvent::TurnTimedOut { player }
+            } if *player == seat
+        )));
+        let world = h.lobby.table(1).unwrap().world().unwrap();
+        let view = PlayerView::for_player(world, seat).unwrap();
+        assert_eq!(view.game_state.current_bid.unwrap().player, seat);
+        assert!(!view.is_my_turn());
+    }
+
+    #[test]
+    fn spectators_see_hands_only_after_the_delay() {
+        let mut h = Harness::new();
+        let ((active, seat), _) =
+            started_game_with(&mut h, |settings| settings.omniscient_delay = Some(30));
+        h.connect(3, "Eve");
+        h.drain(3);
+        h.send(
+            3,
+            ClientMessage::WatchTable {
+                table: 1,
+                password: None,
+            },
+        );
+        let seen = h.drain(3);
+        assert!(matches!(seen.first(), Some(ServerMessage::Watching { .. })));
+        assert!(matches!(
+            seen.get(1),
+            Some(ServerMessage::Spectate { view }) if !view.is_omniscient()
+        ));
+
+        let bid = DudoEvent::BidMade {
+            player: seat,
+            quantity: 1,
+            face: 2,
+        };
+        assert_eq!(
+            h.refused(3, ClientMessage::Event { event: bid.clone() }),
+            "you are not at a table"
+        );
+        h.send(active, ClientMessage::Event { event: bid });
+        h.wait(0);
+        let omniscient = |messages: Vec<ServerMessage>| {
+            messages
+                .iter()
+                .filter(|m| matches!(m, ServerMessage::Spectate { view } if view.is_omniscient()))
+                .count()
+        };
+        let live = h.drain(3);
+        assert!(live.iter().any(|m| matches!(
+            m,
+            ServerMessage::Event {
+                event: DudoEvent::BidMade { .. }
+            }
+        )));
+        assert_eq!(omniscient(live), 0);
+        h.wait(29);
+        assert_eq!(omniscient(h.drain(3)), 0);
+        h.wait(1);
+        assert!(omniscient(h.drain(3)) > 0);
+
+        h.send(3, ClientMessage::LeaveTable);
+        assert!(h.lobby.table(1).unwrap().spectators().is_empty());
+    }
+
+    #[test]
+    fn chat_reaches_the_table_except_muters_and_is_rate_limited() {
+        let mut h = Harness::new();
+        let ((speaker, _), (listener, _)) = started_game(&mut h);
+        h.connect(3, "Eve");
+        h.send(
+            3,
+            ClientMessage::WatchTable {
+                table: 1,
+                password: None,
+            },
+        );
+        let chat = |text: &str| ClientMessage::Chat {
+            body: ChatBody::Text(text.into()),
+        };
+        let heard = |messages: Vec<ServerMessage>| -> Vec<String> {
+            messages
+                .into_iter()
+                .filter_map(|m| match m {
+                    ServerMessage::Chat { message } => Some(message.to_string()),
+                    _ => None,
+                })
+                .collect()
+        };
+        let name = if speaker == 1 { "Ana" } else { "Ben" };
+        h.drain(speaker);
+        h.drain(listener);
+        h.drain(3);
+
+        h.send(speaker, chat("six fives"));
+        let line = format!("{name}: six fives");
+        assert_eq!(heard(h.drain(listener)), vec![line.clone()]);
+        assert_eq!(heard(h.drain(3)), vec![line]);
+        assert!(h.refused(3, chat("psst")).contains("spectators"));
+
+        h.send(
+            listener,
+            ClientMessage::Mute {
+                name: name.into(),
+                muted: true,
+            },
+        );
+        h.send(speaker, chat("again"));
+        assert!(heard(h.drain(listener)).is_empty());
+        assert!(
+            h.refused(
+                speaker,
+                ClientMessage::Mute {
+                    name: "Zed".into(),
+                    muted: true
+                }
+            )
+            .contains("nobody called Zed")
+        );
+
+        for _ in 0..3 {
+            h.send(speaker, chat("spam"));
+        }
+        assert!(h.refused(speaker, chat("spam")).contains("slow down"));
+        h.wait(10);
+        h.send(speaker, chat("fine now"));
+        assert_eq!(
+            heard(h.drain(3)).last().unwrap(),
+            &format!("{name}: fine now")
+        );
+    }
+
+    #[test]
+    fn verified_tables_deal_from_committed_seeds_that_check_out() {
+        let mut h = Harness::new();
+        started_game_with(&mut h, |settings| settings.verified_dice = true);
+        let phase = |h: &Harness| {
+            let world = h.lobby.table(1).unwrap().world().unwrap();
+            PlayerView::for_player(world, Entity::new(0))
+                .unwrap()
+                .game_state
+                .phase
+        };
+        assert!(h.drain(2).iter().any(|m| matches!(
+            m,
+            ServerMessage::Event {
+                event: DudoEvent::DealerCommitted { .. }
+            }
+        )));
+        assert_eq!(phase(&h), GamePhase::RoundStart);
+
+        // Ana sends her seed; Ben cannot commit behind the server's back,
+        // and is given a seed once the table stops waiting for him.
+        h.send(
+            1,
+            ClientMessage::Seed {
+                seed: Seed::random(),
+            },
+        );
+        assert_eq!(phase(&h), GamePhase::RoundStart);
+        let forged = DudoEvent::SeedCommitted {
+            player: Entity::new(1),
+            commitment: Seed::random().commitment(),
+        };
+        assert_eq!(
+            h.refused(2, ClientMessage::Event { event: forged }),
+            "only the server may send that event"
+        );
+        h.wait(0);
+        h.wait(SEED_WAIT.as_secs());
+        assert_eq!(phase(&h), GamePhase::Bidding);
+
+        // Bid once and challenge, whoever's turn it is.
+        let seat_turn = |h: &Harness| {
+            let world = h.lobby.table(1).unwrap().world().unwrap();
+            PlayerView::for_player(world, Entity::new(0))
+                .unwrap()
+                .current_player()
+        };
+        let bidder = seat_turn(&h);
+        h.send(
+            bidder.id + 1,
+            ClientMessage::Event {
+                event: DudoEvent::BidMade {
+                    player: bidder,
+                    quantity: 1,
+                    face: 2,
+                },
+            },
+        );
+        if phase(&h) == GamePhase::Bidding {
+            let challenger = seat_turn(&h);
+            h.send(
+                challenger.id + 1,
+                ClientMessage::Event {
+                    event: DudoEvent::ChallengeMade { challenger },
+                },
+            );
+        }
+
+        let mut commitments = RoundCommitments::new();
+        let mut checked = false;
+        for message in h.drain(1) {
+            match message {
+                ServerMessage::View { view } => commitments = view.commitments,
+                ServerMessage::Reveal { outcome } if !checked => {
+                    assert!(outcome.proof.is_some());
+                    assert_eq!(commitments.players.len(), 3);
+                    assert_eq!(verify_round(&commitments, &outcome), vec![]);
+                    checked = true;
+                }
+                _ => {}
+            }
+        }
+        assert!(checked);
+    }
+}
@@ -0,0 +1,10 @@
+//! Networked tables: a line-delimited JSON protocol, an authoritative
+//! server with a lobby of tables, a blocking client and a WebSocket gateway
+//! for the web client.
+
+pub mod client;
+pub mod lobby;
+pub mod protocol;
+pub mod server;
+pub mod table;
+pub mod web;
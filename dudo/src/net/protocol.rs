@@ -0,0 +1,250 @@
+//! Wire format shared by the server and its clients.
+//!
+//! Every message is one JSON object on its own line, or in its own text
+//! frame over WebSocket. Messages carry a
+//! `"type"` tag; game events reuse the serde form of [`DudoEvent`], so a
+//! bid travels as
+//!
+//! ```json
+//! {"type":"event","event":{"BidMade":{"player":{"id":1},"quantity":3,"face":4}}}
+//! ```
+//!
+//! A session runs:
+//!
+//! 1. The client sends `hello` with [`PROTOCOL_VERSION`] and a name.
+//! 2. The server answers `welcome` with a session token and the open
+//!    `tables`, or `rejected` with a reason (version mismatch) and closes.
+//! 3. In the lobby the client can `list_tables`, `create_table` (becoming
+//!    its host) or `join_table`, then mark itself `ready` or `leave_table`.
+//!    Everyone at a table gets `lobby` whenever its players change. The
+//!    host sends `start_game` once everyone is ready; tables the server
+//!    opened itself start as soon as everyone is ready.
+//! 4. When a game starts each player gets its `seat`. The server
+//!    broadcasts every `event` it applies to the table. After each change
+//!    every player gets a private `view`, its own [`PlayerView`] with only
+//!    its own dice. After a Dudo or calza everyone gets `reveal` with every
+//!    hand.
+//! 5. On its turn a client sends an `event` (`BidMade`, `ChallengeMade` or
+//!    `CalzaCalled`) for its own seat. Invalid requests are answered with
+//!    `error` and change nothing.
+//! 6. `game_over` names the winner and the players return to the lobby
+//!    with a fresh `tables` list.
+//!
+//! A client that drops mid-game has its seat held for a grace period while
+//! the others get `disconnected`. Saying `hello` again with the session
+//! token from its `welcome` gets the seat back: the server answers
+//! `welcome` and `resumed`, with the player's view and every event it
+//! missed, and tells the others `reconnected`. Once the grace period runs
+//! out the player forfeits with a `PlayerForfeited` event.
+//!
+//! Instead of joining, a client can `watch_table`. It gets `watching`,
+//! then every `event`, `reveal` and `game_over` live, with a public
+//! `spectate` view after each change. If the table's organizer allows it,
+//! `spectate` views with every hand follow after the table's delay, so a
+//! spectator cannot pass hidden dice on to a player in time. `leave_table`
+//! stops watching. Spectators cannot send gameplay events.
+//!
+//! Anyone at a table can `chat` a short text or an emote; everyone at the
+//! table and its spectators get it as `chat`, except players who `mute`d
+//! the sender. Chat is rate limited and never becomes a game event.
+//!
+//! At a table with `verified_dice`, every round opens with the server's
+//! `DealerCommitted` event. Each seated client then sends a secret `seed`,
+//! which the server publishes as a `SeedCommitted` event; seats that send
+//! none in time get a seed from the server. Hands are dealt from all the
+//! seeds together, and `reveal` carries the seeds so clients can check the
+//! deal with [`verify_round`](crate::fair_dice::verify_round).
+
+use game_engine::Entity;
+use serde::{Deserialize, Serialize};
+
+use crate::DudoEvent;
+use crate::chat::{ChatBody, ChatMessage};
+use crate::fair_dice::Seed;
+use crate::net::lobby::{LobbyPlayer, TableId, TableSettings, TableSummary};
+use crate::resources::ChallengeOutcome;
+use crate::view::{PlayerView, SpectatorView};
+
+/// Bumped whenever a message changes incompatibly.
+pub const PROTOCOL_VERSION: u32 = 7;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Hello {
+        version: u32,
+        name: String,
+        /// The token from an earlier `welcome`, to take back a held seat.
+        #[serde(default)]
+        session: Option<String>,
+    },
+    ListTables,
+    CreateTable {
+        table: TableSettings,
+    },
+    JoinTable {
+        table: TableId,
+        #[serde(default)]
+        password: Option<String>,
+    },
+    Ready {
+        ready: bool,
+    },
+    /// Leaves the table, or stops watching it.
+    LeaveTable,
+    /// Watches a table without taking a seat.
+    WatchTable {
+        table: TableId,
+        #[serde(default)]
+        password: Option<String>,
+    },
+    /// Sent by a table's host once everyone is ready.
+    StartGame,
+    Event {
+        event: DudoEvent,
+    },
+    /// The secret seed the client contributes to this round's dice, at a
+    /// table that deals verified dice.
+    Seed {
+        seed: Seed,
+    },
+    Chat {
+        body: ChatBody,
+    },
+    /// Stops or resumes delivering chat from the player called `name`.
+    Mute {
+        name: String,
+        muted: bool,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Welcome {
+        version: u32,
+        /// Identifies the client if it has to reconnect.
+        session: String,
+    },
+    /// The player the receiving client controls, sent when the game starts.
+    Seat {
+        seat: Entity,
+    },
+    Rejected {
+        reason: String,
+    },
+    Tables {
+        tables: Vec<TableSummary>,
+    },
+    /// The receiving client now sits at `table`.
+    Joined {
+        table: TableSummary,
+    },
+    /// The players at the receiving client's table, before the game starts.
+    Lobby {
+        table: TableId,
+        players: Vec<LobbyPlayer>,
+        /// Whether the receiving client hosts the table.
+        host: bool,
+    },
+    Left {
+        table: TableId,
+    },
+    /// The receiving client now watches `table`.
+    Watching {
+        table: TableSummary,
+    },
+    /// What the receiving spectator may see: the public table, or after the
+    /// table's delay every hand.
+    Spectate {
+        view: Box<SpectatorView>,
+    },
+    /// The receiving client is back in its seat after reconnecting.
+    Resumed {
+        table: TableSummary,
+        seat: Entity,
+        view: Box<PlayerView>,
+        /// Events applied while the client was away, oldest first.
+        missed: Vec<DudoEvent>,
+    },
+    /// A player dropped out; their seat is held for `grace_secs`.
+    Disconnected {
+        player: Entity,
+        grace_secs: u64,
+    },
+    Reconnected {
+        player: Entity,
+    },
+    Event {
+        event: DudoEvent,
+    },
+    /// What the receiving player may see, sent only to them.
+    View {
+        view: Box<PlayerView>,
+    },
+    Reveal {
+        outcome: ChallengeOutcome,
+    },
+    GameOver {
+        winner: Entity,
+        name: String,
+    },
+    Chat {
+        message: ChatMessage,
+    },
+    Error {
+        message: String,
+    },
+}
+
+/// Encodes a message as one protocol line, without the newline.
+pub fn encode<T: Serialize>(message: &T) -> String {
+    serde_json::to_string(message).expect("protocol messages always serialize")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_use_the_documented_shape() {
+        let bid = ClientMessage::Event {
+            event: DudoEvent::BidMade {
+                player: Entity::new(1),
+                quantity: 3,
+                face: 4,
+            },
+        };
+        assert_eq!(
+            encode(&bid),
+            r#"{"type":"event","event":{"BidMade":{"player":{"id":1},"quantity":3,"face":4}}}"#
+        );
+
+        let hello: ClientMessage =
+            serde_json::from_str(r#"{"type":"hello","version":7,"name":"Ana"}"#).unwrap();
+        assert!(matches!(
+            hello,
+            ClientMessage::Hello {
+                version: 7,
+                session: None,
+                ..
+            }
+        ));
+
+        // Table settings only need a name; everything else has a default.
+        let create: ClientMessage =
+            serde_json::from_str(r#"{"type":"create_table","table":{"name":"Friday"}}"#).unwrap();
+        let ClientMessage::CreateTable { table } = create else {
+            panic!("expected create_table");
+        };
+        assert_eq!(table, TableSettings::new("Friday"));
+
+        let emote = ClientMessage::Chat {
+            body: ChatBody::Emote(crate::chat::Emote::Laugh),
+        };
+        assert_eq!(
+            encode(&emote),
+            r#"{"type":"chat","body":{"emote":"laugh"}}"#
+        );
+    }
+}
@@ -0,0 +1,459 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, ensure};
+
+use crate::components::player::Controller;
+use crate::net::lobby::{Lobby, TableSettings};
+use crate::net::protocol::{ServerMessage, encode};
+use crate::net::table::ReconnectPolicy;
+use crate::net::web;
+
+pub type ConnId = u64;
+
+/// How often the lobby checks for players whose grace period ran out.
+const TICK: Duration = Duration::from_millis(250);
+
+/// What a transport reports to the lobby. Each connection gets a channel for
+/// its outgoing lines; dropping the sender closes the connection.
+pub enum Incoming {
+    Connected {
+        conn: ConnId,
+        outgoing: Sender<String>,
+        /// Finishes once every queued line has been written.
+        writer: JoinHandle<()>,
+    },
+    Line {
+        conn: ConnId,
+        line: String,
+    },
+    Closed {
+        conn: ConnId,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    pub seed: u64,
+    /// Tables the server keeps open, reopening each after its game ends.
+    pub tables: Vec<TableSettings>,
+    /// Shut down once the first game ends instead of serving forever.
+    pub single_game: bool,
+    pub reconnect: ReconnectPolicy,
+}
+
+impl ServerConfig {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            tables: Vec::new(),
+            single_game: false,
+            reconnect: ReconnectPolicy::default(),
+        }
+    }
+}
+
+/// Hosts a lobby of tables over TCP, and optionally WebSocket.
+pub struct Server {
+    listener: TcpListener,
+    web: Option<TcpListener>,
+    config: ServerConfig,
+}
+
+impl Server {
+    pub fn bind(addr: impl ToSocketAddrs, config: ServerConfig) -> Result<Self> {
+        for table in &config.tables {
+            table.validate()?;
+        }
+        ensure!(
+            config.reconnect.stand_in != Some(Controller::Human),
+            "a stand-in must be a bot"
+        );
+        let listener = TcpListener::bind(addr).context("failed to bind the server")?;
+        Ok(Self {
+            listener,
+            web: None,
+            config,
+        })
+    }
+
+    /// Also serves the web client and its WebSocket connections on `addr`.
+    pub fn with_web(mut self, addr: impl ToSocketAddrs) -> Result<Self> {
+        self.web = Some(TcpListener::bind(addr).context("failed to bind the web server")?);
+        Ok(self)
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    pub fn web_addr(&self) -> Result<Option<SocketAddr>> {
+        Ok(self.web.as_ref().map(TcpListener::local_addr).transpose()?)
+    }
+
+    /// Accepts clients and runs their tables. Only returns in
+    /// `single_game` mode, once that game is over.
+    pub fn run(self) -> Result<()> {
+        let (incoming, events) = mpsc::channel();
+        let ids = Arc::new(AtomicU64::new(0));
+        if let Some(web) = self.web {
+            web::spawn_web_acceptor(web, incoming.clone(), ids.clone());
+        }
+        spawn_tcp_acceptor(self.listener, incoming, ids);
+
+        let mut lobby = Lobby::new(self.config.seed).with_reconnect(self.config.reconnect);
+        for settings in self.config.tables {
+            lobby.open_table(settings, None)?;
+        }
+        let mut out = Outbox::new();
+        let mut writers = Vec::new();
+        loop {
+            let event = match events.recv_timeout(TICK) {
+                Ok(event) => Some(event),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+            let now = Instant::now();
+            match event {
+                Some(Incoming::Connected {
+                    conn,
+                    outgoing,
+                    writer,
+                }) => {
+                    out.insert(conn, outgoing);
+                    writers.push(writer);
+                }
+                Some(Incoming::Line { conn, line }) => match serde_json::from_str(&line) {
+                    Ok(message) => lobby.handle(&mut out, conn, message, now),
+                    Err(err) => out.send(
+                        conn,
+                        &ServerMessage::Error {
+                            message: format!("malformed message: {err}"),
+                        },
+                    ),
+                },
+                Some(Incoming::Closed { conn }) => {
+                    lobby.disconnect(&mut out, conn, now);
+                    out.close(conn);
+                }
+                None => {}
+            }
+            lobby.tick(&mut out, now);
+            if self.config.single_game && lobby.games_finished() > 0 {
+                break;
+            }
+        }
+
+        // Dropping the senders lets each writer finish its queue and close.
+        drop(out);
+        for writer in writers {
+            let _ = writer.join();
+        }
+        Ok(())
+    }
+}
+
+/// Outgoing channels of every open connection.
+pub struct Outbox {
+    senders: HashMap<ConnId, Sender<String>>,
+}
+
+impl Outbox {
+    pub fn new() -> Self {
+        Self {
+            senders: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, conn: ConnId, outgoing: Sender<String>) {
+        self.senders.insert(conn, outgoing);
+    }
+
+    pub fn send(&self, conn: ConnId, message: &ServerMessage) {
+        if let Some(outgoing) = self.senders.get(&conn) {
+            let _ = outgoing.send(encode(message));
+        }
+    }
+
+    pub fn send_all(&self, conns: impl IntoIterator<Item = ConnId>, message: &ServerMessage) {
+        let line = encode(message);
+        for conn in conns {
+            if let Some(outgoing) = self.senders.get(&conn) {
+                let _ = outgoing.send(line.clone());
+            }
+        }
+    }
+
+    /// Drops the connection's channel, which closes it once its queue is
+    /// written.
+    pub fn close(&mut self, conn: ConnId) {
+        self.senders.remove(&conn);
+    }
+}
+
+impl Default for Outbox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn spawn_tcp_acceptor(listener: TcpListener, incoming: Sender<Incoming>, ids: Arc<AtomicU64>) {
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else { continue };
+            let conn = ids.fetch_add(1, Ordering::Relaxed);
+            let Ok(writer) = stream.try_clone() else {
+                continue;
+            };
+            let (outgoing, lines) = mpsc::channel();
+            let writer = thread::spawn(move || write_lines(writer, lines));
+            let connected = Incoming::Connected {
+                conn,
+                outgoing,
+                writer,
+            };
+            if incoming.send(connected).is_err() {
+                return;
+            }
+            let incoming = incoming.clone();
+            thread::spawn(move || read_lines(stream, conn, incoming));
+        }
+    });
+}
+
+fn write_lines(mut stream: TcpStream, lines: Receiver<String>) {
+    for line in lines {
+        if writeln!(stream, "{line}").is_err() {
+            break;
+        }
+    }
+    let _ = stream.shutdown(Shutdown::Both);
+}
+
+fn read_lines(stream: TcpStream, conn: ConnId, incoming: Sender<Incoming>) {
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else { break };
+        if incoming.send(Incoming::Line { conn, line }).is_err() {
+            return;
+        }
+    }
+    let _ = incoming.send(Incoming::Closed { conn });
+}
+
+#[cfg(test)]
+mod tests {
+    use game_engine::Entity;
+
+    use super::*;
+    use crate::DudoEvent;
+    use crate::bot::Difficulty;
+    use crate::controller::TurnAction;
+    use crate::net::client::Client;
+    use crate::net::protocol::{ClientMessage, PROTOCOL_VERSION};
+
+    /// Serves one standing table for `humans` players and `bots`.
+    fn start(
+        humans: usize,
+        bots: Vec<Controller>,
+        seed: u64,
+    ) -> (SocketAddr, JoinHandle<Result<()>>) {
+        let mut table = TableSettings::new("Main");
+        table.min_players = humans;
+        table.max_players = humans;
+        table.bots = bots;
+        let mut config = ServerConfig::new(seed);
+        config.tables.push(table);
+        config.single_game = true;
+        let server = Server::bind("127.0.0.1:0", config).unwrap();
+        let addr = server.local_addr().unwrap();
+        (addr, thread::spawn(move || server.run()))
+    }
+
+    /// Connects and sits down, ready, at the server's table.
+    fn sit(addr: SocketAddr, name: &str) -> Client {
+        let mut client = Client::connect(addr, name).unwrap();
+        let table = client.tables().unwrap()[0].id;
+        client.join_table(table, None).unwrap();
+        client.ready(true).unwrap();
+        client
+    }
+
+    /// Opens with one two, then calls Dudo on anything else. Returns every
+    /// message the client saw.
+    fn play_simply(mut client: Client) -> Vec<ServerMessage> {
+        let mut seen = Vec::new();
+        loop {
+            let message = client.recv().unwrap();
+            seen.push(message.clone());
+            match message {
+                ServerMessage::View { view } if view.is_my_turn() => {
+                    let action = match view.game_state.current_bid {
+                        None => TurnAction::Bid {
+                            quantity: 1,
+                            face: 2,
+                        },
+                        Some(_) => TurnAction::Dudo,
+                    };
+                    client.act(action).unwrap();
+                }
+                ServerMessage::GameOver { .. } => return seen,
+                _ => {}
+            }
+        }
+    }
+
+    #[test]
+    fn clients_play_a_full_game_and_only_see_their_own_dice() {
+        let (addr, server) = start(2, Vec::new(), 7);
+        let clients: Vec<_> = ["Ana", "Ben"]
+            .into_iter()
+            .map(|name| {
+                let mut client = sit(addr, name);
+                thread::spawn(move || {
+                    let seat = client.wait_for_seat().unwrap();
+                    (seat, play_simply(client))
+                })
+            })
+            .collect();
+        let results: Vec<_> = clients.into_iter().map(|c| c.join().unwrap()).collect();
+        server.join().unwrap().unwrap();
+
+        let winners: Vec<_> = results
+            .iter()
+            .map(|(_, seen)| match seen.last() {
+                Some(ServerMessage::GameOver { winner, .. }) => *winner,
+                other => panic!("expected game over, got {other:?}"),
+            })
+            .collect();
+        assert_eq!(winners[0], winners[1]);
+
+        for (seat, seen) in &results {
+            let mut hand = None;
+            let mut hands = 0;
+            for message in seen {
+                match message {
+                    ServerMessage::View { view } => {
+                        assert_eq!(view.player, *seat);
+                        let faces: Vec<u8> = view.hand.dice.iter().filter_map(|d| d.face).collect();
+                        if hand.as_ref() != Some(&faces) {
+                            hands += 1;
+                        }
+                        hand = Some(faces);
+                    }
+                    ServerMessage::Reveal { outcome } => {
+                        let own = outcome.revealed.iter().find(|(p, _)| p == seat).unwrap();
+                        assert_eq!(Some(&own.1), hand.as_ref());
+                    }
+                    _ => {}
+                }
+            }
+            assert!(hands > 0);
+        }
+    }
+
+    #[test]
+    fn actions_are_validated_by_the_server() {
+        let (addr, _server) = start(1, vec![Controller::Bot(Difficulty::Easy)], 3);
+        let mut client = sit(addr, "Ana");
+        let seat = client.wait_for_seat().unwrap();
+        let bot = Entity::new(1 - seat.id);
+
+        client
+            .send(&ClientMessage::Event {
+                event: DudoEvent::BidMade {
+                    player: bot,
+                    quantity: 1,
+                    face: 2,
+                },
+            })
+            .unwrap();
+        client
+            .send(&ClientMessage::Event {
+                event: DudoEvent::RollDice,
+            })
+            .unwrap();
+        let mut errors = Vec::new();
+        let mut bid_sent = false;
+        while errors.len() < 3 {
+            match client.recv().unwrap() {
+                ServerMessage::Error { message } => errors.push(message),
+                // The bid is only judged on its merits once it is our turn.
+                ServerMessage::View { view } if view.is_my_turn() && !bid_sent => {
+                    client
+                        .act(TurnAction::Bid {
+                            quantity: 0,
+                            face: 9,
+                        })
+                        .unwrap();
+                    bid_sent = true;
+                }
+                _ => {}
+            }
+        }
+        assert_eq!(errors[0], "you can only act for your own seat");
+        assert_eq!(errors[1], "only the server may send that event");
+        assert!(errors[2].starts_with("Bid must be"), "{}", errors[2]);
+    }
+
+    #[test]
+    fn mismatched_versions_and_full_tables_are_rejected() {
+        let (addr, _server) = start(1, vec![Controller::Bayes], 3);
+        let mut stale = TcpStream::connect(addr).unwrap();
+        writeln!(
+            stale,
+            "{}",
+            encode(&ClientMessage::Hello {
+                version: PROTOCOL_VERSION + 1,
+                name: "Old".into(),
+                session: None,
+            })
+        )
+        .unwrap();
+        let mut line = String::new();
+        BufReader::new(stale).read_line(&mut line).unwrap();
+        assert!(
+            line.contains("rejected") && line.contains("not supported"),
+            "{line}"
+        );
+
+        let mut ana = Client::connect(addr, "Ana").unwrap();
+        ana.join_table(1, None).unwrap();
+        let mut ben = Client::connect(addr, "Ben").unwrap();
+        let err = ben.join_table(1, None).unwrap_err();
+        assert!(err.to_string().contains("full"), "{err}");
+    }
+
+    #[test]
+    fn a_client_that_drops_on_its_turn_resumes_it() {
+        let (addr, _server) = start(1, vec![Controller::Bot(Difficulty::Easy)], 5);
+        let mut client = sit(addr, "Ana");
+        let seat = client.wait_for_seat().unwrap();
+        loop {
+            if let ServerMessage::View { view } = client.recv().unwrap()
+                && view.is_my_turn()
+            {
+                break;
+            }
+        }
+        let session = client.session().to_string();
+        drop(client);
+
+        let mut client = Client::resume(addr, "Ana", &session).unwrap();
+        assert_eq!(client.session(), session);
+        let ServerMessage::Resumed {
+            seat: back, view, ..
+        } = client.recv().unwrap()
+        else {
+            panic!("expected to resume");
+        };
+        assert_eq!(back, seat);
+        assert_eq!(client.seat(), Some(seat));
+        assert!(view.is_my_turn());
+    }
+}
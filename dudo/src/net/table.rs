@@ -0,0 +1,733 @@
+//! One table in the lobby: who sits there before the game, and the
+//! authoritative [`World`] once it starts.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, bail, ensure};
+use game_engine::{Entity, World};
+
+use crate::chat::{ChatBody, ChatMessage, reaction};
+use crate::components::player::{Controller, Gamertag};
+use crate::controller::PlayerController;
+use crate::event_systems::process_events;
+use crate::events::emit;
+use crate::fair_dice::{RoundCommitments, RoundSeeds, Seed};
+use crate::net::lobby::{LobbyPlayer, TableId, TableSettings, TableSummary};
+use crate::net::protocol::ServerMessage;
+use crate::net::server::{ConnId, Outbox};
+use crate::resources::{GamePhase, GameState, TurnOrder};
+use crate::simulate::{StrategyCache, bot_for};
+use crate::systems::turn_timer::{TurnTimerSystem, revealed_hands};
+use crate::view::{PlayerView, SpectatorView};
+use crate::{DudoEvent, setup_game};
+
+/// How long a verified table waits for players' seeds before contributing
+/// one for them.
+pub const SEED_WAIT: Duration = Duration::from_secs(10);
+
+/// What a table does when a player drops out of a game in progress.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    /// How long the seat is held before the player forfeits.
+    pub grace: Duration,
+    /// Plays the absent player's turns meanwhile. Without one the game
+    /// pauses when their turn comes up.
+    pub stand_in: Option<Controller>,
+}
+
+impl ReconnectPolicy {
+    pub fn new(grace: Duration) -> Self {
+        Self {
+            grace,
+            stand_in: None,
+        }
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(60))
+    }
+}
+
+/// A client sitting at a table.
+#[derive(Debug, Clone)]
+struct Member {
+    conn: ConnId,
+    name: String,
+    ready: bool,
+    /// When the client dropped out of the game, if it is gone.
+    away: Option<Instant>,
+    /// How much of the table's history the client had seen when it dropped.
+    seen: usize,
+    /// Names whose chat is not delivered to this client.
+    muted: HashSet<String>,
+}
+
+pub struct Table {
+    id: TableId,
+    settings: TableSettings,
+    seed: u64,
+    /// The client that may start the game. Tables the server opened have
+    /// none and start as soon as everyone is ready.
+    host: Option<ConnId>,
+    /// Clients at the table, in seat order.
+    members: Vec<Member>,
+    seats: HashMap<ConnId, Entity>,
+    world: Option<World>,
+    /// Bot seats, and stand-ins for absent players.
+    bots: HashMap<Entity, Box<dyn PlayerController>>,
+    strategies: StrategyCache,
+    reconnect: ReconnectPolicy,
+    /// Every event applied to the game, for players catching up.
+    history: Vec<DudoEvent>,
+    over: bool,
+    /// When the turn clock was last moved on.
+    last_tick: Option<Instant>,
+    /// Clients watching without a seat.
+    spectators: Vec<ConnId>,
+    /// Omniscient views not yet timed, and those waiting out the delay.
+    unsent: Vec<SpectatorView>,
+    delayed: VecDeque<(Instant, SpectatorView)>,
+    /// When the table started waiting for players' seeds this round.
+    seeds_since: Option<Instant>,
+}
+
+impl Table {
+    pub fn new(
+        id: TableId,
+        settings: TableSettings,
+        seed: u64,
+        host: Option<ConnId>,
+        reconnect: ReconnectPolicy,
+    ) -> Self {
+        Self {
+            id,
+            settings,
+            seed,
+            host,
+            members: Vec::new(),
+            seats: HashMap::new(),
+            world: None,
+            bots: HashMap::new(),
+            strategies: StrategyCache::new(),
+            reconnect,
+            history: Vec::new(),
+            over: false,
+            last_tick: None,
+            spectators: Vec::new(),
+            unsent: Vec::new(),
+            delayed: VecDeque::new(),
+            seeds_since: None,
+        }
+    }
+
+    pub fn id(&self) -> TableId {
+        self.id
+    }
+
+    pub fn settings(&self) -> &TableSettings {
+        &self.settings
+    }
+
+    /// Whether a client opened this table, rather than the server.
+    pub fn is_hosted(&self) -> bool {
+        self.host.is_some()
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.world.is_some()
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.over
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    pub fn conns(&self) -> impl Iterator<Item = ConnId> + '_ {
+        self.members.iter().map(|member| member.conn)
+    }
+
+    pub fn spectators(&self) -> &[ConnId] {
+        &self.spectators
+    }
+
+    /// Players and spectators: everyone who sees public events.
+    fn audience(&self) -> impl Iterator<Item = ConnId> + '_ {
+        self.conns().chain(self.spectators.iter().copied())
+    }
+
+    pub fn world(&self) -> Option<&World> {
+        self.world.as_ref()
+    }
+
+    /// Lets tests put the game into states no client could reach.
+    #[cfg(test)]
+    pub(crate) fn world_mut(&mut self) -> Option<&mut World> {
+        self.world.as_mut()
+    }
+
+    pub fn summary(&self) -> TableSummary {
+        TableSummary {
+            id: self.id,
+            name: self.settings.name.clone(),
+            players: self.members.len(),
+            min_players: self.settings.min_players,
+            max_players: self.settings.max_players,
+            bots: self.settings.bots.len(),
+            rules: self.settings.rules,
+            password: self.settings.password.is_some(),
+            started: self.is_started(),
+            spectators: self.spectators.len(),
+            omniscient_delay: self.settings.omniscient_delay,
+            verified_dice: self.settings.verified_dice,
+        }
+    }
+
+    pub fn join(
+        &mut self,
+        out: &Outbox,
+        conn: ConnId,
+        name: &str,
+        password: Option<&str>,
+    ) -> Result<()> {
+        ensure!(!self.is_started(), "that table is already playing");
+        ensure!(
+            self.members.len() < self.settings.max_players,
+            "that table is full"
+        );
+        if let Some(expected) = &self.settings.password {
+            ensure!(password == Some(expected.as_str()), "wrong password");
+        }
+
+        self.members.push(Member {
+            conn,
+            name: name.to_string(),
+            ready: false,
+            away: None,
+            seen: 0,
+            muted: HashSet::new(),
+        });
+        out.send(
+            conn,
+            &ServerMessage::Joined {
+                table: self.summary(),
+            },
+        );
+        self.broadcast_lobby(out);
+        Ok(())
+    }
+
+    /// Removes a client from the table. A hosted table passes the host role
+    /// on to the next player in seat order.
+    pub fn leave(&mut self, out: &Outbox, conn: ConnId) {
+        self.members.retain(|member| member.conn != conn);
+        self.seats.remove(&conn);
+        if self.host == Some(conn)
+            && let Some(next) = self.members.first()
+        {
+            self.host = Some(next.conn);
+        }
+        out.send(conn, &ServerMessage::Left { table: self.id });
+        if !self.is_started() {
+            self.broadcast_lobby(out);
+        }
+    }
+
+    /// Passes a member's chat on to the table.
+    pub fn chat(&self, out: &Outbox, conn: ConnId, body: ChatBody) -> Result<()> {
+        body.validate()?;
+        let from = self
+            .members
+            .iter()
+            .find(|member| member.conn == conn)
+            .context("you are not at this table")?
+            .name
+            .clone();
+        self.send_chat(out, ChatMessage { from, body });
+        Ok(())
+    }
+
+    /// Stops or resumes delivering chat from `name` to a member.
+    pub fn mute(&mut self, conn: ConnId, name: &str, muted: bool) -> Result<()> {
+        let mut names: Vec<String> = self.members.iter().map(|m| m.name.clone()).collect();
+        if let Some(world) = &self.world {
+            let gamertags = world.query_component::<Gamertag>()?;
+            names.extend(gamertags.values().map(|gamertag| gamertag.name.clone()));
+        }
+        ensure!(
+            names.iter().any(|known| known == name),
+            "nobody called {name} is at this table"
+        );
+        let member = self.member_mut(conn)?;
+        ensure!(member.name != name, "you cannot mute yourself");
+        if muted {
+            member.muted.insert(name.to_string());
+        } else {
+            member.muted.remove(name);
+        }
+        Ok(())
+    }
+
+    /// Sends chat to the spectators and every member who has not muted its
+    /// sender.
+    fn send_chat(&self, out: &Outbox, message: ChatMessage) {
+        let listeners: Vec<ConnId> = self
+            .members
+            .iter()
+            .filter(|member| !member.muted.contains(&message.from))
+            .map(|member| member.conn)
+            .chain(self.spectators.iter().copied())
+            .collect();
+        out.send_all(listeners, &ServerMessage::Chat { message });
+    }
+
+    /// Adds a spectator, who sees the public table from now on.
+    pub fn watch(&mut self, out: &Outbox, conn: ConnId, password: Option<&str>) -> Result<()> {
+        if let Some(expected) = &self.settings.password {
+            ensure!(password == Some(expected.as_str()), "wrong password");
+        }
+        self.spectators.push(conn);
+        out.send(
+            conn,
+            &ServerMessage::Watching {
+                table: self.summary(),
+            },
+        );
+        if let Some(world) = &self.world {
+            let view = Box::new(SpectatorView::public(world)?);
+            out.send(conn, &ServerMessage::Spectate { view });
+        }
+        Ok(())
+    }
+
+    pub fn unwatch(&mut self, out: &Outbox, conn: ConnId) {
+        self.spectators.retain(|&spectator| spectator != conn);
+        out.send(conn, &ServerMessage::Left { table: self.id });
+    }
+
+    /// Handles a client whose connection closed. Mid-game its seat is held
+    /// for the grace period, played by the stand-in if there is one;
+    /// otherwise it simply leaves. Returns whether the seat is being held.
+    pub fn drop_member(&mut self, out: &Outbox, conn: ConnId, now: Instant) -> Result<bool> {
+        let Some(&seat) = self.seats.get(&conn).filter(|_| !self.over) else {
+            self.leave(out, conn);
+            return Ok(false);
+        };
+        let seen = self.history.len();
+        let member = self.member_mut(conn)?;
+        member.away = Some(now);
+        member.seen = seen;
+
+        out.send_all(
+            self.conns(),
+            &ServerMessage::Disconnected {
+                player: seat,
+                grace_secs: self.reconnect.grace.as_secs(),
+            },
+        );
+        if let Some(stand_in) = &self.reconnect.stand_in {
+            let bot = bot_for(
+                stand_in,
+                self.seed.wrapping_add(seat.id),
+                &mut self.strategies,
+            )?;
+            self.bots.insert(seat, bot);
+            self.advance(out)?;
+        }
+        Ok(true)
+    }
+
+    /// Gives a held seat to the client's new connection and catches it up
+    /// on what it missed.
+    pub fn reconnect(&mut self, out: &Outbox, old: ConnId, conn: ConnId) -> Result<()> {
+        let seat = self.seats.remove(&old).context("no seat is held for you")?;
+        self.seats.insert(conn, seat);
+        let member = self.member_mut(old)?;
+        member.conn = conn;
+        member.away = None;
+        let seen = member.seen;
+        self.bots.remove(&seat);
+
+        let world = self.world.as_ref().context("the game has not started")?;
+        out.send(
+            conn,
+            &ServerMessage::Resumed {
+                table: self.summary(),
+                seat,
+                view: Box::new(PlayerView::for_player(world, seat)?),
+                missed: self.history[seen..].to_vec(),
+            },
+        );
+        out.send_all(
+            self.conns().filter(|&other| other != conn),
+            &ServerMessage::Reconnected { player: seat },
+        );
+        Ok(())
+    }
+
+    /// Runs the turn clock and forfeits players whose grace period has run
+    /// out, returning their old connections.
+    pub fn tick(&mut self, out: &Outbox, now: Instant) -> Result<Vec<ConnId>> {
+        let elapsed = self
+            .last_tick
+            .replace(now)
+            .map_or(0.0, |last| now.duration_since(last).as_secs_f64());
+        self.release_omniscient(out, now);
+        if let Some(world) = self.world.as_mut()
+            && !self.over
+            && let Some(event) = TurnTimerSystem::run(world, elapsed)?
+        {
+            self.apply(out, event)?;
+            self.advance(out)?;
+        }
+        if self.waiting_for_seeds()?
+            && now.duration_since(*self.seeds_since.get_or_insert(now)) >= SEED_WAIT
+        {
+            self.commit_seeds(out, true)?;
+            self.advance(out)?;
+        }
+
+        let grace = self.reconnect.grace;
+        let expired: Vec<ConnId> = self
+            .members
+            .iter()
+            .filter(|m| m.away.is_some_and(|away| now.duration_since(away) >= grace))
+            .map(|m| m.conn)
+            .collect();
+        for &conn in &expired {
+            let seat = self.seats.get(&conn).copied();
+            self.members.retain(|member| member.conn != conn);
+            self.seats.remove(&conn);
+            if let Some(seat) = seat
+                && !self.over
+            {
+                self.bots.remove(&seat);
+                self.apply(out, DudoEvent::PlayerForfeited { player: seat })?;
+                self.advance(out)?;
+            }
+        }
+        Ok(expired)
+    }
+
+    fn member_mut(&mut self, conn: ConnId) -> Result<&mut Member> {
+        self.members
+            .iter_mut()
+            .find(|member| member.conn == conn)
+            .context("you are not at this table")
+    }
+
+    pub fn set_ready(&mut self, out: &Outbox, conn: ConnId, ready: bool) -> Result<()> {
+        ensure!(!self.is_started(), "the game has already started");
+        self.member_mut(conn)?.ready = ready;
+        self.broadcast_lobby(out);
+        if self.host.is_none() && self.can_start().is_ok() {
+            self.start_game(out)?;
+        }
+        Ok(())
+    }
+
+    /// Starts the game on the host's request.
+    pub fn start(&mut self, out: &Outbox, conn: ConnId) -> Result<()> {
+        ensure!(!self.is_started(), "the game has already started");
+        ensure!(self.host == Some(conn), "only the host can start the game");
+        self.can_start()?;
+        self.start_game(out)
+    }
+
+    fn can_start(&self) -> Result<()> {
+        ensure!(
+            self.members.len() >= self.settings.min_players,
+            "waiting for at least {} players",
+            self.settings.min_players
+        );
+        ensure!(
+            self.members.iter().all(|member| member.ready),
+            "not everyone is ready"
+        );
+        Ok(())
+    }
+
+    fn start_game(&mut self, out: &Outbox) -> Result<()> {
+        let mut names: Vec<String> = self.members.iter().map(|m| m.name.clone()).collect();
+        for (i, bot) in self.settings.bots.iter().enumerate() {
+            names.push(format!("Bot {} ({bot})", i + 1));
+        }
+        let mut world = setup_game(names, self.settings.rules, self.seed)?;
+        let players = world.resource::<TurnOrder>()?.players.clone();
+
+        for (member, &player) in self.members.iter().zip(&players) {
+            self.seats.insert(member.conn, player);
+        }
+        for (bot, &player) in self
+            .settings
+            .bots
+            .iter()
+            .zip(&players[self.members.len()..])
+        {
+            world.insert_component(player, bot.clone())?;
+            self.bots.insert(
+                player,
+                bot_for(bot, self.seed.wrapping_add(player.id), &mut self.strategies)?,
+            );
+        }
+        for (&conn, &seat) in &self.seats {
+            out.send(conn, &ServerMessage::Seat { seat });
+        }
+
+        self.world = Some(world);
+        self.apply(out, DudoEvent::GameReady)?;
+        self.advance(out)
+    }
+
+    /// Applies a client's move for its own seat.
+    pub fn event(&mut self, out: &Outbox, conn: ConnId, event: DudoEvent) -> Result<()> {
+        ensure!(self.is_started(), "the game has not started");
+        let seat = *self
+            .seats
+            .get(&conn)
+            .context("you are not seated at this table")?;
+        let actor = match &event {
+            DudoEvent::BidMade { player, .. } => *player,
+            DudoEvent::ChallengeMade { challenger } => *challenger,
+            DudoEvent::CalzaCalled { caller } => *caller,
+            DudoEvent::GameReady
+            | DudoEvent::RollDice
+            | DudoEvent::PlayerForfeited { .. }
+            | DudoEvent::TurnWarning { .. }
+            | DudoEvent::TurnTimedOut { .. }
+            | DudoEvent::DealerCommitted { .. }
+            | DudoEvent::SeedCommitted { .. } => {
+                bail!("only the server may send that event")
+            }
+        };
+        ensure!(actor == seat, "you can only act for your own seat");
+        self.apply(out, event)?;
+        self.advance(out)
+    }
+
+    /// Takes the secret seed a client contributes to this round's dice.
+    pub fn seed(&mut self, out: &Outbox, conn: ConnId, seed: Seed) -> Result<()> {
+        ensure!(self.is_started(), "the game has not started");
+        ensure!(
+            self.settings.verified_dice,
+            "this table does not deal verified dice"
+        );
+        let seat = *self
+            .seats
+            .get(&conn)
+            .context("you are not seated at this table")?;
+        self.contribute(out, seat, seed)?;
+        self.advance(out)
+    }
+
+    /// Commits to `seed` for `player` and keeps it for the deal.
+    fn contribute(&mut self, out: &Outbox, player: Entity, seed: Seed) -> Result<()> {
+        let commitment = seed.commitment();
+        self.apply(out, DudoEvent::SeedCommitted { player, commitment })?;
+        let world = self.world.as_mut().expect("applied above");
+        world.resource_mut::<RoundSeeds>()?.add(player, seed);
+        Ok(())
+    }
+
+    /// Commits the dealer's seed if the round has none yet, then seeds for
+    /// the seats that have no client to send one, or for every seat still
+    /// missing one if `everyone`. Returns whether every seat has committed.
+    fn commit_seeds(&mut self, out: &Outbox, everyone: bool) -> Result<bool> {
+        let world = self.world.as_mut().context("the game has not started")?;
+        let round = world.resource::<GameState>()?.round;
+        if world.resource::<RoundCommitments>()?.needs_dealer(round) {
+            let commitment = world.resource_mut::<RoundSeeds>()?.new_round(round);
+            self.apply(out, DudoEvent::DealerCommitted { commitment })?;
+        }
+
+        let present: HashSet<Entity> = self
+            .members
+            .iter()
+            .filter(|member| member.away.is_none())
+            .filter_map(|member| self.seats.get(&member.conn).copied())
+            .collect();
+        let world = self.world.as_ref().expect("checked above");
+        let commitments = world.resource::<RoundCommitments>()?;
+        let missing: Vec<Entity> = world
+            .resource::<TurnOrder>()?
+            .players
+            .iter()
+            .copied()
+            .filter(|&player| !commitments.has_committed(player))
+            .collect();
+        let mut waiting = false;
+        for player in missing {
+            if everyone || !present.contains(&player) {
+                self.contribute(out, player, Seed::random())?;
+            } else {
+                waiting = true;
+            }
+        }
+        Ok(!waiting)
+    }
+
+    /// Whether a verified round is held up by players' seeds.
+    fn waiting_for_seeds(&self) -> Result<bool> {
+        let Some(world) = self.world.as_ref().filter(|_| self.settings.verified_dice) else {
+            return Ok(false);
+        };
+        Ok(!self.over && world.resource::<GameState>()?.phase == GamePhase::RoundStart)
+    }
+
+    /// Applies one event to the world and tells the players what changed.
+    fn apply(&mut self, out: &Outbox, event: DudoEvent) -> Result<()> {
+        let world = self.world.as_mut().context("the game has not started")?;
+        let round = world.resource::<GameState>()?.round;
+        emit(world, event.clone())?;
+        process_events(world)?;
+        self.history.push(event.clone());
+
+        let conns: Vec<ConnId> = self.audience().collect();
+        out.send_all(
+            conns.iter().copied(),
+            &ServerMessage::Event {
+                event: event.clone(),
+            },
+        );
+        let world = self.world.as_ref().expect("checked above");
+        if revealed_hands(world, &event, round)?
+            && let Some(outcome) = world.resource::<GameState>()?.last_challenge.clone()
+        {
+            out.send_all(
+                conns,
+                &ServerMessage::Reveal {
+                    outcome: outcome.clone(),
+                },
+            );
+            for seat in world.resource::<TurnOrder>()?.players.clone() {
+                if world.component::<Controller>(seat)? != &Controller::Human
+                    && let Some(emote) = reaction(&outcome, seat)
+                {
+                    let from = world.component::<Gamertag>(seat)?.name.clone();
+                    self.send_chat(
+                        out,
+                        ChatMessage {
+                            from,
+                            body: ChatBody::Emote(emote),
+                        },
+                    );
+                }
+            }
+        }
+        for (&conn, &seat) in &self.seats {
+            let view = Box::new(PlayerView::for_player(world, seat)?);
+            out.send(conn, &ServerMessage::View { view });
+        }
+        let view = Box::new(SpectatorView::public(world)?);
+        out.send_all(
+            self.spectators.iter().copied(),
+            &ServerMessage::Spectate { view },
+        );
+        if self.settings.omniscient_delay.is_some() {
+            self.unsent.push(SpectatorView::omniscient(world)?);
+        }
+        Ok(())
+    }
+
+    /// Rolls new rounds and plays bot turns until a client has to act.
+    fn advance(&mut self, out: &Outbox) -> Result<()> {
+        loop {
+            let world = self.world.as_ref().context("the game has not started")?;
+            let current = world.resource::<TurnOrder>()?.current_player();
+            match world.resource::<GameState>()?.phase {
+                GamePhase::RoundStart => {
+                    if self.settings.verified_dice && !self.commit_seeds(out, false)? {
+                        return Ok(());
+                    }
+                    self.seeds_since = None;
+                    self.apply(out, DudoEvent::RollDice)?;
+                }
+                GamePhase::GameOver => {
+                    let name = world.component::<Gamertag>(current)?.name.clone();
+                    self.flush_omniscient(out);
+                    out.send_all(
+                        self.audience(),
+                        &ServerMessage::GameOver {
+                            winner: current,
+                            name,
+                        },
+                    );
+                    self.over = true;
+                    return Ok(());
+                }
+                GamePhase::Bidding if self.bots.contains_key(&current) => {
+                    let view = PlayerView::for_player(world, current)?;
+                    let bot = self.bots.get_mut(&current).expect("checked above");
+                    let Some(action) = bot.choose_action(&view)? else {
+                        bail!("bot {} passed its turn", current.id);
+                    };
+                    self.apply(out, action.into_event(current))?;
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    /// Sends spectators the omniscient views whose delay has passed. Views
+    /// are timed from the tick after they were taken.
+    fn release_omniscient(&mut self, out: &Outbox, now: Instant) {
+        let Some(delay) = self.settings.omniscient_delay else {
+            return;
+        };
+        let due = now + Duration::from_secs(delay);
+        self.delayed
+            .extend(self.unsent.drain(..).map(|view| (due, view)));
+        while let Some((at, _)) = self.delayed.front()
+            && *at <= now
+        {
+            let (_, view) = self.delayed.pop_front().expect("checked above");
+            out.send_all(
+                self.spectators.iter().copied(),
+                &ServerMessage::Spectate {
+                    view: Box::new(view),
+                },
+            );
+        }
+    }
+
+    /// Sends spectators every omniscient view still held back, once the
+    /// game is over and there are no hidden dice left to protect.
+    fn flush_omniscient(&mut self, out: &Outbox) {
+        let held = self.delayed.drain(..).map(|(_, view)| view);
+        for view in held.chain(self.unsent.drain(..)) {
+            out.send_all(
+                self.spectators.iter().copied(),
+                &ServerMessage::Spectate {
+                    view: Box::new(view),
+                },
+            );
+        }
+    }
+
+    fn broadcast_lobby(&self, out: &Outbox) {
+        let players: Vec<LobbyPlayer> = self
+            .members
+            .iter()
+            .map(|member| LobbyPlayer {
+                name: member.name.clone(),
+                ready: member.ready,
+                host: self.host == Some(member.conn),
+            })
+            .collect();
+        for member in &self.members {
+            out.send(
+                member.conn,
+                &ServerMessage::Lobby {
+                    table: self.id,
+                    players: players.clone(),
+                    host: self.host == Some(member.conn),
+                },
+            );
+        }
+    }
+}
@@ -0,0 +1,354 @@
+//! WebSocket gateway for the browser client.
+//!
+//! The web port answers plain HTTP requests with the static client in
+//! `index.html` and upgrades WebSocket requests to a connection that carries
+//! the same JSON messages as the TCP protocol, one per text frame.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+use tungstenite::protocol::Role;
+use tungstenite::{Message, WebSocket};
+
+use crate::net::server::{ConnId, Incoming};
+
+/// The static web client.
+pub const INDEX_HTML: &str = include_str!("index.html");
+
+/// Longest request head we wait for before giving up on a connection.
+const MAX_HEAD: usize = 8 * 1024;
+
+pub(crate) fn spawn_web_acceptor(
+    listener: TcpListener,
+    incoming: Sender<Incoming>,
+    ids: Arc<AtomicU64>,
+) {
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else { continue };
+            let incoming = incoming.clone();
+            let ids = ids.clone();
+            thread::spawn(move || {
+                if is_upgrade(&stream) {
+                    serve_socket(stream, incoming, ids.fetch_add(1, Ordering::Relaxed));
+                } else {
+                    serve_page(stream);
+                }
+            });
+        }
+    });
+}
+
+/// Peeks at the request head to see whether it asks for a WebSocket.
+fn is_upgrade(stream: &TcpStream) -> bool {
+    let mut buf = [0; MAX_HEAD];
+    for _ in 0..100 {
+        let Ok(read) = stream.peek(&mut buf) else {
+            return false;
+        };
+        let head = String::from_utf8_lossy(&buf[..read]).to_ascii_lowercase();
+        if head.contains("\r\n\r\n") || read == MAX_HEAD {
+            return head.contains("upgrade: websocket");
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    false
+}
+
+fn serve_page(mut stream: TcpStream) {
+    let mut reader = BufReader::new(&stream);
+    let mut line = String::new();
+    while reader.read_line(&mut line).is_ok_and(|read| read > 2) {
+        line.clear();
+    }
+    let _ = write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{INDEX_HTML}",
+        INDEX_HTML.len()
+    );
+    let _ = stream.shutdown(Shutdown::Both);
+}
+
+fn serve_socket(stream: TcpStream, incoming: Sender<Incoming>, conn: ConnId) {
+    let Ok(raw) = stream.try_clone() else { return };
+    let reader = ReadHalf {
+        stream,
+        handshake: true,
+    };
+    let Ok(mut socket) = tungstenite::accept(reader) else {
+        return;
+    };
+    socket.get_mut().handshake = false;
+    // Only the writer thread puts frames on the wire: the server's lines and
+    // the replies to the browser's pings and close both go through it.
+    let sender = WebSocket::from_raw_socket(raw, Role::Server, None);
+    let (frames, queue) = mpsc::channel();
+    let (outgoing, lines) = mpsc::channel();
+    let forward = frames.clone();
+    thread::spawn(move || forward_lines(lines, forward));
+    let writer = thread::spawn(move || write_frames(sender, queue));
+    let connected = Incoming::Connected {
+        conn,
+        outgoing,
+        writer,
+    };
+    if incoming.send(connected).is_err() {
+        return;
+    }
+
+    while let Ok(message) = socket.read() {
+        match message {
+            Message::Text(text) => {
+                let line = text.as_str().to_string();
+                if incoming.send(Incoming::Line { conn, line }).is_err() {
+                    return;
+                }
+            }
+            Message::Ping(data) => {
+                let _ = frames.send(Message::Pong(data));
+            }
+            Message::Close(frame) => {
+                let _ = frames.send(Message::Close(frame));
+                break;
+            }
+            _ => {}
+        }
+    }
+    let _ = incoming.send(Incoming::Closed { conn });
+}
+
+/// The reading side of a browser socket. Past the handshake its writes are
+/// dropped, so the frames tungstenite answers with on its own never reach
+/// the wire alongside the writer's.
+struct ReadHalf {
+    stream: TcpStream,
+    handshake: bool,
+}
+
+impl Read for ReadHalf {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.stream.read(buf)
+    }
+}
+
+impl Write for ReadHalf {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.handshake {
+            self.stream.write(buf)
+        } else {
+            Ok(buf.len())
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+/// Queues the server's lines for the writer, then asks it to close once the
+/// server lets the connection go.
+fn forward_lines(lines: Receiver<String>, frames: Sender<Message>) {
+    for line in lines {
+        if frames.send(Message::text(line)).is_err() {
+            return;
+        }
+    }
+    let _ = frames.send(Message::Close(None));
+}
+
+fn write_frames(mut socket: WebSocket<TcpStream>, frames: Receiver<Message>) {
+    for frame in frames {
+        if let Message::Close(frame) = frame {
+            let _ = socket.close(frame);
+            break;
+        }
+        if socket.send(frame).is_err() {
+            return;
+        }
+    }
+    let _ = socket.flush();
+    let _ = socket.get_ref().shutdown(Shutdown::Write);
+}
+
+#[cfg(test)]
+mod tests {
+    use game_engine::Entity;
+
+    use super::*;
+    use crate::bot::Difficulty;
+    use crate::components::player::Controller;
+    use crate::controller::TurnAction;
+    use crate::net::client::Client;
+    use crate::net::lobby::TableSettings;
+    use crate::net::protocol::{ClientMessage, PROTOCOL_VERSION, ServerMessage, encode};
+    use crate::net::server::{Server, ServerConfig};
+
+    type Browser = WebSocket<tungstenite::stream::MaybeTlsStream<TcpStream>>;
+
+    fn recv(socket: &mut Browser) -> ServerMessage {
+        loop {
+            if let Message::Text(text) = socket.read().unwrap() {
+                return serde_json::from_str(text.as_str()).unwrap();
+            }
+        }
+    }
+
+    fn send(socket: &mut Browser, message: &ClientMessage) {
+        socket.send(Message::text(encode(message))).unwrap();
+    }
+
+    fn start(bots: Vec<Controller>, seed: u64) -> (String, String) {
+        let mut table = TableSettings::new("Main");
+        table.min_players = 2;
+        table.max_players = 2;
+        table.bots = bots;
+        let mut config = ServerConfig::new(seed);
+        config.tables.push(table);
+        let server = Server::bind("127.0.0.1:0", config)
+            .unwrap()
+            .with_web("127.0.0.1:0")
+            .unwrap();
+        let tcp = server.local_addr().unwrap().to_string();
+        let web = server.web_addr().unwrap().unwrap().to_string();
+        thread::spawn(move || server.run());
+        (tcp, web)
+    }
+
+    #[test]
+    fn serves_the_web_client() {
+        let (_, web) = start(Vec::new(), 1);
+        let mut stream = TcpStream::connect(web).unwrap();
+        write!(stream, "GET / HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("new WebSocket"));
+    }
+
+    #[test]
+    fn pings_are_answered_by_the_writer() {
+        let (_, web) = start(Vec::new(), 1);
+        let (mut browser, _) = tungstenite::connect(format!("ws://{web}/ws")).unwrap();
+        browser
+            .send(Message::Ping(b"still there?".to_vec().into()))
+            .unwrap();
+        send(
+            &mut browser,
+            &ClientMessage::Hello {
+                version: PROTOCOL_VERSION,
+                name: "Web".into(),
+                session: None,
+            },
+        );
+
+        let (mut ponged, mut welcomed) = (false, false);
+        while !(ponged && welcomed) {
+            match browser.read().unwrap() {
+                Message::Pong(data) => ponged = data.as_ref() == b"still there?",
+                Message::Text(text) => {
+                    let message: ServerMessage = serde_json::from_str(text.as_str()).unwrap();
+                    welcomed |= matches!(message, ServerMessage::Welcome { .. });
+                }
+                other => panic!("unexpected frame {other:?}"),
+            }
+        }
+        browser.close(None).unwrap();
+    }
+
+    #[test]
+    fn a_browser_plays_a_round_against_a_terminal_player() {
+        let (tcp, web) = start(vec![Controller::Bot(Difficulty::Easy)], 11);
+
+        let (mut browser, _) = tungstenite::connect(format!("ws://{web}/ws")).unwrap();
+        send(
+            &mut browser,
+            &ClientMessage::Hello {
+                version: PROTOCOL_VERSION,
+                name: "Web".into(),
+                session: None,
+            },
+        );
+        assert!(matches!(recv(&mut browser), ServerMessage::Welcome { .. }));
+        let ServerMessage::Tables { tables } = recv(&mut browser) else {
+            panic!("expected the table list");
+        };
+        send(
+            &mut browser,
+            &ClientMessage::JoinTable {
+                table: tables[0].id,
+                password: None,
+            },
+        );
+        send(&mut browser, &ClientMessage::Ready { ready: true });
+
+        // The terminal player bids one two when opening and otherwise calls Dudo.
+        let terminal = thread::spawn(move || {
+            let mut client = Client::connect(tcp, "Term").unwrap();
+            client.join_table(1, None).unwrap();
+            client.ready(true).unwrap();
+            let seat = client.wait_for_seat().unwrap();
+            loop {
+                match client.recv().unwrap() {
+                    ServerMessage::View { view } if view.is_my_turn() => {
+                        assert_eq!(view.player, seat);
+                        let action = match view.game_state.current_bid {
+                            None => TurnAction::Bid {
+                                quantity: 1,
+                                face: 2,
+                            },
+                            Some(_) => TurnAction::Dudo,
+                        };
+                        client.act(action).unwrap();
+                    }
+                    ServerMessage::Reveal { .. } => return,
+                    _ => {}
+                }
+            }
+        });
+
+        let mut seat = None;
+        let mut hand = None;
+        loop {
+            match recv(&mut browser) {
+                ServerMessage::Seat { seat: s } => seat = Some(s),
+                ServerMessage::View { view } => {
+                    hand = Some(view.hand.dice.iter().filter_map(|d| d.face).collect());
+                    if !view.is_my_turn() {
+                        continue;
+                    }
+                    let event = match view.game_state.current_bid {
+                        None => TurnAction::Bid {
+                            quantity: 1,
+                            face: 3,
+                        },
+                        Some(bid) => TurnAction::Bid {
+                            quantity: bid.quantity + 1,
+                            face: bid.face,
+                        },
+                    }
+                    .into_event(view.player);
+                    send(&mut browser, &ClientMessage::Event { event });
+                }
+                ServerMessage::Reveal { outcome } => {
+                    let own = outcome
+                        .revealed
+                        .iter()
+                        .find(|(p, _)| Some(*p) == seat)
+                        .unwrap();
+                    assert_eq!(Some(&own.1), hand.as_ref());
+                    assert_eq!(outcome.revealed.len(), 3);
+                    assert_ne!(seat, Some(Entity::new(2)));
+                    break;
+                }
+                _ => {}
+            }
+        }
+        terminal.join().unwrap();
+    }
+}
@@ -0,0 +1,337 @@
+use std::collections::HashMap;
+
+use game_engine::Entity;
+
+use crate::bid::{ACES, Bid};
+use crate::probability::{binomial_pmf, rank_raises};
+use crate::resources::{DudoRules, GameState};
+use crate::view::PlayerView;
+
+/// Weight every face gets in a bidder's choice regardless of their hand, so a
+/// face they hold none of is still possible.
+const FACE_PRIOR: f64 = 0.5;
+
+/// Share of bids assumed to be bluffs on a face picked uniformly at random.
+const BLUFF_RATE: f64 = 0.3;
+
+/// Posterior over one player's hidden hand given the faces they bid on this
+/// round.
+///
+/// Hands are tracked as face counts, so five dice have 252 possible hands.
+/// Each bid is scored with a simple bidder model: honest bidders pick a face
+/// in proportion to how many of their dice count towards it, and a fixed
+/// share of bids are bluffs on any face.
+#[derive(Debug, Clone)]
+pub struct HandPosterior {
+    dice: u8,
+    bids_seen: usize,
+    /// Face counts (index 0 is aces) and their probability.
+    hands: Vec<([u8; 6], f64)>,
+}
+
+impl HandPosterior {
+    /// Every hand of `dice` fair dice, weighted by how likely it is to be rolled.
+    pub fn prior(dice: u8) -> Self {
+        let mut hands = Vec::new();
+        let mut counts = [0; 6];
+        enumerate_hands(dice, 0, &mut counts, &mut hands);
+
+        let rolls = 6f64.powi(dice as i32);
+        let hands = hands
+            .into_iter()
+            .map(|counts| (counts, arrangements(&counts) / rolls))
+            .collect();
+        Self {
+            dice,
+            bids_seen: 0,
+            hands,
+        }
+    }
+
+    pub fn dice(&self) -> u8 {
+        self.dice
+    }
+
+    /// Bids folded into this posterior so far.
+    pub fn bids_seen(&self) -> usize {
+        self.bids_seen
+    }
+
+    /// Bayes update for this player having bid on `face`.
+    pub fn observe(&mut self, face: u8, wild_ones: bool) {
+        for (counts, weight) in &mut self.hands {
+            *weight *= bid_likelihood(counts, face, wild_ones);
+        }
+        let total: f64 = self.hands.iter().map(|(_, w)| w).sum();
+        if total > 0.0 {
+            for (_, weight) in &mut self.hands {
+                *weight /= total;
+            }
+        }
+        self.bids_seen += 1;
+    }
+
+    /// Distribution of how many of this player's dice count towards `face`,
+    /// indexed by count.
+    pub fn count_distribution(&self, face: u8, wild_ones: bool) -> Vec<f64> {
+        let mut distribution = vec![0.0; self.dice as usize + 1];
+        for (counts, weight) in &self.hands {
+            distribution[matches(counts, face, wild_ones) as usize] += weight;
+        }
+        distribution
+    }
+
+    /// Expected number of dice counting towards each face, aces first.
+    pub fn expected_counts(&self, wild_ones: bool) -> [f64; 6] {
+        let mut expected = [0.0; 6];
+        for (face, slot) in (1..=6).zip(&mut expected) {
+            *slot = self
+                .count_distribution(face, wild_ones)
+                .iter()
+                .enumerate()
+                .map(|(count, p)| count as f64 * p)
+                .sum();
+        }
+        expected
+    }
+}
+
+/// What the table has revealed about every player's hand this round, built
+/// from their bids. Bids are public, so one set of beliefs serves every
+/// seat.
+#[derive(Debug, Clone)]
+pub struct HandBeliefs {
+    posteriors: HashMap<Entity, HandPosterior>,
+}
+
+impl HandBeliefs {
+    pub fn new() -> Self {
+        Self {
+            posteriors: HashMap::new(),
+        }
+    }
+
+    /// Replays `bids`, with `dice` giving each bidder's dice count.
+    pub fn from_bids(bids: &[Bid], dice: impl Fn(Entity) -> u8, wild_ones: bool) -> Self {
+        let mut beliefs = Self::new();
+        for bid in bids {
+            beliefs.observe(bid, dice(bid.player), wild_ones);
+        }
+        beliefs
+    }
+
+    pub fn observe(&mut self, bid: &Bid, dice: u8, wild_ones: bool) {
+        self.posteriors
+            .entry(bid.player)
+            .or_insert_with(|| HandPosterior::prior(dice))
+            .observe(bid.face, wild_ones);
+    }
+
+    /// The posterior for `player`, if they have bid this round.
+    pub fn posterior(&self, player: Entity) -> Option<&HandPosterior> {
+        self.posteriors.get(&player)
+    }
+
+    /// Like `HandPosterior::count_distribution`, falling back to fair dice
+    /// for players who have not bid yet.
+    pub fn count_distribution(&self, player: Entity, dice: u8, face: u8, wild: bool) -> Vec<f64> {
+        match self.posterior(player) {
+            Some(posterior) => posterior.count_distribution(face, wild),
+            None => {
+                let p = if wild && face != ACES {
+                    2.0 / 6.0
+                } else {
+                    1.0 / 6.0
+                };
+                (0..=dice).map(|k| binomial_pmf(dice, k, p)).collect()
+            }
+        }
+    }
+}
+
+impl Default for HandBeliefs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Odds of bids from one player's point of view, using the `HandBeliefs` of
+/// their `PlayerView` for everyone else's dice instead of assuming fair rolls.
+#[derive(Debug, Clone)]
+pub struct BeliefOdds {
+    /// Distribution of the table total for each face, aces first.
+    totals: Vec<Vec<f64>>,
+}
+
+impl BeliefOdds {
+    pub fn from_view(view: &PlayerView) -> Self {
+        let beliefs = view.beliefs();
+        let wild_ones = view.ones_are_wild();
+        let own_faces: Vec<u8> = view.hand.dice.iter().filter_map(|d| d.face).collect();
+
+        let mut totals = Vec::new();
+        for face in 1..=6 {
+            let own = own_faces
+                .iter()
+                .filter(|&&f| f == face || (wild_ones && face != ACES && f == ACES))
+                .count();
+            let mut total = vec![0.0; own + 1];
+            total[own] = 1.0;
+            for &other in &view.turn_order.players {
+                if other == view.player {
+                    continue;
+                }
+                let dice = view.dice_count(other);
+                total = convolve(
+                    &total,
+                    &beliefs.count_distribution(other, dice, face, wild_ones),
+                );
+            }
+            totals.push(total);
+        }
+        Self { totals }
+    }
+
+    pub fn total_dice(&self) -> u8 {
+        (self.totals[0].len() - 1) as u8
+    }
+
+    pub fn chance_at_least(&self, quantity: u8, face: u8) -> f64 {
+        let total = &self.totals[face as usize - 1];
+        let tail: f64 = total.iter().skip(quantity as usize).sum();
+        tail.min(1.0)
+    }
+
+    pub fn chance_exactly(&self, quantity: u8, face: u8) -> f64 {
+        self.totals[face as usize - 1]
+            .get(quantity as usize)
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    pub fn ranked_raises(
+        &self,
+        game_state: &GameState,
+        rules: &DudoRules,
+        player: Entity,
+    ) -> Vec<(Bid, f64)> {
+        rank_raises(self.total_dice(), game_state, rules, player, |q, f| {
+            self.chance_at_least(q, f)
+        })
+    }
+}
+
+fn enumerate_hands(left: u8, face: usize, counts: &mut [u8; 6], out: &mut Vec<[u8; 6]>) {
+    if face == 5 {
+        counts[5] = left;
+        out.push(*counts);
+        return;
+    }
+    for n in 0..=left {
+        counts[face] = n;
+        enumerate_hands(left - n, face + 1, counts, out);
+    }
+}
+
+/// Number of ordered rolls that give these face counts.
+fn arrangements(counts: &[u8; 6]) -> f64 {
+    let factorial = |n: u8| (1..=n as u64).product::<u64>() as f64;
+    let dice = counts.iter().sum();
+    factorial(dice) / counts.iter().map(|&c| factorial(c)).product::<f64>()
+}
+
+fn matches(counts: &[u8; 6], face: u8, wild_ones: bool) -> u8 {
+    let own = counts[face as usize - 1];
+    if wild_ones && face != ACES {
+        own + counts[0]
+    } else {
+        own
+    }
+}
+
+/// Chance a player holding `counts` bids on `face`.
+fn bid_likelihood(counts: &[u8; 6], face: u8, wild_ones: bool) -> f64 {
+    let support = |f: u8| FACE_PRIOR + matches(counts, f, wild_ones) as f64;
+    let honest = support(face) / (1..=6).map(support).sum::<f64>();
+    (1.0 - BLUFF_RATE) * honest + BLUFF_RATE / 6.0
+}
+
+fn convolve(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut out = vec![0.0; a.len() + b.len() - 1];
+    for (i, pa) in a.iter().enumerate() {
+        for (j, pb) in b.iter().enumerate() {
+            out[i + j] += pa * pb;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::dice::Hand;
+    use crate::probability::BidOdds;
+    use crate::resources::{BidHistory, GamePhase, TurnOrder};
+    use crate::setup_game;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn prior_matches_fair_dice() {
+        let prior = HandPosterior::prior(5);
+        assert_eq!(prior.hands.len(), 252);
+        let total: f64 = prior.hands.iter().map(|(_, w)| w).sum();
+        assert!(close(total, 1.0));
+
+        let fives = prior.count_distribution(5, true);
+        for (k, p) in fives.iter().enumerate() {
+            assert!(close(*p, binomial_pmf(5, k as u8, 1.0 / 3.0)), "k = {k}");
+        }
+    }
+
+    #[test]
+    fn bidding_on_a_face_raises_its_expected_count() {
+        let mut posterior = HandPosterior::prior(5);
+        let before = posterior.expected_counts(true);
+        posterior.observe(4, true);
+        posterior.observe(4, true);
+        let after = posterior.expected_counts(true);
+
+        assert!(after[3] > before[3]);
+        assert!(after[5] < before[5]);
+        assert_eq!(posterior.bids_seen(), 2);
+        let total: f64 = posterior.hands.iter().map(|(_, w)| w).sum();
+        assert!(close(total, 1.0));
+    }
+
+    #[test]
+    fn belief_odds_without_bids_match_fair_odds() {
+        let names = vec!["Ana".to_string(), "Ben".to_string(), "Cid".to_string()];
+        let mut world = setup_game(names, DudoRules::perudo(), 1).unwrap();
+        let players = world.resource::<TurnOrder>().unwrap().players.clone();
+        *world.component_mut::<Hand>(players[0]).unwrap() = Hand::from_faces(&[1, 3, 3, 5, 6]);
+        world.resource_mut::<GameState>().unwrap().phase = GamePhase::Bidding;
+
+        let view = PlayerView::for_player(&world, players[0]).unwrap();
+        let beliefs = BeliefOdds::from_view(&view);
+        let fair = BidOdds::from_view(&view);
+        for quantity in 0..=15 {
+            for face in 1..=6 {
+                assert!(close(
+                    beliefs.chance_at_least(quantity, face),
+                    fair.chance_at_least(quantity, face)
+                ));
+            }
+        }
+
+        world
+            .resource_mut::<BidHistory>()
+            .unwrap()
+            .push(Bid::new(players[1], 3, 6));
+        let view = PlayerView::for_player(&world, players[0]).unwrap();
+        let informed = BeliefOdds::from_view(&view);
+        assert!(informed.chance_at_least(5, 6) > fair.chance_at_least(5, 6));
+    }
+}
@@ -0,0 +1,681 @@
+//! Hidden dice for peer-to-peer play, where no one deals and no one sees
+//! another player's hand before it is revealed.
+//!
+//! Each round every peer runs a [`PeerRound`], broadcasting the messages it
+//! returns to every other peer and feeding it theirs. The round goes:
+//!
+//! 1. Every peer commits to two secrets: some entropy, and a private seed.
+//! 2. Once a peer has every commitment it reveals its entropy. The entropy
+//!    of all peers, hashed together, is the round's joint randomness.
+//! 3. Each peer deals its own hand from the joint randomness and its
+//!    private seed, and commits to a hash of the hand.
+//! 4. At a challenge every peer reveals its seed and hand, and the others
+//!    check the hand is the one the seed deals and the one it committed to.
+//!
+//! Nobody can steer the joint randomness, since every contribution was
+//! fixed before any was seen, and nobody can steer their own hand, since
+//! their seed was fixed before the joint randomness was known. Nobody else
+//! knows a hand until its seed is revealed. A peer that changes anything it
+//! committed to is reported as a [`Violation`].
+//!
+//! A peer can still refuse to reveal its entropy once it has seen everyone
+//! else's, stalling the round; the caller decides how long to wait. The
+//! protocol relies on each peer's messages arriving in the order they were
+//! sent, but not on any order between peers.
+//!
+//! This is the protocol alone: no front end plays peer-to-peer yet, and
+//! networked tables are dealt by the server with [`crate::fair_dice`].
+
+use std::fmt;
+
+use anyhow::{Result, anyhow, bail, ensure};
+use game_engine::Entity;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::components::dice::Hand;
+use crate::fair_dice::{Bytes32, Commitment, Seed, hash};
+
+/// What one peer broadcasts to the others.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PeerMessage {
+    Commit {
+        round: u32,
+        entropy: Commitment,
+        seed: Commitment,
+    },
+    Entropy {
+        round: u32,
+        entropy: Bytes32,
+    },
+    HandCommitted {
+        round: u32,
+        hand: Commitment,
+    },
+    Reveal {
+        round: u32,
+        seed: Seed,
+        faces: Vec<u8>,
+    },
+}
+
+impl PeerMessage {
+    pub fn round(&self) -> u32 {
+        match *self {
+            PeerMessage::Commit { round, .. }
+            | PeerMessage::Entropy { round, .. }
+            | PeerMessage::HandCommitted { round, .. }
+            | PeerMessage::Reveal { round, .. } => round,
+        }
+    }
+}
+
+/// A peer caught breaking the protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Violation {
+    /// Sent a second message of a kind it may only send once.
+    Equivocation { peer: Entity },
+    /// Sent a message before the one it depends on.
+    OutOfOrder { peer: Entity },
+    /// Revealed entropy that does not match its commitment.
+    Entropy { peer: Entity },
+    /// Revealed a seed that does not match its commitment.
+    Seed { peer: Entity },
+    /// Revealed a hand its seed does not deal.
+    Hand { peer: Entity },
+    /// Revealed a hand other than the one it committed to.
+    HandCommitment { peer: Entity },
+}
+
+impl Violation {
+    pub fn peer(self) -> Entity {
+        match self {
+            Violation::Equivocation { peer }
+            | Violation::OutOfOrder { peer }
+            | Violation::Entropy { peer }
+            | Violation::Seed { peer }
+            | Violation::Hand { peer }
+            | Violation::HandCommitment { peer } => peer,
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let peer = self.peer().id;
+        match self {
+            Violation::Equivocation { .. } => write!(f, "peer {peer} sent a step twice"),
+            Violation::OutOfOrder { .. } => write!(f, "peer {peer} skipped a step"),
+            Violation::Entropy { .. } => write!(f, "peer {peer} changed its entropy"),
+            Violation::Seed { .. } => write!(f, "peer {peer} changed its seed"),
+            Violation::Hand { .. } => write!(f, "peer {peer} revealed dice its seed does not deal"),
+            Violation::HandCommitment { .. } => {
+                write!(f, "peer {peer} revealed dice it did not commit to")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Violation {}
+
+/// Where a round stands for one peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeerPhase {
+    /// Waiting for every peer's commitments.
+    Committing,
+    /// Waiting for every peer's entropy.
+    Mixing,
+    /// Our hand is dealt; waiting for every peer's hand commitment.
+    Dealing,
+    /// Every hand is committed and the round can be played.
+    Playing,
+    /// Waiting for every peer to reveal.
+    Revealing,
+    /// Every hand is revealed and checked.
+    Revealed,
+}
+
+/// What we know about one peer this round.
+#[derive(Debug, Clone, Default)]
+struct PeerState {
+    dice: usize,
+    entropy_commitment: Option<Commitment>,
+    seed_commitment: Option<Commitment>,
+    entropy: Option<Bytes32>,
+    hand_commitment: Option<Commitment>,
+    /// Whether it has revealed, so a reveal that failed its checks cannot
+    /// be sent again corrected.
+    revealed: bool,
+    /// Revealed but not yet checked, until the joint randomness is known.
+    reveal: Option<(Seed, Vec<u8>)>,
+    faces: Option<Vec<u8>>,
+}
+
+/// One peer's side of a round.
+#[derive(Debug, Clone)]
+pub struct PeerRound {
+    me: Entity,
+    round: u32,
+    entropy: Bytes32,
+    seed: Seed,
+    /// Every peer, ourselves included, in seat order.
+    peers: Vec<(Entity, PeerState)>,
+    joint: Option<Bytes32>,
+    entropy_sent: bool,
+    revealing: bool,
+}
+
+impl PeerRound {
+    /// Starts `round` for peer `me`, given how many dice every peer at the
+    /// table holds, `me` included. Returns the commitment to broadcast.
+    pub fn new(
+        me: Entity,
+        round: u32,
+        dice: impl IntoIterator<Item = (Entity, usize)>,
+    ) -> Result<(Self, PeerMessage)> {
+        let (entropy, seed) = (Bytes32::random(), Seed::random());
+        let mut peers: Vec<(Entity, PeerState)> = dice
+            .into_iter()
+            .map(|(peer, dice)| {
+                (
+                    peer,
+                    PeerState {
+                        dice,
+                        ..PeerState::default()
+                    },
+                )
+            })
+            .collect();
+        peers.sort_by_key(|(peer, _)| peer.id);
+        let (_, mine) = peers
+            .iter_mut()
+            .find(|(peer, _)| *peer == me)
+            .ok_or_else(|| anyhow!("peer {} is not at the table", me.id))?;
+        let commit = PeerMessage::Commit {
+            round,
+            entropy: entropy.commitment(),
+            seed: seed.commitment(),
+        };
+        mine.entropy_commitment = Some(entropy.commitment());
+        mine.seed_commitment = Some(seed.commitment());
+        Ok((
+            Self {
+                me,
+                round,
+                entropy,
+                seed,
+                peers,
+                joint: None,
+                entropy_sent: false,
+                revealing: false,
+            },
+            commit,
+        ))
+    }
+
+    pub fn round(&self) -> u32 {
+        self.round
+    }
+
+    pub fn phase(&self) -> PeerPhase {
+        let all = |f: fn(&PeerState) -> bool| self.peers.iter().all(|(_, peer)| f(peer));
+        if all(|peer| peer.faces.is_some()) {
+            PeerPhase::Revealed
+        } else if self.revealing {
+            PeerPhase::Revealing
+        } else if all(|peer| peer.hand_commitment.is_some()) {
+            PeerPhase::Playing
+        } else if self.joint.is_some() {
+            PeerPhase::Dealing
+        } else if self.entropy_sent {
+            PeerPhase::Mixing
+        } else {
+            PeerPhase::Committing
+        }
+    }
+
+    /// Our own hand, once it is dealt.
+    pub fn hand(&self) -> Option<Hand> {
+        self.joint
+            .map(|joint| Hand::from_faces(&self.deal(joint, self.me, self.seed)))
+    }
+
+    /// Every hand, once all of them are revealed and checked.
+    pub fn revealed(&self) -> Option<Vec<(Entity, Vec<u8>)>> {
+        self.peers
+            .iter()
+            .map(|(peer, state)| Some((*peer, state.faces.clone()?)))
+            .collect()
+    }
+
+    /// Handles a message broadcast by `from`, returning what we broadcast
+    /// in turn. A peer breaking the protocol fails with a [`Violation`].
+    pub fn receive(&mut self, from: Entity, message: PeerMessage) -> Result<Vec<PeerMessage>> {
+        ensure!(from != self.me, "peers do not receive their own messages");
+        ensure!(
+            message.round() == self.round,
+            "peer {} sent a message for round {} during round {}",
+            from.id,
+            message.round(),
+            self.round
+        );
+        let Some((_, peer)) = self.peers.iter_mut().find(|(peer, _)| *peer == from) else {
+            bail!("peer {} is not at the table", from.id);
+        };
+        let once = |slot: bool| {
+            if slot {
+                Err(Violation::Equivocation { peer: from })
+            } else {
+                Ok(())
+            }
+        };
+        let after = |prior: bool| {
+            if prior {
+                Ok(())
+            } else {
+                Err(Violation::OutOfOrder { peer: from })
+            }
+        };
+        match message {
+            PeerMessage::Commit { entropy, seed, .. } => {
+                once(peer.entropy_commitment.is_some())?;
+                peer.entropy_commitment = Some(entropy);
+                peer.seed_commitment = Some(seed);
+            }
+            PeerMessage::Entropy { entropy, .. } => {
+                after(peer.entropy_commitment.is_some())?;
+                once(peer.entropy.is_some())?;
+                if peer.entropy_commitment != Some(entropy.commitment()) {
+                    bail!(Violation::Entropy { peer: from });
+                }
+                peer.entropy = Some(entropy);
+            }
+            PeerMessage::HandCommitted { hand, .. } => {
+                after(peer.entropy.is_some())?;
+                once(peer.hand_commitment.is_some())?;
+                peer.hand_commitment = Some(hand);
+            }
+            PeerMessage::Reveal { seed, faces, .. } => {
+                after(peer.hand_commitment.is_some())?;
+                once(peer.revealed)?;
+                peer.revealed = true;
+                peer.reveal = Some((seed, faces));
+            }
+        }
+        self.progress()
+    }
+
+    /// Reveals our seed and hand for a challenge. Every hand must be
+    /// committed first.
+    pub fn reveal(&mut self) -> Result<PeerMessage> {
+        ensure!(
+            matches!(self.phase(), PeerPhase::Playing | PeerPhase::Revealing),
+            "not every hand is committed yet"
+        );
+        let faces = self.deal(self.joint.expect("dealt"), self.me, self.seed);
+        self.revealing = true;
+        self.mine().faces = Some(faces.clone());
+        Ok(PeerMessage::Reveal {
+            round: self.round,
+            seed: self.seed,
+            faces,
+        })
+    }
+
+    /// Takes whatever step the messages so far allow.
+    fn progress(&mut self) -> Result<Vec<PeerMessage>> {
+        let mut outgoing = Vec::new();
+        if !self.entropy_sent
+            && self
+                .peers
+                .iter()
+                .all(|(_, p)| p.entropy_commitment.is_some())
+        {
+            self.entropy_sent = true;
+            self.mine().entropy = Some(self.entropy);
+            outgoing.push(PeerMessage::Entropy {
+                round: self.round,
+                entropy: self.entropy,
+            });
+        }
+        if self.joint.is_none() && self.peers.iter().all(|(_, p)| p.entropy.is_some()) {
+            let round = self.round.to_le_bytes();
+            let mut parts: Vec<&[u8]> = vec![b"dudo-joint", &round];
+            parts.extend(
+                self.peers
+                    .iter()
+                    .filter_map(|(_, p)| Some(&p.entropy.as_ref()?.0[..])),
+            );
+            let joint = hash(&parts);
+            self.joint = Some(joint);
+
+            let faces = self.deal(joint, self.me, self.seed);
+            let hand = hand_commitment(self.round, self.me, &faces, self.seed);
+            self.mine().hand_commitment = Some(hand);
+            outgoing.push(PeerMessage::HandCommitted {
+                round: self.round,
+                hand,
+            });
+        }
+        if let Some(joint) = self.joint {
+            for (peer, state) in &mut self.peers {
+                let peer = *peer;
+                let Some((seed, faces)) = state.reveal.take() else {
+                    continue;
+                };
+                if state.seed_commitment != Some(seed.commitment()) {
+                    bail!(Violation::Seed { peer });
+                }
+                if deal(self.round, joint, peer, seed, state.dice) != faces {
+                    bail!(Violation::Hand { peer });
+                }
+                if state.hand_commitment != Some(hand_commitment(self.round, peer, &faces, seed)) {
+                    bail!(Violation::HandCommitment { peer });
+                }
+                state.faces = Some(faces);
+            }
+        }
+        Ok(outgoing)
+    }
+
+    fn mine(&mut self) -> &mut PeerState {
+        let me = self.me;
+        let mine = self.peers.iter_mut().find(|(peer, _)| *peer == me);
+        &mut mine.expect("checked in new").1
+    }
+
+    fn deal(&self, joint: Bytes32, peer: Entity, seed: Seed) -> Vec<u8> {
+        let (_, state) = self.peers.iter().find(|(p, _)| *p == peer).expect("a peer");
+        deal(self.round, joint, peer, seed, state.dice)
+    }
+}
+
+/// The hand `peer` deals itself from the joint randomness and its seed.
+fn deal(round: u32, joint: Bytes32, peer: Entity, seed: Seed, count: usize) -> Vec<u8> {
+    let key = hash(&[
+        b"dudo-peer-hand",
+        &round.to_le_bytes(),
+        &joint.0,
+        &peer.id.to_le_bytes(),
+        &seed.0,
+    ]);
+    let mut rng = ChaCha8Rng::from_seed(key.0);
+    (0..count).map(|_| rng.random_range(1..7)).collect()
+}
+
+/// Binds a peer to its hand. The secret seed keeps the few possible hands
+/// from being guessed by hashing each of them.
+fn hand_commitment(round: u32, peer: Entity, faces: &[u8], seed: Seed) -> Commitment {
+    hash(&[
+        b"dudo-peer-commit",
+        &round.to_le_bytes(),
+        &peer.id.to_le_bytes(),
+        faces,
+        &seed.0,
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, VecDeque};
+
+    use super::*;
+
+    /// How a simulated peer tampers with what it broadcasts.
+    type Tamper = Box<dyn FnMut(PeerMessage) -> PeerMessage>;
+
+    /// Peers connected by an in-memory broadcast, some of them dishonest.
+    struct Table {
+        peers: Vec<PeerRound>,
+        tamper: HashMap<Entity, Tamper>,
+        queue: VecDeque<(Entity, PeerMessage)>,
+    }
+
+    impl Table {
+        fn new(dice: &[usize]) -> Self {
+            let seats: Vec<(Entity, usize)> = dice
+                .iter()
+                .enumerate()
+                .map(|(i, &n)| (Entity::new(i as u64), n))
+                .collect();
+            let mut queue = VecDeque::new();
+            let peers = seats
+                .iter()
+                .map(|&(me, _)| {
+                    let (peer, commit) = PeerRound::new(me, 1, seats.clone()).unwrap();
+                    queue.push_back((me, commit));
+                    peer
+                })
+                .collect();
+            Self {
+                peers,
+                tamper: HashMap::new(),
+                queue,
+            }
+        }
+
+        fn cheat(&mut self, peer: u64, tamper: impl FnMut(PeerMessage) -> PeerMessage + 'static) {
+            self.tamper.insert(Entity::new(peer), Box::new(tamper));
+        }
+
+        fn broadcast(&mut self, from: Entity, message: PeerMessage) {
+            let message = match self.tamper.get_mut(&from) {
+                Some(tamper) => tamper(message),
+                None => message,
+            };
+            self.queue.push_back((from, message));
+        }
+
+        /// Delivers everything queued, returning the violations honest peers
+        /// reported.
+        fn run(&mut self) -> Vec<Violation> {
+            let mut caught = Vec::new();
+            while let Some((from, message)) = self.queue.pop_front() {
+                for i in 0..self.peers.len() {
+                    let me = self.peers[i].me;
+                    if me == from || self.tamper.contains_key(&me) {
+                        continue;
+                    }
+                    match self.peers[i].receive(from, message.clone()) {
+                        Ok(replies) => {
+                            for reply in replies {
+                                self.broadcast(me, reply);
+                            }
+                        }
+                        Err(err) => caught.push(*err.downcast_ref::<Violation>().unwrap()),
+                    }
+                }
+                // Dishonest peers follow along without checking anyone.
+                for i in 0..self.peers.len() {
+                    let me = self.peers[i].me;
+                    if me != from && self.tamper.contains_key(&me) {
+                        let replies = self.peers[i].receive(from, message.clone());
+                        for reply in replies.unwrap_or_default() {
+                            self.broadcast(me, reply);
+                        }
+                    }
+                }
+            }
+            caught
+        }
+
+        fn reveal_all(&mut self) -> Vec<Violation> {
+            for i in 0..self.peers.len() {
+                let reveal = self.peers[i].reveal().unwrap();
+                let me = self.peers[i].me;
+                self.broadcast(me, reveal);
+            }
+            self.run()
+        }
+    }
+
+    #[test]
+    fn honest_peers_see_only_their_own_hand_until_the_reveal() {
+        let mut table = Table::new(&[5, 3, 1]);
+        assert_eq!(table.run(), vec![]);
+        for (peer, dice) in table.peers.iter().zip([5, 3, 1]) {
+            assert_eq!(peer.phase(), PeerPhase::Playing);
+            assert_eq!(peer.hand().unwrap().dice.len(), dice);
+            assert!(peer.revealed().is_none());
+        }
+
+        assert_eq!(table.reveal_all(), vec![]);
+        let hands: Vec<(Entity, Vec<u8>)> = table
+            .peers
+            .iter()
+            .map(|peer| {
+                let hand = peer.hand().unwrap();
+                (peer.me, hand.dice.iter().map(|d| d.face.unwrap()).collect())
+            })
+            .collect();
+        for peer in &table.peers {
+            assert_eq!(peer.phase(), PeerPhase::Revealed);
+            assert_eq!(peer.revealed().unwrap(), hands);
+        }
+    }
+
+    #[test]
+    fn peers_cannot_reveal_other_dice_than_they_were_dealt() {
+        let mut table = Table::new(&[5, 5, 5]);
+        table.cheat(1, |message| match message {
+            PeerMessage::Reveal {
+                round,
+                seed,
+                mut faces,
+            } => {
+                faces[0] = faces[0] % 6 + 1;
+                PeerMessage::Reveal { round, seed, faces }
+            }
+            other => other,
+        });
+        table.run();
+        assert_eq!(
+            table.reveal_all(),
+            vec![
+                Violation::Hand {
+                    peer: Entity::new(1)
+                };
+                2
+            ]
+        );
+    }
+
+    #[test]
+    fn a_peer_caught_revealing_other_dice_cannot_try_again() {
+        let mut table = Table::new(&[5, 5, 5]);
+        table.cheat(1, |message| match message {
+            PeerMessage::Reveal {
+                round,
+                seed,
+                mut faces,
+            } => {
+                faces[0] = faces[0] % 6 + 1;
+                PeerMessage::Reveal { round, seed, faces }
+            }
+            other => other,
+        });
+        table.run();
+        let cheat = Entity::new(1);
+        assert_eq!(table.reveal_all(), vec![Violation::Hand { peer: cheat }; 2]);
+
+        let cheater = &table.peers[1];
+        let honest = PeerMessage::Reveal {
+            round: 1,
+            seed: cheater.seed,
+            faces: cheater.deal(cheater.joint.unwrap(), cheat, cheater.seed),
+        };
+        table.queue.push_back((cheat, honest));
+        assert_eq!(
+            table.run(),
+            vec![Violation::Equivocation { peer: cheat }; 2]
+        );
+        assert!(table.peers[0].revealed().is_none());
+        assert!(table.peers[2].revealed().is_none());
+    }
+
+    #[test]
+    fn peers_cannot_swap_in_a_seed_that_deals_better_dice() {
+        let mut table = Table::new(&[5, 5]);
+        table.cheat(1, |message| match message {
+            PeerMessage::Reveal { round, faces, .. } => PeerMessage::Reveal {
+                round,
+                seed: Seed::random(),
+                faces,
+            },
+            other => other,
+        });
+        table.run();
+        assert_eq!(
+            table.reveal_all(),
+            vec![Violation::Seed {
+                peer: Entity::new(1)
+            }]
+        );
+    }
+
+    #[test]
+    fn peers_cannot_commit_to_one_hand_and_reveal_another() {
+        let mut table = Table::new(&[5, 5]);
+        table.cheat(1, |message| match message {
+            PeerMessage::HandCommitted { round, .. } => PeerMessage::HandCommitted {
+                round,
+                hand: Seed::random().commitment(),
+            },
+            other => other,
+        });
+        assert_eq!(table.run(), vec![]);
+        assert_eq!(
+            table.reveal_all(),
+            vec![Violation::HandCommitment {
+                peer: Entity::new(1)
+            }]
+        );
+    }
+
+    #[test]
+    fn peers_cannot_change_their_entropy_after_seeing_everyone_elses() {
+        let mut table = Table::new(&[5, 5, 5]);
+        table.cheat(2, |message| match message {
+            PeerMessage::Entropy { round, .. } => PeerMessage::Entropy {
+                round,
+                entropy: Bytes32::random(),
+            },
+            other => other,
+        });
+        // Both honest peers catch it, then refuse the rest of its round.
+        let caught = table.run();
+        let cheat = Entity::new(2);
+        assert_eq!(caught[..2], [Violation::Entropy { peer: cheat }; 2]);
+        assert!(caught.iter().all(|violation| violation.peer() == cheat));
+        assert!(table.peers[0].hand().is_none());
+    }
+
+    #[test]
+    fn a_second_commitment_or_a_skipped_step_is_refused() {
+        let dice = [(Entity::new(0), 5), (Entity::new(1), 5)];
+        let (mut ana, _) = PeerRound::new(Entity::new(0), 1, dice).unwrap();
+        let (_, ben_commit) = PeerRound::new(Entity::new(1), 1, dice).unwrap();
+        let (_, forged) = PeerRound::new(Entity::new(1), 1, dice).unwrap();
+        let ben = Entity::new(1);
+
+        let early = PeerMessage::HandCommitted {
+            round: 1,
+            hand: Seed::random().commitment(),
+        };
+        let err = ana.receive(ben, early).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<Violation>(),
+            Some(&Violation::OutOfOrder { peer: ben })
+        );
+
+        let replies = ana.receive(ben, ben_commit).unwrap();
+        assert!(matches!(replies[..], [PeerMessage::Entropy { .. }]));
+        let err = ana.receive(ben, forged).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<Violation>(),
+            Some(&Violation::Equivocation { peer: ben })
+        );
+        assert!(ana.reveal().is_err());
+    }
+}
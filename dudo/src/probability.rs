@@ -0,0 +1,203 @@
+use game_engine::Entity;
+
+use crate::bid::{ACES, Bid};
+use crate::components::dice::Hand;
+use crate::resources::{DudoRules, GameState};
+use crate::view::PlayerView;
+
+/// Odds of bids from one player's point of view: their own dice are known,
+/// every other die on the table is an independent fair roll.
+#[derive(Debug, Clone)]
+pub struct BidOdds {
+    own_faces: Vec<u8>,
+    unknown_dice: u8,
+    wild_ones: bool,
+}
+
+impl BidOdds {
+    pub fn new(hand: &Hand, unknown_dice: u8, wild_ones: bool) -> Self {
+        Self {
+            own_faces: hand.dice.iter().filter_map(|d| d.face).collect(),
+            unknown_dice,
+            wild_ones,
+        }
+    }
+
+    /// Odds for a player who knows nothing but the number of dice in play.
+    pub fn blind(total_dice: u8, wild_ones: bool) -> Self {
+        Self {
+            own_faces: Vec::new(),
+            unknown_dice: total_dice,
+            wild_ones,
+        }
+    }
+
+    /// Odds for the viewing player in the current round.
+    pub fn from_view(view: &PlayerView) -> Self {
+        let unknown_dice = view.total_dice() - view.hand.dice.len() as u8;
+        Self::new(&view.hand, unknown_dice, view.ones_are_wild())
+    }
+
+    pub fn total_dice(&self) -> u8 {
+        self.unknown_dice + self.own_faces.len() as u8
+    }
+
+    /// Whether aces count towards `face`.
+    fn aces_count_for(&self, face: u8) -> bool {
+        self.wild_ones && face != ACES
+    }
+
+    /// Dice in the asking player's own hand that count towards `face`.
+    pub fn own_matches(&self, face: u8) -> u8 {
+        self.own_faces
+            .iter()
+            .filter(|&&f| f == face || (self.aces_count_for(face) && f == ACES))
+            .count() as u8
+    }
+
+    /// Chance that a single unknown die counts towards `face`.
+    pub fn face_chance(&self, face: u8) -> f64 {
+        if self.aces_count_for(face) {
+            2.0 / 6.0
+        } else {
+            1.0 / 6.0
+        }
+    }
+
+    /// Chance there are at least `quantity` dice counting as `face`.
+    pub fn chance_at_least(&self, quantity: u8, face: u8) -> f64 {
+        let needed = quantity.saturating_sub(self.own_matches(face));
+        binomial_tail(self.unknown_dice, needed, self.face_chance(face))
+    }
+
+    /// Chance there are exactly `quantity` dice counting as `face`.
+    pub fn chance_exactly(&self, quantity: u8, face: u8) -> f64 {
+        match quantity.checked_sub(self.own_matches(face)) {
+            Some(needed) => binomial_pmf(self.unknown_dice, needed, self.face_chance(face)),
+            None => 0.0,
+        }
+    }
+
+    /// Every bid `player` could make next, most likely to hold first.
+    pub fn ranked_raises(
+        &self,
+        game_state: &GameState,
+        rules: &DudoRules,
+        player: Entity,
+    ) -> Vec<(Bid, f64)> {
+        rank_raises(self.total_dice(), game_state, rules, player, |q, f| {
+            self.chance_at_least(q, f)
+        })
+    }
+}
+
+/// Every bid `player` could make next with up to `total_dice` dice, scored by
+/// `chance` and most likely first.
+pub fn rank_raises(
+    total_dice: u8,
+    game_state: &GameState,
+    rules: &DudoRules,
+    player: Entity,
+    chance: impl Fn(u8, u8) -> f64,
+) -> Vec<(Bid, f64)> {
+    let mut raises: Vec<_> = (1..=total_dice)
+        .flat_map(|quantity| (1..=6).map(move |face| Bid::new(player, quantity, face)))
+        .filter(|bid| game_state.is_valid_next_bid(bid, rules))
+        .map(|bid| (bid, chance(bid.quantity, bid.face)))
+        .collect();
+    raises.sort_by(|(a, pa), (b, pb)| {
+        pb.total_cmp(pa)
+            .then(a.quantity.cmp(&b.quantity))
+            .then(a.face.cmp(&b.face))
+    });
+    raises
+}
+
+/// P(X = k) for X ~ Binomial(n, p).
+pub fn binomial_pmf(n: u8, k: u8, p: f64) -> f64 {
+    if k > n {
+        return 0.0;
+    }
+    let ways: f64 = (0..k).map(|i| (n - i) as f64 / (i + 1) as f64).product();
+    ways * p.powi(k as i32) * (1.0 - p).powi((n - k) as i32)
+}
+
+/// P(X >= k) for X ~ Binomial(n, p).
+pub fn binomial_tail(n: u8, k: u8, p: f64) -> f64 {
+    if k == 0 {
+        return 1.0;
+    }
+    let tail: f64 = (k..=n).map(|i| binomial_pmf(n, i, p)).sum();
+    tail.min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::GamePhase;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn binomial_probabilities_sum_to_one() {
+        for n in 0..=30 {
+            let total: f64 = (0..=n).map(|k| binomial_pmf(n, k, 1.0 / 3.0)).sum();
+            assert!(close(total, 1.0), "n = {n}");
+        }
+    }
+
+    #[test]
+    fn tails_match_known_values() {
+        assert!(close(binomial_tail(3, 3, 1.0 / 3.0), 1.0 / 27.0));
+        assert!(close(binomial_tail(2, 1, 1.0 / 6.0), 11.0 / 36.0));
+        assert_eq!(binomial_tail(3, 4, 0.5), 0.0);
+        assert_eq!(binomial_tail(0, 0, 0.5), 1.0);
+    }
+
+    #[test]
+    fn tails_never_increase_with_quantity() {
+        for n in 0..=20 {
+            for k in 0..n {
+                assert!(binomial_tail(n, k + 1, 1.0 / 3.0) <= binomial_tail(n, k, 1.0 / 3.0));
+            }
+        }
+    }
+
+    #[test]
+    fn own_hand_and_wild_ones_shift_the_odds() {
+        let hand = Hand::from_faces(&[5, 5, 1]);
+
+        let wild = BidOdds::new(&hand, 3, true);
+        assert_eq!(wild.own_matches(5), 3);
+        assert_eq!(wild.chance_at_least(3, 5), 1.0);
+        assert!(close(wild.chance_at_least(6, 5), 1.0 / 27.0));
+        assert_eq!(wild.chance_at_least(7, 5), 0.0);
+
+        let plain = BidOdds::new(&hand, 3, false);
+        assert_eq!(plain.own_matches(5), 2);
+        assert!(close(plain.chance_exactly(2, 5), (5.0f64 / 6.0).powi(3)));
+    }
+
+    #[test]
+    fn ranked_raises_are_valid_and_most_likely_first() {
+        let mut game_state = GameState::new();
+        game_state.phase = GamePhase::Bidding;
+        game_state.current_bid = Some(Bid::new(Entity::new(1), 3, 4));
+        let rules = DudoRules::perudo();
+        let odds = BidOdds::new(&Hand::from_faces(&[4, 4, 6, 1, 2]), 5, true);
+
+        let raises = odds.ranked_raises(&game_state, &rules, Entity::new(0));
+        assert!(!raises.is_empty());
+        assert!(
+            raises
+                .iter()
+                .all(|(bid, _)| game_state.is_valid_next_bid(bid, &rules))
+        );
+        assert!(raises.windows(2).all(|w| w[0].1 >= w[1].1));
+        let (best, chance) = raises[0];
+        assert_eq!((best.quantity, best.face), (3, 6));
+        assert!(close(chance, 1.0 - (2.0f64 / 3.0).powi(5)));
+    }
+}
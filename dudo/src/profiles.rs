@@ -0,0 +1,487 @@
+//! Player profiles that outlast a single game: lifetime statistics and an
+//! Elo rating, kept in a JSON file.
+//!
+//! Players are known by name. Bots share one profile per kind of bot, so
+//! the leaderboard shows how each of them rates against people; a game with
+//! several of the same bot counts once for it.
+
+use std::cmp::Ordering;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use game_engine::{Entity, World};
+use serde::{Deserialize, Serialize};
+
+use crate::DudoEvent;
+use crate::components::dice::Hand;
+use crate::components::player::{Controller, Gamertag};
+use crate::resources::{ChallengeKind, GamePhase, GameState, TurnOrder};
+use crate::systems::turn_timer::revealed_hands;
+
+pub const PROFILES_VERSION: u32 = 1;
+
+/// Where every new profile's rating starts.
+pub const DEFAULT_RATING: f64 = 1500.0;
+
+/// Most rating points one game can move a player.
+const K_FACTOR: f64 = 32.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub games_played: u32,
+    pub wins: u32,
+    /// Times the player called Dudo on someone's bid.
+    pub bluffs_called: u32,
+    /// Dudo or calza calls that did not cost the player a die.
+    pub successful_challenges: u32,
+    pub dice_lost: u32,
+    pub rounds_played: u32,
+    pub rating: f64,
+}
+
+impl Profile {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            games_played: 0,
+            wins: 0,
+            bluffs_called: 0,
+            successful_challenges: 0,
+            dice_lost: 0,
+            rounds_played: 0,
+            rating: DEFAULT_RATING,
+        }
+    }
+
+    pub fn average_dice_lost(&self) -> f64 {
+        if self.rounds_played == 0 {
+            return 0.0;
+        }
+        self.dice_lost as f64 / self.rounds_played as f64
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        if self.games_played == 0 {
+            return 0.0;
+        }
+        self.wins as f64 / self.games_played as f64
+    }
+}
+
+/// How one player did in one game.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PlayerResult {
+    pub name: String,
+    /// 1 for the winner, then in reverse order of being knocked out.
+    pub place: usize,
+    pub bluffs_called: u32,
+    pub successful_challenges: u32,
+    pub dice_lost: u32,
+    pub rounds_played: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ProfilesFile {
+    version: u32,
+    profiles: Vec<Profile>,
+}
+
+/// Every known profile, written back to its file after each game.
+#[derive(Debug, Clone, Default)]
+pub struct PlayerDb {
+    path: Option<PathBuf>,
+    profiles: Vec<Profile>,
+}
+
+impl PlayerDb {
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Reads the profiles at `path`, starting empty if there is no file yet.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let profiles = match fs::read_to_string(path) {
+            Ok(json) => {
+                let file: ProfilesFile = serde_json::from_str(&json)
+                    .with_context(|| format!("{} is not a dudo profiles file", path.display()))?;
+                if file.version != PROFILES_VERSION {
+                    bail!(
+                        "profiles file version {} is not supported (this build reads version {PROFILES_VERSION})",
+                        file.version
+                    );
+                }
+                file.profiles
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to read {}", path.display()));
+            }
+        };
+        Ok(Self {
+            path: Some(path.to_path_buf()),
+            profiles,
+        })
+    }
+
+    /// Writes the profiles back to their file, if they have one.
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let json = serde_json::to_string_pretty(&ProfilesFile {
+            version: PROFILES_VERSION,
+            profiles: self.profiles.clone(),
+        })?;
+        fs::write(path, json).with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    pub fn profile(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|profile| profile.name == name)
+    }
+
+    fn profile_mut(&mut self, name: &str) -> &mut Profile {
+        let index = match self.profiles.iter().position(|p| p.name == name) {
+            Some(index) => index,
+            None => {
+                self.profiles.push(Profile::new(name));
+                self.profiles.len() - 1
+            }
+        };
+        &mut self.profiles[index]
+    }
+
+    /// Every profile, best rated first.
+    pub fn leaderboard(&self) -> Vec<&Profile> {
+        let mut profiles: Vec<&Profile> = self.profiles.iter().collect();
+        profiles.sort_by(|a, b| {
+            b.rating
+                .total_cmp(&a.rating)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        profiles
+    }
+
+    /// Adds a finished game to its players' profiles and ratings, then saves.
+    ///
+    /// Seats sharing a profile, such as several bots of one kind, count as
+    /// one game for it: a win if any of them won, their tallies summed and
+    /// the average of their rating changes. A profile with no one else at
+    /// the table has learnt nothing and is left alone.
+    pub fn record_game(&mut self, results: &[PlayerResult]) -> Result<()> {
+        let changes = rating_changes(self, results);
+        let mut names: Vec<&str> = Vec::new();
+        for result in results {
+            if !names.contains(&result.name.as_str()) {
+                names.push(&result.name);
+            }
+        }
+        if names.len() < 2 {
+            return Ok(());
+        }
+
+        for name in names {
+            let seats: Vec<(&PlayerResult, f64)> = results
+                .iter()
+                .zip(&changes)
+                .filter(|(result, _)| result.name == name)
+                .map(|(result, &change)| (result, change))
+                .collect();
+            let profile = self.profile_mut(name);
+            profile.games_played += 1;
+            if seats.iter().any(|(result, _)| result.place == 1) {
+                profile.wins += 1;
+            }
+            for (result, change) in &seats {
+                profile.bluffs_called += result.bluffs_called;
+                profile.successful_challenges += result.successful_challenges;
+                profile.dice_lost += result.dice_lost;
+                profile.rounds_played += result.rounds_played;
+                profile.rating += change / seats.len() as f64;
+            }
+        }
+        self.save()
+    }
+}
+
+/// Elo over every pair of seats with different profiles: finishing ahead of
+/// someone is a win against them. Each seat's pairs share [`K_FACTOR`], so
+/// a game moves a rating about as much however many played.
+fn rating_changes(db: &PlayerDb, results: &[PlayerResult]) -> Vec<f64> {
+    let ratings: Vec<f64> = results
+        .iter()
+        .map(|r| db.profile(&r.name).map_or(DEFAULT_RATING, |p| p.rating))
+        .collect();
+    results
+        .iter()
+        .enumerate()
+        .map(|(i, me)| {
+            let opponents: Vec<(usize, &PlayerResult)> = results
+                .iter()
+                .enumerate()
+                .filter(|(_, other)| other.name != me.name)
+                .collect();
+            let k = K_FACTOR / opponents.len().max(1) as f64;
+            opponents
+                .into_iter()
+                .map(|(j, other)| {
+                    let expected = 1.0 / (1.0 + 10f64.powf((ratings[j] - ratings[i]) / 400.0));
+                    let score = match me.place.cmp(&other.place) {
+                        Ordering::Less => 1.0,
+                        Ordering::Equal => 0.5,
+                        Ordering::Greater => 0.0,
+                    };
+                    k * (score - expected)
+                })
+                .sum()
+        })
+        .collect()
+}
+
+/// A seat's running tally in the game in progress.
+#[derive(Debug, Clone)]
+struct SeatTally {
+    player: Entity,
+    /// Dice the player held after the last event.
+    dice: usize,
+    result: PlayerResult,
+}
+
+/// Tallies the game in progress and records it in a [`PlayerDb`] once it
+/// is over. Kept as a world resource; see [`attach_tally`].
+#[derive(Debug, Clone)]
+pub struct GameTally {
+    db: PlayerDb,
+    seats: Vec<SeatTally>,
+    round: u32,
+    /// Players knocked out so far, first out first.
+    knocked_out: Vec<Entity>,
+    recorded: bool,
+}
+
+impl GameTally {
+    pub fn db(&self) -> &PlayerDb {
+        &self.db
+    }
+
+    fn seat_mut(&mut self, player: Entity) -> Option<&mut SeatTally> {
+        self.seats.iter_mut().find(|seat| seat.player == player)
+    }
+}
+
+/// The profile a seat plays under: its name, or its kind of bot.
+fn profile_name(world: &World, player: Entity) -> Result<String> {
+    Ok(match world.component::<Controller>(player) {
+        Ok(Controller::Human) | Err(_) => world.component::<Gamertag>(player)?.name.clone(),
+        Ok(bot) => format!("Bot ({bot})"),
+    })
+}
+
+/// Starts tallying the players still in the game for `db`.
+pub fn attach_tally(world: &mut World, db: PlayerDb) -> Result<()> {
+    let mut seats = Vec::new();
+    for &player in &world.resource::<TurnOrder>()?.players {
+        seats.push(SeatTally {
+            player,
+            dice: world.component::<Hand>(player)?.dice.len(),
+            result: PlayerResult {
+                name: profile_name(world, player)?,
+                ..PlayerResult::default()
+            },
+        });
+    }
+    world.insert_resource(GameTally {
+        db,
+        seats,
+        round: world.resource::<GameState>()?.round,
+        knocked_out: Vec::new(),
+        recorded: false,
+    });
+    Ok(())
+}
+
+/// Updates the tally after a processed event, if the world has one, and
+/// records the game once it is over.
+pub fn tally_processed_event(world: &mut World, event: &DudoEvent) -> Result<()> {
+    let Ok(tally) = world.resource::<GameTally>() else {
+        return Ok(());
+    };
+    if tally.recorded {
+        return Ok(());
+    }
+    let round_before = tally.round;
+    let challenge = if revealed_hands(world, event, round_before)? {
+        world.resource::<GameState>()?.last_challenge.clone()
+    } else {
+        None
+    };
+    let game_state = world.resource::<GameState>()?;
+    let (round, over) = (game_state.round, game_state.phase == GamePhase::GameOver);
+    let still_in = world.resource::<TurnOrder>()?.players.clone();
+    let mut dice = Vec::new();
+    for seat in &tally.seats {
+        dice.push(world.component::<Hand>(seat.player)?.dice.len());
+    }
+
+    let tally = world.resource_mut::<GameTally>()?;
+    if let Some(outcome) = challenge
+        && let Some(seat) = tally.seat_mut(outcome.challenger)
+    {
+        if outcome.kind == ChallengeKind::Dudo {
+            seat.result.bluffs_called += 1;
+        }
+        if outcome.loser != Some(outcome.challenger) {
+            seat.result.successful_challenges += 1;
+        }
+    }
+    let forfeit = matches!(event, DudoEvent::PlayerForfeited { .. });
+    for (seat, now) in tally.seats.iter_mut().zip(dice) {
+        if (round > round_before || over) && seat.dice > 0 {
+            seat.result.rounds_played += 1;
+        }
+        if !forfeit {
+            seat.result.dice_lost += seat.dice.saturating_sub(now) as u32;
+        }
+        seat.dice = now;
+        if !still_in.contains(&seat.player) && !tally.knocked_out.contains(&seat.player) {
+            tally.knocked_out.push(seat.player);
+        }
+    }
+    tally.round = round;
+
+    if over {
+        tally.recorded = true;
+        let mut results = Vec::new();
+        for seat in &tally.seats {
+            let place = match tally.knocked_out.iter().position(|&p| p == seat.player) {
+                Some(out) => tally.seats.len() - out,
+                None => 1,
+            };
+            results.push(PlayerResult {
+                place,
+                ..seat.result.clone()
+            });
+        }
+        tally.db.record_game(&results)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_systems::process_events;
+    use crate::resources::DudoRules;
+    use crate::setup_game;
+
+    fn result(name: &str, place: usize) -> PlayerResult {
+        PlayerResult {
+            name: name.into(),
+            place,
+            ..PlayerResult::default()
+        }
+    }
+
+    #[test]
+    fn winners_gain_what_losers_lose_and_upsets_pay_more() {
+        let mut db = PlayerDb::in_memory();
+        db.record_game(&[result("Ana", 1), result("Ben", 2), result("Cid", 3)])
+            .unwrap();
+        let rating = |db: &PlayerDb, name| db.profile(name).unwrap().rating;
+        assert!((rating(&db, "Ana") - 1516.0).abs() < 1e-9);
+        assert!((rating(&db, "Ben") - 1500.0).abs() < 1e-9);
+        assert!((rating(&db, "Cid") - 1484.0).abs() < 1e-9);
+
+        let favourite = rating(&db, "Ana");
+        db.record_game(&[result("Cid", 1), result("Ana", 2)])
+            .unwrap();
+        assert!(favourite - rating(&db, "Ana") > 16.0);
+        let names: Vec<&str> = db.leaderboard().iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["Cid", "Ben", "Ana"]);
+    }
+
+    #[test]
+    fn seats_sharing_a_profile_count_once_and_never_rate_each_other() {
+        let mut db = PlayerDb::in_memory();
+        let bot = "Bot (medium)";
+        db.record_game(&[result(bot, 1), result(bot, 2), result("Ana", 3)])
+            .unwrap();
+        let bots = db.profile(bot).unwrap();
+        assert_eq!((bots.games_played, bots.wins), (1, 1));
+        assert!((bots.rating - 1516.0).abs() < 1e-9);
+        assert!((db.profile("Ana").unwrap().rating - 1484.0).abs() < 1e-9);
+
+        db.record_game(&[result(bot, 1), result(bot, 2)]).unwrap();
+        assert_eq!(db.profile(bot).unwrap().games_played, 1);
+    }
+
+    #[test]
+    fn a_finished_game_lands_in_the_players_profiles() {
+        let rules = DudoRules {
+            starting_dice: 1,
+            ..DudoRules::perudo()
+        };
+        let names = vec!["Ana".to_string(), "Ben".to_string()];
+        let mut world = setup_game(names, rules, 3).unwrap();
+        attach_tally(&mut world, PlayerDb::in_memory()).unwrap();
+        let play = |world: &mut World, event| {
+            world.emit_event(event, 0.0).unwrap();
+            process_events(world).unwrap();
+        };
+        play(&mut world, DudoEvent::GameReady);
+        play(&mut world, DudoEvent::RollDice);
+        let bidder = world.resource::<TurnOrder>().unwrap().current_player();
+        play(
+            &mut world,
+            DudoEvent::BidMade {
+                player: bidder,
+                quantity: 2,
+                face: 6,
+            },
+        );
+        let challenger = world.resource::<TurnOrder>().unwrap().current_player();
+        play(&mut world, DudoEvent::ChallengeMade { challenger });
+        let loser = world
+            .resource::<GameState>()
+            .unwrap()
+            .last_challenge
+            .clone();
+        let loser = loser.unwrap().loser.unwrap();
+        let name = |player: Entity| ["Ana", "Ben"][player.id as usize];
+
+        let db = world.resource::<GameTally>().unwrap().db();
+        let (winner, loser) = if loser == bidder {
+            (challenger, bidder)
+        } else {
+            (bidder, challenger)
+        };
+        let won = db.profile(name(winner)).unwrap();
+        let lost = db.profile(name(loser)).unwrap();
+        assert_eq!((won.games_played, won.wins, won.dice_lost), (1, 1, 0));
+        assert_eq!((lost.games_played, lost.wins, lost.dice_lost), (1, 0, 1));
+        assert_eq!(lost.average_dice_lost(), 1.0);
+        assert!(won.rating > lost.rating);
+
+        let caller = db.profile(name(challenger)).unwrap();
+        assert_eq!(caller.bluffs_called, 1);
+        assert_eq!(
+            caller.successful_challenges,
+            u32::from(winner == challenger)
+        );
+    }
+
+    #[test]
+    fn profiles_survive_a_round_trip_through_their_file() {
+        let path = std::env::temp_dir().join(format!("dudo-players-{}.json", std::process::id()));
+        let mut db = PlayerDb::open(&path).unwrap();
+        assert!(db.leaderboard().is_empty());
+        db.record_game(&[result("Ana", 1), result("Ben", 2)])
+            .unwrap();
+
+        let reopened = PlayerDb::open(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(reopened.leaderboard(), db.leaderboard());
+    }
+}
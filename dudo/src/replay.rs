@@ -0,0 +1,245 @@
+use std::path::Path;
+
+use anyhow::{Result, bail};
+use game_engine::World;
+
+use crate::components::dice::Hand;
+use crate::event_systems::process_events;
+use crate::game_log::{LogEntry, read_log};
+use crate::setup_game;
+
+/// Steps through a recorded game, rebuilding the `World` at any point of the
+/// log by re-applying its entries from the start.
+pub struct Replay {
+    entries: Vec<LogEntry>,
+    position: usize,
+}
+
+impl Replay {
+    pub fn new(entries: Vec<LogEntry>) -> Result<Self> {
+        if !matches!(entries.first(), Some(LogEntry::GameStarted { .. })) {
+            bail!("game log does not start with a GameStarted entry");
+        }
+        Ok(Self {
+            entries,
+            position: 0,
+        })
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::new(read_log(path)?)
+    }
+
+    /// Number of entries after the game setup.
+    pub fn len(&self) -> usize {
+        self.entries.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// How many entries have been applied so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The entry applied most recently, `GameStarted` at the beginning.
+    pub fn current_entry(&self) -> &LogEntry {
+        &self.entries[self.position]
+    }
+
+    pub fn step_forward(&mut self) -> bool {
+        if self.position >= self.len() {
+            return false;
+        }
+        self.position += 1;
+        true
+    }
+
+    pub fn step_back(&mut self) -> bool {
+        if self.position == 0 {
+            return false;
+        }
+        self.position -= 1;
+        true
+    }
+
+    pub fn seek(&mut self, position: usize) {
+        self.position = position.min(self.len());
+    }
+
+    /// Rebuilds the world as it was after the current entry.
+    pub fn world(&self) -> Result<World> {
+        let LogEntry::GameStarted {
+            players,
+            rules,
+            seed,
+        } = &self.entries[0]
+        else {
+            unreachable!("checked in Replay::new");
+        };
+        let mut world = setup_game(players.clone(), *rules, *seed)?;
+
+        for entry in &self.entries[1..=self.position] {
+            match entry {
+                LogEntry::Event { timestamp, event } => {
+                    world.emit_event(event.clone(), *timestamp)?;
+                    process_events(&mut world)?;
+                }
+                // The log is authoritative over whatever the rng produces now.
+                LogEntry::HandsRolled { hands, .. } => {
+                    for (player, faces) in hands {
+                        *world.component_mut::<Hand>(*player)? = Hand::from_faces(faces);
+                    }
+                }
+                LogEntry::ChallengeResolved(_)
+                | LogEntry::GameStarted { .. }
+                | LogEntry::Chat { .. } => {}
+            }
+        }
+
+        Ok(world)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DudoEvent;
+    use crate::chat::{ChatBody, ChatMessage, Emote};
+    use crate::game_log::{EventLog, attach_event_log, parse_log};
+    use crate::resources::{DudoRules, GamePhase, GameState, TurnOrder};
+
+    fn play(world: &mut World, event: DudoEvent) {
+        world.emit_event(event, 0.0).unwrap();
+        process_events(world).unwrap();
+    }
+
+    fn recorded_game() -> (World, Vec<LogEntry>) {
+        let names = vec!["Ana".to_string(), "Ben".to_string()];
+        let mut world = setup_game(names, DudoRules::perudo(), 99).unwrap();
+        attach_event_log(&mut world, EventLog::in_memory()).unwrap();
+
+        play(&mut world, DudoEvent::GameReady);
+        play(&mut world, DudoEvent::RollDice);
+        let bidder = world.resource::<TurnOrder>().unwrap().current_player();
+        play(
+            &mut world,
+            DudoEvent::BidMade {
+                player: bidder,
+                quantity: 4,
+                face: 3,
+            },
+        );
+        let challenger = world.resource::<TurnOrder>().unwrap().current_player();
+        play(&mut world, DudoEvent::ChallengeMade { challenger });
+        play(&mut world, DudoEvent::RollDice);
+
+        let entries = world.resource::<EventLog>().unwrap().entries.clone();
+        (world, entries)
+    }
+
+    fn hands(world: &World) -> Vec<Vec<Option<u8>>> {
+        let players = world.resource::<TurnOrder>().unwrap().players.clone();
+        players
+            .iter()
+            .map(|&p| {
+                let hand = world.component::<Hand>(p).unwrap();
+                hand.dice.iter().map(|d| d.face).collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn log_records_hands_and_challenge_results() {
+        let (_, entries) = recorded_game();
+        assert!(matches!(entries[0], LogEntry::GameStarted { seed: 99, .. }));
+        let rolls = entries
+            .iter()
+            .filter(|e| matches!(e, LogEntry::HandsRolled { .. }))
+            .count();
+        assert_eq!(rolls, 2);
+        assert!(
+            entries
+                .iter()
+                .any(|e| matches!(e, LogEntry::ChallengeResolved(_)))
+        );
+    }
+
+    #[test]
+    fn replaying_the_whole_log_rebuilds_the_world() {
+        let (original, entries) = recorded_game();
+        let mut replay = Replay::new(entries).unwrap();
+        replay.seek(replay.len());
+
+        let rebuilt = replay.world().unwrap();
+        assert_eq!(hands(&rebuilt), hands(&original));
+        let (a, b) = (
+            rebuilt.resource::<GameState>().unwrap(),
+            original.resource::<GameState>().unwrap(),
+        );
+        assert_eq!((a.round, a.phase), (b.round, b.phase));
+    }
+
+    #[test]
+    fn stepping_back_restores_earlier_states() {
+        let (_, entries) = recorded_game();
+        let mut replay = Replay::new(entries).unwrap();
+
+        assert!(!replay.step_back());
+        while replay.step_forward() {}
+        assert_eq!(replay.position(), replay.len());
+        replay.seek(0);
+        let start = replay.world().unwrap();
+        assert_eq!(
+            start.resource::<GameState>().unwrap().phase,
+            GamePhase::RoundStart
+        );
+    }
+
+    #[test]
+    fn logs_survive_a_json_lines_round_trip() {
+        let (_, entries) = recorded_game();
+        let text: String = entries
+            .iter()
+            .map(|e| serde_json::to_string(e).unwrap() + "\n")
+            .collect();
+
+        let parsed = parse_log(text.as_bytes()).unwrap();
+        assert_eq!(parsed.len(), entries.len());
+        Replay::new(parsed).unwrap().world().unwrap();
+    }
+
+    #[test]
+    fn logs_must_start_with_the_game_setup() {
+        assert!(Replay::new(vec![]).is_err());
+        assert!(
+            Replay::new(vec![LogEntry::Event {
+                timestamp: 0.0,
+                event: DudoEvent::RollDice,
+            }])
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn chat_is_only_logged_when_asked_for_and_replays_skip_it() {
+        let message = ChatMessage {
+            from: "Ana".into(),
+            body: ChatBody::Emote(Emote::Think),
+        };
+        let mut quiet = EventLog::in_memory();
+        quiet.record_chat(0.0, &message).unwrap();
+        assert!(quiet.entries.is_empty());
+
+        let (original, mut entries) = recorded_game();
+        let mut chatty = EventLog::in_memory().with_chat();
+        chatty.record_chat(0.0, &message).unwrap();
+        entries.insert(2, chatty.entries.remove(0));
+
+        let mut replay = Replay::new(entries).unwrap();
+        replay.seek(replay.len());
+        assert_eq!(hands(&replay.world().unwrap()), hands(&original));
+    }
+}
@@ -1,5 +1,11 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+
 use crate::bid::Bid;
+use crate::fair_dice::RoundProof;
 use game_engine::Entity;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use serde::{Deserialize, Serialize};
 
 // ============================================================================
@@ -15,10 +21,16 @@ pub enum GamePhase {
     GameOver,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameState {
     pub round: u32,
     pub current_bid: Option<Bid>,
     pub phase: GamePhase,
+    pub last_challenge: Option<ChallengeOutcome>,
+    /// Player whose drop to one die made this round palifico.
+    pub palifico: Option<Entity>,
+    /// Players who have already had their palifico round.
+    pub palifico_played: HashSet<Entity>,
 }
 
 impl GameState {
@@ -26,15 +38,256 @@ impl GameState {
         Self {
             round: 1,
             current_bid: None,
-            phase: GamePhase::Bidding,
+            phase: GamePhase::RoundStart,
+            last_challenge: None,
+            palifico: None,
+            palifico_played: HashSet::new(),
+        }
+    }
+
+    pub fn is_palifico(&self) -> bool {
+        self.palifico.is_some()
+    }
+
+    /// Whether ones count towards other faces this round.
+    pub fn ones_are_wild(&self, rules: &DudoRules) -> bool {
+        rules.wild_ones && !self.is_palifico()
+    }
+
+    /// The lowest bid `player` may make next, by quantity and then face.
+    pub fn minimum_raise(&self, player: Entity, rules: &DudoRules) -> Option<Bid> {
+        (1..=u8::MAX)
+            .flat_map(|quantity| (1..=6).map(move |face| Bid::new(player, quantity, face)))
+            .find(|bid| self.is_valid_next_bid(bid, rules))
+    }
+
+    /// Whether `bid` may follow the current bid of this round.
+    pub fn is_valid_next_bid(&self, bid: &Bid, rules: &DudoRules) -> bool {
+        match &self.current_bid {
+            None => bid.is_well_formed(),
+            Some(prev) if self.is_palifico() => bid.is_valid_palifico_raise(prev),
+            Some(prev) => bid.is_valid_raise(prev, rules),
+        }
+    }
+}
+
+impl Default for GameState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChallengeKind {
+    Dudo,
+    Calza,
+}
+
+/// Result of the most recent challenge, kept so the UI can show the reveal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChallengeOutcome {
+    pub kind: ChallengeKind,
+    pub challenger: Entity,
+    pub bid: Bid,
+    pub total: usize,
+    /// `None` when a calza was exact and the caller regained a die instead.
+    pub loser: Option<Entity>,
+    pub revealed: Vec<(Entity, Vec<u8>)>,
+    /// The round's seeds, when the hands were dealt from committed ones.
+    #[serde(default)]
+    pub proof: Option<RoundProof>,
+}
+
+// ============================================================================
+// Rules
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DudoRules {
+    pub starting_dice: u8,
+    /// Ones count towards every other face and follow the aces bidding
+    /// conversions (halve to switch onto ones, double plus one to leave).
+    pub wild_ones: bool,
+    /// Players may claim the current bid is exact, regaining a die if right.
+    pub calza: bool,
+    /// A player's first drop to one die makes the next round palifico: ones
+    /// are not wild and the opening face cannot be changed.
+    pub palifico: bool,
+    /// Seconds each player has to act, if turns are timed.
+    #[serde(default)]
+    pub turn_seconds: Option<u32>,
+    /// What happens to a player who runs out of time.
+    #[serde(default)]
+    pub on_timeout: TimeoutAction,
+}
+
+impl DudoRules {
+    pub fn perudo() -> Self {
+        Self {
+            starting_dice: 5,
+            wild_ones: true,
+            calza: true,
+            palifico: true,
+            turn_seconds: None,
+            on_timeout: TimeoutAction::default(),
+        }
+    }
+
+    pub fn classic() -> Self {
+        Self {
+            starting_dice: 5,
+            wild_ones: false,
+            calza: false,
+            palifico: false,
+            turn_seconds: None,
+            on_timeout: TimeoutAction::default(),
+        }
+    }
+
+    /// The same rules without a turn clock, which changes nothing about
+    /// how a hand is played.
+    pub fn untimed(self) -> Self {
+        Self {
+            turn_seconds: None,
+            on_timeout: TimeoutAction::default(),
+            ..self
+        }
+    }
+}
+
+impl Default for DudoRules {
+    fn default() -> Self {
+        Self::perudo()
+    }
+}
+
+/// Parses a preset name: `perudo` or `classic`.
+impl FromStr for DudoRules {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "perudo" => Ok(Self::perudo()),
+            "classic" => Ok(Self::classic()),
+            _ => anyhow::bail!("unknown rules preset '{s}' (expected perudo or classic)"),
+        }
+    }
+}
+
+/// The default action for a player whose turn timer runs out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TimeoutAction {
+    /// Make the lowest legal bid.
+    #[default]
+    MinimumRaise,
+    /// Call Dudo on the standing bid, or open with the lowest bid if there
+    /// is none.
+    CallDudo,
+    /// Lose a die, which ends the round.
+    LoseDie,
+}
+
+/// Parses `raise`, `dudo` or `lose-die`.
+impl FromStr for TimeoutAction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "raise" => Ok(Self::MinimumRaise),
+            "dudo" => Ok(Self::CallDudo),
+            "lose-die" => Ok(Self::LoseDie),
+            _ => anyhow::bail!("unknown timeout action '{s}' (expected raise, dudo or lose-die)"),
         }
     }
 }
 
+// ============================================================================
+// Clock
+// ============================================================================
+
+/// Game time, and the turn being timed. Whoever drives the game advances
+/// it, so timed turns play out the same under test as they do live.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TurnClock {
+    /// Seconds of game time so far.
+    pub now: f64,
+    /// The round, player and bid count identifying the timed turn.
+    pub turn: Option<(u32, Entity, usize)>,
+    /// When the timed turn began.
+    pub started: f64,
+    /// Whether the player has been warned that time is running out.
+    pub warned: bool,
+}
+
+impl TurnClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Seconds the current player has left, if their turn is timed.
+    pub fn remaining(&self, rules: &DudoRules) -> Option<f64> {
+        let limit = rules.turn_seconds? as f64;
+        self.turn?;
+        Some((limit - (self.now - self.started)).max(0.0))
+    }
+}
+
+// ============================================================================
+// Randomness
+// ============================================================================
+
+/// Single source of randomness for the game, so a seed replays it exactly.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiceRng {
+    rng: ChaCha8Rng,
+}
+
+impl DiceRng {
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn roll(&mut self) -> u8 {
+        self.rng.random_range(1..7)
+    }
+
+    pub fn roll_many(&mut self, count: usize) -> Vec<u8> {
+        (0..count).map(|_| self.roll()).collect()
+    }
+
+    /// Uniform index into a collection of `len` items.
+    pub fn index(&mut self, len: usize) -> usize {
+        self.rng.random_range(0..len)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameMetadata {
+    pub seed: u64,
+}
+
+// ============================================================================
+// Table Options
+// ============================================================================
+
+/// How a local table is presented, as opposed to the rules of play.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableOptions {
+    /// Several people share one screen: hide each hand until its owner
+    /// confirms they have the device, and clear it again after their turn.
+    pub hot_seat: bool,
+    /// Play in the full-screen terminal UI rather than at the prompt.
+    #[serde(default)]
+    pub tui: bool,
+}
+
 // ============================================================================
 // Turn Order
 // ============================================================================
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TurnOrder {
     pub players: Vec<Entity>,
     pub current_index: usize,
@@ -48,6 +301,28 @@ impl TurnOrder {
         }
     }
 
+    /// Makes `player` the one to act, if they are still seated.
+    pub fn set_current(&mut self, player: Entity) {
+        if let Some(idx) = self.players.iter().position(|&p| p == player) {
+            self.current_index = idx;
+        }
+    }
+
+    /// Removes an eliminated player, keeping the turn on the player that
+    /// would have followed them.
+    pub fn remove_player(&mut self, player: Entity) {
+        let Some(idx) = self.players.iter().position(|&p| p == player) else {
+            return;
+        };
+        self.players.remove(idx);
+        if idx < self.current_index {
+            self.current_index -= 1;
+        }
+        if self.current_index >= self.players.len() {
+            self.current_index = 0;
+        }
+    }
+
     pub fn current_player(&self) -> Entity {
         self.players[self.current_index]
     }
@@ -75,6 +350,7 @@ impl TurnOrder {
 // Bid History
 // ============================================================================
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BidHistory {
     pub bids: Vec<Bid>,
 }
@@ -88,7 +364,17 @@ impl BidHistory {
         self.bids.last()
     }
 
+    pub fn push(&mut self, bid: Bid) {
+        self.bids.push(bid);
+    }
+
     pub fn clear_round(&mut self) {
         self.bids.clear();
     }
 }
+
+impl Default for BidHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
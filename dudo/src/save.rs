@@ -0,0 +1,262 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use game_engine::{Entity, EventQueue, World};
+use serde::{Deserialize, Serialize};
+
+use crate::DudoEvent;
+use crate::components::dice::Hand;
+use crate::components::player::{Controller, Gamertag, Player};
+use crate::fair_dice::{RoundCommitments, RoundSeeds};
+use crate::resources::{
+    BidHistory, DiceRng, DudoRules, GameMetadata, GameState, TableOptions, TurnClock, TurnOrder,
+};
+
+/// Bumped whenever the layout of `SaveFile` changes incompatibly.
+pub const SAVE_VERSION: u32 = 2;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SaveFile {
+    pub version: u32,
+    pub metadata: GameMetadata,
+    pub rules: DudoRules,
+    #[serde(default)]
+    pub table_options: TableOptions,
+    pub game_state: GameState,
+    pub turn_order: TurnOrder,
+    pub bid_history: BidHistory,
+    pub rng: DiceRng,
+    pub players: Vec<SavedPlayer>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedPlayer {
+    pub entity: Entity,
+    pub gamertag: String,
+    pub hand: Hand,
+    pub controller: Controller,
+}
+
+impl SaveFile {
+    pub fn from_world(world: &World) -> Result<Self> {
+        let mut entities = world.query::<(Player, Gamertag, Hand)>();
+        entities.sort_by_key(|entity| entity.id);
+
+        let mut players = Vec::new();
+        for entity in entities {
+            players.push(SavedPlayer {
+                entity,
+                gamertag: world.component::<Gamertag>(entity)?.name.clone(),
+                hand: world.component::<Hand>(entity)?.clone(),
+                controller: world.component::<Controller>(entity)?.clone(),
+            });
+        }
+
+        Ok(Self {
+            version: SAVE_VERSION,
+            metadata: *world.resource::<GameMetadata>()?,
+            rules: *world.resource::<DudoRules>()?,
+            table_options: *world.resource::<TableOptions>()?,
+            game_state: world.resource::<GameState>()?.clone(),
+            turn_order: world.resource::<TurnOrder>()?.clone(),
+            bid_history: world.resource::<BidHistory>()?.clone(),
+            rng: world.resource::<DiceRng>()?.clone(),
+            players,
+        })
+    }
+
+    /// Rebuilds a world that continues exactly where the save was made.
+    pub fn into_world(self) -> Result<World> {
+        let mut world = World::new();
+        world.insert_resource(EventQueue::<DudoEvent>::new());
+
+        for saved in self.players {
+            let entity = world
+                .spawn()
+                .with(Player)?
+                .with(Gamertag::new(saved.gamertag))?
+                .with(saved.hand)?
+                .with(saved.controller)?
+                .build();
+            if entity != saved.entity {
+                bail!(
+                    "save file player ids are not sequential (expected {}, found {})",
+                    entity.id,
+                    saved.entity.id
+                );
+            }
+        }
+
+        world.insert_resource(self.metadata);
+        world.insert_resource(self.rules);
+        world.insert_resource(self.table_options);
+        world.insert_resource(self.game_state);
+        world.insert_resource(self.turn_order);
+        world.insert_resource(self.bid_history);
+        world.insert_resource(self.rng);
+        // A timed turn starts over when the game is loaded.
+        world.insert_resource(TurnClock::new());
+        // Seeds are never saved, so the round is dealt again if need be.
+        world.insert_resource(RoundCommitments::new());
+        world.insert_resource(RoundSeeds::new());
+        Ok(world)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        #[derive(Deserialize)]
+        struct Versioned {
+            version: u32,
+        }
+
+        let Versioned { version } = serde_json::from_str(json).context("not a dudo save file")?;
+        if version != SAVE_VERSION {
+            bail!(
+                "save file version {version} is not supported (this build reads version {SAVE_VERSION})"
+            );
+        }
+        serde_json::from_str(json).context("save file is corrupted")
+    }
+}
+
+pub fn save_game(world: &World, path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+    let json = serde_json::to_string_pretty(&SaveFile::from_world(world)?)?;
+    fs::write(path, json).with_context(|| format!("failed to write {}", path.display()))
+}
+
+pub fn load_game(path: impl AsRef<Path>) -> Result<World> {
+    let path = path.as_ref();
+    let json =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    SaveFile::from_json(&json)?.into_world()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_systems::process_events;
+    use crate::setup_game;
+
+    fn world_mid_round() -> World {
+        let names = vec!["Ana".to_string(), "Ben".to_string(), "Cid".to_string()];
+        let mut world = setup_game(names, DudoRules::perudo(), 5).unwrap();
+        world.emit_event(DudoEvent::GameReady, 0.0).unwrap();
+        world.emit_event(DudoEvent::RollDice, 0.0).unwrap();
+        process_events(&mut world).unwrap();
+        let player = world.resource::<TurnOrder>().unwrap().current_player();
+        world
+            .emit_event(
+                DudoEvent::BidMade {
+                    player,
+                    quantity: 2,
+                    face: 5,
+                },
+                0.0,
+            )
+            .unwrap();
+        process_events(&mut world).unwrap();
+        world
+    }
+
+    fn round_trip(world: &World) -> World {
+        let json = serde_json::to_string(&SaveFile::from_world(world).unwrap()).unwrap();
+        SaveFile::from_json(&json).unwrap().into_world().unwrap()
+    }
+
+    #[test]
+    fn loading_resumes_at_the_same_turn() {
+        let world = world_mid_round();
+        let loaded = round_trip(&world);
+
+        let (a, b) = (
+            world.resource::<TurnOrder>().unwrap(),
+            loaded.resource::<TurnOrder>().unwrap(),
+        );
+        assert_eq!(a.players, b.players);
+        assert_eq!(a.current_player(), b.current_player());
+
+        let (a, b) = (
+            world.resource::<GameState>().unwrap(),
+            loaded.resource::<GameState>().unwrap(),
+        );
+        assert_eq!((a.round, a.phase), (b.round, b.phase));
+        assert_eq!(
+            a.current_bid.map(|bid| (bid.quantity, bid.face)),
+            b.current_bid.map(|bid| (bid.quantity, bid.face))
+        );
+        assert_eq!(loaded.resource::<BidHistory>().unwrap().bids.len(), 1);
+
+        for &player in &a.palifico_played {
+            assert!(b.palifico_played.contains(&player));
+        }
+        for player in world.resource::<TurnOrder>().unwrap().players.clone() {
+            assert_eq!(
+                world.component::<Hand>(player).unwrap().dice,
+                loaded.component::<Hand>(player).unwrap().dice
+            );
+            assert_eq!(
+                world.component::<Gamertag>(player).unwrap().name,
+                loaded.component::<Gamertag>(player).unwrap().name
+            );
+        }
+    }
+
+    #[test]
+    fn loading_restores_the_rng_stream() {
+        let mut world = world_mid_round();
+        let mut loaded = round_trip(&world);
+
+        let original = world.resource_mut::<DiceRng>().unwrap().roll_many(20);
+        let resumed = loaded.resource_mut::<DiceRng>().unwrap().roll_many(20);
+        assert_eq!(original, resumed);
+    }
+
+    #[test]
+    fn table_options_survive_saving_and_older_saves_default_them_off() {
+        let mut world = world_mid_round();
+        let options = TableOptions {
+            hot_seat: true,
+            tui: true,
+        };
+        world.insert_resource(options);
+        assert_eq!(
+            *round_trip(&world).resource::<TableOptions>().unwrap(),
+            options
+        );
+
+        let mut save = serde_json::to_value(SaveFile::from_world(&world).unwrap()).unwrap();
+        let fields = save.as_object_mut().unwrap();
+        fields["table_options"]
+            .as_object_mut()
+            .unwrap()
+            .remove("tui");
+        let loaded = SaveFile::from_json(&save.to_string())
+            .unwrap()
+            .into_world()
+            .unwrap();
+        assert!(!loaded.resource::<TableOptions>().unwrap().tui);
+
+        save.as_object_mut().unwrap().remove("table_options");
+        let loaded = SaveFile::from_json(&save.to_string())
+            .unwrap()
+            .into_world()
+            .unwrap();
+        assert!(!loaded.resource::<TableOptions>().unwrap().hot_seat);
+    }
+
+    #[test]
+    fn incompatible_versions_are_rejected() {
+        let mut save =
+            serde_json::to_value(SaveFile::from_world(&world_mid_round()).unwrap()).unwrap();
+        save["version"] = (SAVE_VERSION + 1).into();
+
+        let err = SaveFile::from_json(&save.to_string()).unwrap_err();
+        assert!(err.to_string().contains("not supported"), "{err}");
+    }
+
+    #[test]
+    fn non_save_files_are_rejected() {
+        assert!(SaveFile::from_json("{\"hello\": 1}").is_err());
+    }
+}
@@ -0,0 +1,465 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use anyhow::{Result, bail};
+use game_engine::World;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::Serialize;
+
+use crate::bot::{BayesBot, HeuristicBot};
+use crate::cfr::{CfrBot, StrategyTable};
+use crate::components::player::Controller;
+use crate::controller::{PlayerController, TurnAction};
+use crate::event_systems::process_events;
+use crate::resources::{ChallengeKind, DudoRules, GamePhase, GameState, TurnOrder};
+use crate::view::PlayerView;
+use crate::{DudoEvent, setup_game};
+
+/// Turns after which a simulated game is reported as stuck.
+const MAX_TURNS: u32 = 10_000;
+
+/// z-score for the 95% confidence intervals in reports.
+const Z_95: f64 = 1.96;
+
+/// Strategy tables already read, so every CFR seat playing from the same
+/// file shares one copy of it.
+#[derive(Debug, Clone, Default)]
+pub struct StrategyCache {
+    tables: HashMap<String, Arc<StrategyTable>>,
+}
+
+impl StrategyCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The table stored at `path`, loaded on first use.
+    pub fn get(&mut self, path: &str) -> Result<Arc<StrategyTable>> {
+        if let Some(table) = self.tables.get(path) {
+            return Ok(Arc::clone(table));
+        }
+        let table = Arc::new(StrategyTable::load(path)?);
+        self.tables.insert(path.to_string(), Arc::clone(&table));
+        Ok(table)
+    }
+}
+
+/// Builds the controller for a bot seat. Humans cannot be simulated.
+pub fn bot_for(
+    controller: &Controller,
+    seed: u64,
+    strategies: &mut StrategyCache,
+) -> Result<Box<dyn PlayerController>> {
+    Ok(match controller {
+        Controller::Human => bail!("human seats cannot be simulated"),
+        Controller::Bot(difficulty) => Box::new(HeuristicBot::new(*difficulty)),
+        Controller::Bayes => Box::new(BayesBot::new()),
+        Controller::Cfr { strategy } => Box::new(CfrBot::new(strategies.get(strategy)?, seed)),
+    })
+}
+
+/// A tournament of bot-only games.
+#[derive(Debug, Clone)]
+pub struct Tournament {
+    pub entrants: Vec<Controller>,
+    pub games: u32,
+    pub seed: u64,
+    pub rules: DudoRules,
+}
+
+impl Tournament {
+    pub fn new(entrants: Vec<Controller>, games: u32, seed: u64, rules: DudoRules) -> Result<Self> {
+        if !(2..=6).contains(&entrants.len()) {
+            bail!("a tournament needs 2-6 entrants, got {}", entrants.len());
+        }
+        if entrants.contains(&Controller::Human) {
+            bail!("human seats cannot be simulated");
+        }
+        Ok(Self {
+            entrants,
+            games,
+            seed,
+            rules,
+        })
+    }
+
+    /// Plays every game. The seating rotates each game so no entrant keeps
+    /// the same seat, and each game's seed is drawn from `seed`.
+    pub fn run(&self) -> Result<TournamentReport> {
+        let mut seeds = ChaCha8Rng::seed_from_u64(self.seed);
+        let mut stats = vec![EntrantStats::default(); self.entrants.len()];
+        let mut rounds = Vec::new();
+        let mut turns = Vec::new();
+        let mut strategies = StrategyCache::new();
+
+        for game in 0..self.games {
+            let seating: Vec<usize> = (0..self.entrants.len())
+                .map(|seat| (seat + game as usize) % self.entrants.len())
+                .collect();
+            let result = self.play_game(&seating, seeds.random(), &mut strategies)?;
+
+            stats[result.winner].wins += 1;
+            for (entrant, record) in result.challenges {
+                let entry = &mut stats[entrant];
+                match record.kind {
+                    ChallengeKind::Dudo => {
+                        entry.dudos += 1;
+                        entry.dudos_won += record.won as u32;
+                    }
+                    ChallengeKind::Calza => {
+                        entry.calzas += 1;
+                        entry.calzas_won += record.won as u32;
+                    }
+                }
+            }
+            rounds.push(result.rounds as f64);
+            turns.push(result.turns as f64);
+        }
+
+        let entrants = self
+            .entrants
+            .iter()
+            .zip(stats)
+            .map(|(controller, stats)| EntrantReport::new(controller, stats, self.games))
+            .collect();
+        Ok(TournamentReport {
+            games: self.games,
+            seed: self.seed,
+            rules: self.rules,
+            entrants,
+            rounds: Summary::of(&rounds),
+            turns: Summary::of(&turns),
+        })
+    }
+
+    /// Plays one game with entrant `seating[i]` in seat `i`.
+    fn play_game(
+        &self,
+        seating: &[usize],
+        seed: u64,
+        strategies: &mut StrategyCache,
+    ) -> Result<GameResult> {
+        let names = seating
+            .iter()
+            .map(|&entrant| format!("{} #{entrant}", self.entrants[entrant]))
+            .collect();
+        let mut world = setup_game(names, self.rules, seed)?;
+        let players = world.resource::<TurnOrder>()?.players.clone();
+        let mut bots = Vec::new();
+        for (&player, &entrant) in players.iter().zip(seating) {
+            world.insert_component(player, self.entrants[entrant].clone())?;
+            bots.push(bot_for(
+                &self.entrants[entrant],
+                seed.wrapping_add(player.id),
+                strategies,
+            )?);
+        }
+        emit_and_process(&mut world, DudoEvent::GameReady)?;
+
+        let mut result = GameResult::default();
+        while result.turns < MAX_TURNS {
+            match world.resource::<GameState>()?.phase {
+                GamePhase::RoundStart => emit_and_process(&mut world, DudoEvent::RollDice)?,
+                GamePhase::GameOver => {
+                    let winner = world.resource::<TurnOrder>()?.players[0];
+                    result.winner = seating[winner.id as usize];
+                    result.rounds = world.resource::<GameState>()?.round;
+                    return Ok(result);
+                }
+                _ => {
+                    let player = world.resource::<TurnOrder>()?.current_player();
+                    let view = PlayerView::for_player(&world, player)?;
+                    let Some(action) = bots[player.id as usize].choose_action(&view)? else {
+                        bail!("bot in seat {} passed its turn", player.id);
+                    };
+                    emit_and_process(&mut world, action.into_event(player))?;
+                    result.turns += 1;
+
+                    if !matches!(action, TurnAction::Bid { .. }) {
+                        let Some(outcome) = &world.resource::<GameState>()?.last_challenge else {
+                            bail!("challenge by seat {} left no outcome", player.id);
+                        };
+                        let won = match outcome.kind {
+                            ChallengeKind::Dudo => outcome.loser != Some(player),
+                            ChallengeKind::Calza => outcome.loser.is_none(),
+                        };
+                        result.challenges.push((
+                            seating[player.id as usize],
+                            ChallengeRecord {
+                                kind: outcome.kind,
+                                won,
+                            },
+                        ));
+                    }
+                }
+            }
+        }
+        bail!("game with seed {seed} did not finish within {MAX_TURNS} turns")
+    }
+}
+
+fn emit_and_process(world: &mut World, event: DudoEvent) -> Result<()> {
+    world.emit_event(event, 0.0)?;
+    process_events(world)
+}
+
+#[derive(Debug, Default)]
+struct GameResult {
+    winner: usize,
+    rounds: u32,
+    turns: u32,
+    challenges: Vec<(usize, ChallengeRecord)>,
+}
+
+#[derive(Debug)]
+struct ChallengeRecord {
+    kind: ChallengeKind,
+    won: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+struct EntrantStats {
+    wins: u32,
+    dudos: u32,
+    dudos_won: u32,
+    calzas: u32,
+    calzas_won: u32,
+}
+
+/// A proportion with its 95% Wilson score interval.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Rate {
+    pub successes: u32,
+    pub trials: u32,
+    pub rate: f64,
+    pub low: f64,
+    pub high: f64,
+}
+
+impl Rate {
+    pub fn new(successes: u32, trials: u32) -> Self {
+        if trials == 0 {
+            return Self {
+                successes,
+                trials,
+                rate: 0.0,
+                low: 0.0,
+                high: 1.0,
+            };
+        }
+        let n = trials as f64;
+        let p = successes as f64 / n;
+        let z2 = Z_95 * Z_95;
+        let centre = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+        let margin = Z_95 * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / (1.0 + z2 / n);
+        Self {
+            successes,
+            trials,
+            rate: p,
+            low: (centre - margin).max(0.0),
+            high: (centre + margin).min(1.0),
+        }
+    }
+}
+
+impl fmt::Display for Rate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:5.1}% [{:5.1}, {:5.1}] ({}/{})",
+            self.rate * 100.0,
+            self.low * 100.0,
+            self.high * 100.0,
+            self.successes,
+            self.trials
+        )
+    }
+}
+
+/// Mean of a sample with a normal-approximation 95% interval.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Summary {
+    pub mean: f64,
+    pub low: f64,
+    pub high: f64,
+}
+
+impl Summary {
+    pub fn of(values: &[f64]) -> Self {
+        if values.is_empty() {
+            return Self {
+                mean: 0.0,
+                low: 0.0,
+                high: 0.0,
+            };
+        }
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let margin = if values.len() > 1 {
+            let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+            Z_95 * (variance / n).sqrt()
+        } else {
+            0.0
+        };
+        Self {
+            mean,
+            low: mean - margin,
+            high: mean + margin,
+        }
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.1} [{:.1}, {:.1}]", self.mean, self.low, self.high)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EntrantReport {
+    pub controller: String,
+    pub wins: Rate,
+    pub dudo_success: Rate,
+    pub calza_success: Rate,
+}
+
+impl EntrantReport {
+    fn new(controller: &Controller, stats: EntrantStats, games: u32) -> Self {
+        Self {
+            controller: controller.to_string(),
+            wins: Rate::new(stats.wins, games),
+            dudo_success: Rate::new(stats.dudos_won, stats.dudos),
+            calza_success: Rate::new(stats.calzas_won, stats.calzas),
+        }
+    }
+}
+
+/// Results of a `Tournament`, printable as text or serialisable as JSON.
+#[derive(Debug, Clone, Serialize)]
+pub struct TournamentReport {
+    pub games: u32,
+    pub seed: u64,
+    pub rules: DudoRules,
+    pub entrants: Vec<EntrantReport>,
+    /// Rounds per game.
+    pub rounds: Summary,
+    /// Turns per game.
+    pub turns: Summary,
+}
+
+impl fmt::Display for TournamentReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} games, seed {}", self.games, self.seed)?;
+        let rules = &self.rules;
+        write!(f, "Rules: {} dice each", rules.starting_dice)?;
+        for (on, name) in [
+            (rules.wild_ones, "wild ones"),
+            (rules.calza, "calza"),
+            (rules.palifico, "palifico"),
+        ] {
+            if on {
+                write!(f, ", {name}")?;
+            }
+        }
+        writeln!(f)?;
+        writeln!(f, "Rounds per game: {}", self.rounds)?;
+        writeln!(f, "Turns per game:  {}", self.turns)?;
+        writeln!(f, "Intervals are 95% confidence.")?;
+        for (i, entrant) in self.entrants.iter().enumerate() {
+            writeln!(f)?;
+            writeln!(f, "#{i} {}", entrant.controller)?;
+            writeln!(f, "  wins   {}", entrant.wins)?;
+            writeln!(f, "  dudo   {}", entrant.dudo_success)?;
+            writeln!(f, "  calza  {}", entrant.calza_success)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot::Difficulty;
+
+    fn entrants() -> Vec<Controller> {
+        vec![
+            Controller::Bot(Difficulty::Easy),
+            Controller::Bot(Difficulty::Hard),
+            Controller::Bayes,
+        ]
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_report() {
+        let tournament = Tournament::new(entrants(), 6, 9, DudoRules::perudo()).unwrap();
+        let a = serde_json::to_string(&tournament.run().unwrap()).unwrap();
+        let b = serde_json::to_string(&tournament.run().unwrap()).unwrap();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn strategy_files_are_read_once() {
+        let config = crate::cfr::CfrConfig::new([1, 1], DudoRules::perudo()).unwrap();
+        let mut trainer = crate::cfr::CfrTrainer::new(config, 1);
+        trainer.train(50);
+        let path = std::env::temp_dir().join(format!("dudo-cache-{}.json", std::process::id()));
+        trainer.strategy().save(&path).unwrap();
+
+        let mut strategies = StrategyCache::new();
+        let path = path.to_str().unwrap();
+        let first = strategies.get(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        let second = strategies.get(path).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+    }
+
+    #[test]
+    fn every_game_has_one_winner() {
+        let report = Tournament::new(entrants(), 12, 3, DudoRules::perudo())
+            .unwrap()
+            .run()
+            .unwrap();
+        let wins: u32 = report.entrants.iter().map(|e| e.wins.successes).sum();
+        assert_eq!(wins, 12);
+        assert!(report.rounds.mean >= 1.0);
+        for entrant in &report.entrants {
+            assert!(entrant.wins.low <= entrant.wins.rate);
+            assert!(entrant.wins.rate <= entrant.wins.high);
+        }
+    }
+
+    #[test]
+    fn the_rules_are_played_and_reported() {
+        let rules = DudoRules::classic();
+        let report = Tournament::new(entrants(), 6, 3, rules)
+            .unwrap()
+            .run()
+            .unwrap();
+        assert_eq!(report.rules, rules);
+        assert!(report.entrants.iter().all(|e| e.calza_success.trials == 0));
+        assert!(report.to_string().contains("Rules: 5 dice each\n"));
+    }
+
+    #[test]
+    fn humans_cannot_enter() {
+        assert!(
+            Tournament::new(
+                vec![Controller::Human, Controller::Bayes],
+                1,
+                0,
+                DudoRules::perudo(),
+            )
+            .is_err()
+        );
+        assert!(Tournament::new(vec![Controller::Bayes], 1, 0, DudoRules::perudo()).is_err());
+    }
+
+    #[test]
+    fn wilson_interval_matches_a_known_value() {
+        let rate = Rate::new(5, 10);
+        assert!((rate.low - 0.2366).abs() < 1e-3);
+        assert!((rate.high - 0.7634).abs() < 1e-3);
+    }
+}
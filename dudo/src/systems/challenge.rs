@@ -1,10 +1,87 @@
+use crate::bid::{ACES, Bid};
 use crate::dice::Dice;
+use crate::fair_dice::committed_proof;
+use crate::resources::{
+    BidHistory, ChallengeKind, ChallengeOutcome, DiceRng, DudoRules, GamePhase, TurnOrder,
+};
 use crate::{components::dice::Hand, resources::GameState};
 use game_engine::{Entity, World};
 
-use crate::resources::TurnOrder;
-use anyhow::Result;
-use rand::random_range;
+use anyhow::{Result, bail};
+
+pub struct ChallengeSystem;
+
+impl ChallengeSystem {
+    pub fn run(world: &mut World, challenger: Entity) -> Result<()> {
+        let bid = challengeable_bid(world, challenger)?;
+
+        let revealed = reveal_hands(world)?;
+        let proof = committed_proof(world)?;
+        let total = count_total_dice(world, bid.face)?;
+        let loser = resolve_challenge(world, challenger, bid.player)?;
+        remove_die_from_player(world, loser)?;
+
+        world.resource_mut::<GameState>()?.last_challenge = Some(ChallengeOutcome {
+            kind: ChallengeKind::Dudo,
+            challenger,
+            bid,
+            total,
+            loser: Some(loser),
+            revealed,
+            proof,
+        });
+        end_round(world, loser)
+    }
+}
+
+pub struct CalzaSystem;
+
+impl CalzaSystem {
+    pub fn run(world: &mut World, caller: Entity) -> Result<()> {
+        let rules = *world.resource::<DudoRules>()?;
+        if !rules.calza {
+            bail!("Calza is not allowed under the current rules");
+        }
+        let bid = challengeable_bid(world, caller)?;
+
+        let revealed = reveal_hands(world)?;
+        let proof = committed_proof(world)?;
+        let total = count_total_dice(world, bid.face)?;
+        let loser = if total == bid.quantity as usize {
+            add_die_to_player(world, caller, rules.starting_dice)?;
+            None
+        } else {
+            remove_die_from_player(world, caller)?;
+            Some(caller)
+        };
+
+        world.resource_mut::<GameState>()?.last_challenge = Some(ChallengeOutcome {
+            kind: ChallengeKind::Calza,
+            challenger: caller,
+            bid,
+            total,
+            loser,
+            revealed,
+            proof,
+        });
+        end_round(world, caller)
+    }
+}
+
+/// The bid `caller` may challenge right now.
+fn challengeable_bid(world: &World, caller: Entity) -> Result<Bid> {
+    let game_state = world.resource::<GameState>()?;
+    if game_state.phase != GamePhase::Bidding {
+        bail!("Bids can only be challenged during the bidding phase");
+    }
+    let bid = game_state
+        .current_bid
+        .ok_or_else(|| anyhow::anyhow!("No bid to challenge"))?;
+    if world.resource::<TurnOrder>()?.current_player() != caller {
+        bail!("It is not player {}'s turn", caller.id);
+    }
+    Ok(bid)
+}
 
 fn resolve_challenge(world: &World, challenger: Entity, challenged: Entity) -> Result<Entity> {
     let game_state = world.resource::<GameState>()?;
@@ -15,27 +92,225 @@ fn resolve_challenge(world: &World, challenger: Entity, challenged: Entity) -> R
 
     let total = count_total_dice(world, current_bid.face)?;
 
-    Ok(if total >= current_bid.quantity as usize {
+    Ok(if bid_stands(&current_bid, total) {
         challenger
     } else {
         challenged
     })
 }
 
+/// Whether `bid` survives a Dudo call when `total` dice count towards it.
+pub fn bid_stands(bid: &Bid, total: usize) -> bool {
+    total >= bid.quantity as usize
+}
+
+/// How many of `faces` count towards `face`, aces included when wild.
+pub fn count_matching(faces: impl IntoIterator<Item = u8>, face: u8, wild_ones: bool) -> usize {
+    faces
+        .into_iter()
+        .filter(|&f| f == face || (wild_ones && f == ACES))
+        .count()
+}
+
 fn count_total_dice(world: &World, face: u8) -> Result<usize> {
     let turn_order = world.resource::<TurnOrder>()?;
+    let rules = world.resource::<DudoRules>()?;
+    let wild_ones = world.resource::<GameState>()?.ones_are_wild(rules);
     let mut count = 0;
 
     for &player in &turn_order.players {
         let hand = world.component::<Hand>(player)?;
-        count += hand.dice.iter().filter(|d| d.face == Some(face)).count();
+        count += count_matching(hand.dice.iter().filter_map(|d| d.face), face, wild_ones);
     }
 
     Ok(count)
 }
 
-fn remove_die_from_player(world: &mut World, player: Entity) -> Result<Option<Dice>> {
+fn reveal_hands(world: &World) -> Result<Vec<(Entity, Vec<u8>)>> {
+    let turn_order = world.resource::<TurnOrder>()?;
+    let mut revealed = Vec::new();
+
+    for &player in &turn_order.players {
+        let hand = world.component::<Hand>(player)?;
+        revealed.push((player, hand.dice.iter().filter_map(|d| d.face).collect()));
+    }
+
+    Ok(revealed)
+}
+
+pub(crate) fn remove_die_from_player(world: &mut World, player: Entity) -> Result<Option<Dice>> {
+    let len = world.component::<Hand>(player)?.dice.len();
+    if len == 0 {
+        return Ok(None);
+    }
+    let idx = world.resource_mut::<DiceRng>()?.index(len);
     let hand = world.component_mut::<Hand>(player)?;
-    let idx = random_range((0..hand.dice.iter().len()));
     Ok(Some(hand.dice.swap_remove(idx)))
 }
+
+fn add_die_to_player(world: &mut World, player: Entity, max_dice: u8) -> Result<bool> {
+    let hand = world.component_mut::<Hand>(player)?;
+    if hand.dice.len() >= max_dice as usize {
+        return Ok(false);
+    }
+    hand.dice.push(Dice::new());
+    Ok(true)
+}
+
+/// Eliminates players without dice and sets up the next round, which
+/// `opener` starts if they are still in the game.
+pub(crate) fn end_round(world: &mut World, opener: Entity) -> Result<()> {
+    let dice_left = world.component::<Hand>(opener)?.dice.len();
+    let out_of_dice = dice_left == 0;
+    let palifico_rule = world.resource::<DudoRules>()?.palifico;
+
+    let turn_order = world.resource_mut::<TurnOrder>()?;
+    turn_order.set_current(opener);
+    if out_of_dice {
+        turn_order.remove_player(opener);
+    }
+    let remaining = turn_order.player_count();
+
+    world.resource_mut::<BidHistory>()?.clear_round();
+    let game_state = world.resource_mut::<GameState>()?;
+    game_state.current_bid = None;
+    game_state.palifico = None;
+    if palifico_rule && dice_left == 1 && game_state.palifico_played.insert(opener) {
+        game_state.palifico = Some(opener);
+    }
+    if remaining <= 1 {
+        game_state.phase = GamePhase::GameOver;
+    } else {
+        game_state.round += 1;
+        game_state.phase = GamePhase::RoundStart;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup_game;
+
+    fn world_with_hands(rules: DudoRules, hands: &[&[u8]]) -> (World, Vec<Entity>) {
+        let names = (0..hands.len()).map(|i| format!("Player {i}")).collect();
+        let mut world = setup_game(names, rules, 7).unwrap();
+        let players = world.resource::<TurnOrder>().unwrap().players.clone();
+        for (&player, faces) in players.iter().zip(hands) {
+            *world.component_mut::<Hand>(player).unwrap() = Hand::from_faces(faces);
+        }
+        world.resource_mut::<GameState>().unwrap().phase = GamePhase::Bidding;
+        (world, players)
+    }
+
+    fn place_bid(world: &mut World, player: Entity, quantity: u8, face: u8) {
+        world.resource_mut::<GameState>().unwrap().current_bid =
+            Some(Bid::new(player, quantity, face));
+        world.resource_mut::<TurnOrder>().unwrap().advance();
+    }
+
+    fn dice_count(world: &World, player: Entity) -> usize {
+        world.component::<Hand>(player).unwrap().dice.len()
+    }
+
+    #[test]
+    fn wild_ones_count_towards_the_bid_face() {
+        let (world, _) = world_with_hands(DudoRules::perudo(), &[&[1, 3, 5], &[3, 3, 1]]);
+        assert_eq!(count_total_dice(&world, 3).unwrap(), 5);
+
+        let (world, _) = world_with_hands(DudoRules::classic(), &[&[1, 3, 5], &[3, 3, 1]]);
+        assert_eq!(count_total_dice(&world, 3).unwrap(), 3);
+    }
+
+    #[test]
+    fn exact_calza_regains_a_die() {
+        let (mut world, players) = world_with_hands(DudoRules::perudo(), &[&[2, 4], &[4, 6]]);
+        place_bid(&mut world, players[0], 2, 4);
+
+        CalzaSystem::run(&mut world, players[1]).unwrap();
+
+        let outcome = world
+            .resource::<GameState>()
+            .unwrap()
+            .last_challenge
+            .clone();
+        assert_eq!(outcome.unwrap().loser, None);
+        assert_eq!(dice_count(&world, players[1]), 3);
+        assert_eq!(
+            world.resource::<TurnOrder>().unwrap().current_player(),
+            players[1]
+        );
+    }
+
+    #[test]
+    fn calza_never_exceeds_starting_dice() {
+        let full: &[u8] = &[4, 4, 4, 4, 4];
+        let (mut world, players) = world_with_hands(DudoRules::perudo(), &[full, full]);
+        place_bid(&mut world, players[0], 10, 4);
+
+        CalzaSystem::run(&mut world, players[1]).unwrap();
+
+        assert_eq!(dice_count(&world, players[1]), 5);
+    }
+
+    #[test]
+    fn wrong_calza_costs_the_caller_a_die() {
+        let (mut world, players) = world_with_hands(DudoRules::perudo(), &[&[2, 4], &[4, 6]]);
+        place_bid(&mut world, players[0], 3, 4);
+
+        CalzaSystem::run(&mut world, players[1]).unwrap();
+
+        assert_eq!(dice_count(&world, players[0]), 2);
+        assert_eq!(dice_count(&world, players[1]), 1);
+    }
+
+    #[test]
+    fn first_drop_to_one_die_starts_a_palifico_round() {
+        let (mut world, players) = world_with_hands(DudoRules::perudo(), &[&[2, 2], &[3, 5, 6]]);
+        place_bid(&mut world, players[1], 2, 2);
+        world
+            .resource_mut::<TurnOrder>()
+            .unwrap()
+            .set_current(players[0]);
+
+        ChallengeSystem::run(&mut world, players[0]).unwrap();
+
+        let state = world.resource::<GameState>().unwrap();
+        assert_eq!(state.palifico, Some(players[0]));
+        assert!(!state.ones_are_wild(&DudoRules::perudo()));
+    }
+
+    #[test]
+    fn palifico_happens_once_per_player() {
+        let (mut world, players) = world_with_hands(DudoRules::perudo(), &[&[2, 2], &[3, 5, 6]]);
+        world
+            .resource_mut::<GameState>()
+            .unwrap()
+            .palifico_played
+            .insert(players[0]);
+        place_bid(&mut world, players[1], 2, 2);
+        world
+            .resource_mut::<TurnOrder>()
+            .unwrap()
+            .set_current(players[0]);
+
+        ChallengeSystem::run(&mut world, players[0]).unwrap();
+
+        assert_eq!(world.resource::<GameState>().unwrap().palifico, None);
+    }
+
+    #[test]
+    fn ones_are_not_wild_in_palifico_rounds() {
+        let (mut world, _) = world_with_hands(DudoRules::perudo(), &[&[1, 3], &[3, 1]]);
+        world.resource_mut::<GameState>().unwrap().palifico = Some(Entity::new(0));
+        assert_eq!(count_total_dice(&world, 3).unwrap(), 2);
+    }
+
+    #[test]
+    fn calza_is_rejected_when_disabled() {
+        let (mut world, players) = world_with_hands(DudoRules::classic(), &[&[2, 4], &[4, 6]]);
+        place_bid(&mut world, players[0], 2, 4);
+
+        assert!(CalzaSystem::run(&mut world, players[1]).is_err());
+    }
+}
@@ -0,0 +1,81 @@
+use anyhow::{Result, ensure};
+use game_engine::{Entity, World};
+
+use crate::fair_dice::{Commitment, RoundCommitments};
+use crate::resources::{GamePhase, GameState, TurnOrder};
+
+pub struct DealerCommitSystem;
+
+impl DealerCommitSystem {
+    /// Opens the round's commitments with the dealer's.
+    pub fn run(world: &mut World, commitment: Commitment) -> Result<()> {
+        let game_state = world.resource::<GameState>()?;
+        ensure!(
+            game_state.phase == GamePhase::RoundStart,
+            "Seeds can only be committed before the dice are rolled"
+        );
+        let round = game_state.round;
+        *world.resource_mut::<RoundCommitments>()? = RoundCommitments {
+            round,
+            dealer: Some(commitment),
+            players: Vec::new(),
+        };
+        Ok(())
+    }
+}
+
+pub struct SeedCommitSystem;
+
+impl SeedCommitSystem {
+    pub fn run(world: &mut World, player: Entity, commitment: Commitment) -> Result<()> {
+        let game_state = world.resource::<GameState>()?;
+        ensure!(
+            game_state.phase == GamePhase::RoundStart,
+            "Seeds can only be committed before the dice are rolled"
+        );
+        let round = game_state.round;
+        ensure!(
+            world.resource::<TurnOrder>()?.players.contains(&player),
+            "Player {} is not in the game",
+            player.id
+        );
+        let commitments = world.resource_mut::<RoundCommitments>()?;
+        ensure!(
+            !commitments.needs_dealer(round),
+            "The dealer has not committed to this round yet"
+        );
+        ensure!(
+            !commitments.has_committed(player),
+            "Player {} already committed to a seed this round",
+            player.id
+        );
+        commitments.players.push((player, commitment));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fair_dice::Seed;
+    use crate::resources::DudoRules;
+    use crate::setup_game;
+
+    #[test]
+    fn players_commit_once_after_the_dealer() {
+        let names = vec!["Ana".to_string(), "Ben".to_string()];
+        let mut world = setup_game(names, DudoRules::perudo(), 0).unwrap();
+        let ana = world.resource::<TurnOrder>().unwrap().players[0];
+        let commitment = Seed::random().commitment();
+
+        assert!(SeedCommitSystem::run(&mut world, ana, commitment).is_err());
+        DealerCommitSystem::run(&mut world, commitment).unwrap();
+        SeedCommitSystem::run(&mut world, ana, commitment).unwrap();
+        assert!(SeedCommitSystem::run(&mut world, ana, commitment).is_err());
+        assert!(SeedCommitSystem::run(&mut world, Entity::new(99), commitment).is_err());
+
+        let commitments = world.resource::<RoundCommitments>().unwrap();
+        assert_eq!(commitments.round, 1);
+        assert_eq!(commitments.players, vec![(ana, commitment)]);
+    }
+}
@@ -0,0 +1,72 @@
+use anyhow::{Result, ensure};
+use game_engine::{Entity, World};
+
+use crate::components::dice::Hand;
+use crate::resources::{BidHistory, GamePhase, GameState, TurnOrder};
+
+pub struct ForfeitSystem;
+
+impl ForfeitSystem {
+    /// Takes `player` out of the game. A round in progress is called off, so
+    /// nobody is left bidding on dice that are no longer on the table.
+    pub fn run(world: &mut World, player: Entity) -> Result<()> {
+        let phase = world.resource::<GameState>()?.phase;
+        ensure!(phase != GamePhase::GameOver, "The game is already over");
+        let turn_order = world.resource_mut::<TurnOrder>()?;
+        ensure!(
+            turn_order.players.contains(&player),
+            "Player {} is not in the game",
+            player.id
+        );
+        turn_order.remove_player(player);
+        let remaining = turn_order.player_count();
+        world.component_mut::<Hand>(player)?.dice.clear();
+
+        world.resource_mut::<BidHistory>()?.clear_round();
+        let game_state = world.resource_mut::<GameState>()?;
+        game_state.current_bid = None;
+        if game_state.palifico == Some(player) {
+            game_state.palifico = None;
+        }
+        if remaining <= 1 {
+            game_state.phase = GamePhase::GameOver;
+        } else if phase == GamePhase::Bidding {
+            game_state.round += 1;
+            game_state.phase = GamePhase::RoundStart;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bid::Bid;
+    use crate::resources::DudoRules;
+    use crate::setup_game;
+
+    #[test]
+    fn forfeiting_calls_off_the_round_and_can_end_the_game() {
+        let names = vec!["Ana".to_string(), "Ben".to_string(), "Cid".to_string()];
+        let mut world = setup_game(names, DudoRules::perudo(), 3).unwrap();
+        let players = world.resource::<TurnOrder>().unwrap().players.clone();
+        let state = world.resource_mut::<GameState>().unwrap();
+        state.phase = GamePhase::Bidding;
+        state.current_bid = Some(Bid::new(players[0], 2, 3));
+
+        ForfeitSystem::run(&mut world, players[1]).unwrap();
+        let state = world.resource::<GameState>().unwrap();
+        assert_eq!(state.phase, GamePhase::RoundStart);
+        assert!(state.current_bid.is_none());
+        assert!(world.component::<Hand>(players[1]).unwrap().dice.is_empty());
+        assert!(ForfeitSystem::run(&mut world, players[1]).is_err());
+
+        ForfeitSystem::run(&mut world, players[0]).unwrap();
+        assert_eq!(
+            world.resource::<GameState>().unwrap().phase,
+            GamePhase::GameOver
+        );
+        let turn_order = world.resource::<TurnOrder>().unwrap();
+        assert_eq!(turn_order.current_player(), players[2]);
+    }
+}
@@ -1,3 +1,7 @@
 pub mod challenge;
+pub mod commit_seeds;
+pub mod forfeit;
 pub mod place_bid;
 pub mod roll_dice;
+pub mod start_game;
+pub mod turn_timer;
@@ -1,14 +1,42 @@
-use anyhow::Result;
+use anyhow::{Result, bail};
 use game_engine::{Entity, World};
 
-use crate::{components::bid::Bid, resources::GameState};
+use crate::{
+    components::bid::Bid,
+    resources::{BidHistory, DudoRules, GamePhase, GameState, TurnOrder},
+};
 
 pub struct PlaceBidSystem;
 
 impl PlaceBidSystem {
     pub fn run(world: &mut World, player: Entity, quantity: u8, face: u8) -> Result<()> {
-        let game_state = world.resource_mut::<GameState>()?;
-        game_state.current_bid = Some(Bid::new(player, quantity, face));
+        let bid = Bid::new(player, quantity, face);
+        let rules = *world.resource::<DudoRules>()?;
+        let game_state = world.resource::<GameState>()?;
+
+        if game_state.phase != GamePhase::Bidding {
+            bail!("Bids can only be made during the bidding phase");
+        }
+        if world.resource::<TurnOrder>()?.current_player() != player {
+            bail!("It is not player {}'s turn", player.id);
+        }
+        if !game_state.is_valid_next_bid(&bid, &rules) {
+            match game_state.current_bid {
+                Some(prev) if game_state.is_palifico() => {
+                    bail!(
+                        "Palifico: bid must be more than {} × {}",
+                        prev.quantity,
+                        prev.face
+                    )
+                }
+                Some(prev) => bail!("Bid must be higher than {} × {}", prev.quantity, prev.face),
+                None => bail!("Bid must be at least one die of face 1-6"),
+            }
+        }
+
+        world.resource_mut::<GameState>()?.current_bid = Some(bid);
+        world.resource_mut::<BidHistory>()?.push(bid);
+        world.resource_mut::<TurnOrder>()?.advance();
         Ok(())
     }
 }
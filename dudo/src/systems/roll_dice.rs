@@ -1,36 +1,97 @@
 use crate::components::dice::{Dice, Hand};
 use crate::components::player::Player;
-use crate::resources::{GamePhase, GameState};
+use crate::fair_dice::committed_proof;
+use crate::resources::{BidHistory, DiceRng, GamePhase, GameState};
 use anyhow::Result;
 use game_engine::World;
-use rand::random_range;
 
 pub struct RollDiceSystem;
 
 impl RollDiceSystem {
     pub fn run(world: &mut World) -> Result<()> {
-        let players_to_roll = world.query::<(Player, Hand)>();
+        let mut players_to_roll = world.query::<(Player, Hand)>();
+        // Storage order is not stable, and the rng must be drawn from in the
+        // same order every time for a seed to replay.
+        players_to_roll.sort_by_key(|entity| entity.id);
         let state = world.resource::<GameState>()?;
 
         if state.phase == GamePhase::RoundStart {
+            // Deal from the committed seeds when there are any, so the
+            // hands can be checked once they are revealed.
+            let proof = committed_proof(world)?;
             for entity in players_to_roll {
-                if let Ok(hand) = world.component_mut::<Hand>(entity) {
-                    roll_hand(hand);
+                let mut hand = std::mem::take(world.component_mut::<Hand>(entity)?);
+                match &proof {
+                    Some(proof) => hand = Hand::from_faces(&proof.hand(entity, hand.dice.len())),
+                    None => roll_hand(&mut hand, world.resource_mut::<DiceRng>()?),
                 }
+                *world.component_mut::<Hand>(entity)? = hand;
             }
         }
 
+        world.resource_mut::<BidHistory>()?.clear_round();
         let state = world.resource_mut::<GameState>()?;
+        state.current_bid = None;
         state.phase = GamePhase::Bidding;
 
         Ok(())
     }
 }
 
-pub fn roll_dice(dice: &mut Dice) {
-    dice.face = Some(random_range(1..7));
+pub fn roll_dice(dice: &mut Dice, rng: &mut DiceRng) {
+    dice.face = Some(rng.roll());
 }
 
-pub fn roll_hand(hand: &mut Hand) {
-    hand.dice.iter_mut().for_each(|die| roll_dice(die));
+pub fn roll_hand(hand: &mut Hand, rng: &mut DiceRng) {
+    for die in &mut hand.dice {
+        roll_dice(die, rng);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::{DudoRules, TurnOrder};
+    use crate::setup_game;
+
+    fn rolled_hands(seed: u64) -> Vec<Vec<Option<u8>>> {
+        let names = vec!["Ana".to_string(), "Ben".to_string(), "Cid".to_string()];
+        let mut world = setup_game(names, DudoRules::perudo(), seed).unwrap();
+        RollDiceSystem::run(&mut world).unwrap();
+
+        let players = world.resource::<TurnOrder>().unwrap().players.clone();
+        players
+            .into_iter()
+            .map(|player| {
+                let hand = world.component::<Hand>(player).unwrap();
+                hand.dice.iter().map(|d| d.face).collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_rolls_the_same_hands() {
+        assert_eq!(rolled_hands(42), rolled_hands(42));
+    }
+
+    #[test]
+    fn different_seeds_roll_different_hands() {
+        assert_ne!(rolled_hands(1), rolled_hands(2));
+    }
+
+    #[test]
+    fn rolling_moves_the_game_to_bidding() {
+        let names = vec!["Ana".to_string(), "Ben".to_string()];
+        let mut world = setup_game(names, DudoRules::perudo(), 0).unwrap();
+        RollDiceSystem::run(&mut world).unwrap();
+
+        assert_eq!(
+            world.resource::<GameState>().unwrap().phase,
+            GamePhase::Bidding
+        );
+        for player in world.resource::<TurnOrder>().unwrap().players.clone() {
+            let hand = world.component::<Hand>(player).unwrap();
+            assert!(hand.dice.iter().all(|d| matches!(d.face, Some(1..=6))));
+        }
+    }
 }
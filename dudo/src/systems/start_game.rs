@@ -0,0 +1,17 @@
+use anyhow::Result;
+use game_engine::World;
+
+use crate::resources::{DiceRng, GamePhase, GameState, TurnOrder};
+
+pub struct StartGameSystem;
+
+impl StartGameSystem {
+    /// Picks who opens the first round and moves the game to its first roll.
+    pub fn run(world: &mut World) -> Result<()> {
+        let player_count = world.resource::<TurnOrder>()?.player_count();
+        let starting_idx = world.resource_mut::<DiceRng>()?.index(player_count);
+        world.resource_mut::<TurnOrder>()?.current_index = starting_idx;
+        world.resource_mut::<GameState>()?.phase = GamePhase::RoundStart;
+        Ok(())
+    }
+}
@@ -0,0 +1,227 @@
+use anyhow::{Result, ensure};
+use game_engine::{Entity, World};
+
+use crate::DudoEvent;
+use crate::resources::{
+    BidHistory, DudoRules, GamePhase, GameState, TimeoutAction, TurnClock, TurnOrder,
+};
+use crate::systems::challenge::{ChallengeSystem, end_round, remove_die_from_player};
+use crate::systems::place_bid::PlaceBidSystem;
+
+/// Seconds before the deadline that the player to act is warned.
+pub const WARNING_SECS: u32 = 10;
+
+pub struct TurnTimerSystem;
+
+impl TurnTimerSystem {
+    /// Moves the game clock on by `elapsed` seconds and returns the timer
+    /// event that is now due, if any, for the caller to emit.
+    pub fn run(world: &mut World, elapsed: f64) -> Result<Option<DudoEvent>> {
+        let Some(limit) = world.resource::<DudoRules>()?.turn_seconds else {
+            return Ok(None);
+        };
+        let game_state = world.resource::<GameState>()?;
+        let turn = (game_state.phase == GamePhase::Bidding).then_some((
+            game_state.round,
+            world.resource::<TurnOrder>()?.current_player(),
+            world.resource::<BidHistory>()?.bids.len(),
+        ));
+
+        let clock = world.resource_mut::<TurnClock>()?;
+        clock.now += elapsed;
+        if clock.turn != turn {
+            clock.turn = turn;
+            clock.started = clock.now;
+            clock.warned = false;
+        }
+        let Some((_, player, _)) = turn else {
+            return Ok(None);
+        };
+
+        let left = limit as f64 - (clock.now - clock.started);
+        Ok(if left <= 0.0 {
+            Some(DudoEvent::TurnTimedOut { player })
+        } else if !clock.warned && left <= WARNING_SECS as f64 {
+            Some(DudoEvent::TurnWarning {
+                player,
+                seconds_left: left.ceil() as u32,
+            })
+        } else {
+            None
+        })
+    }
+}
+
+pub struct TurnWarningSystem;
+
+impl TurnWarningSystem {
+    pub fn run(world: &mut World, player: Entity) -> Result<()> {
+        let clock = world.resource_mut::<TurnClock>()?;
+        if clock.turn.is_some_and(|(_, timed, _)| timed == player) {
+            clock.warned = true;
+        }
+        Ok(())
+    }
+}
+
+pub struct TimeoutSystem;
+
+impl TimeoutSystem {
+    /// Takes the rules' default action for `player`, whose time ran out.
+    pub fn run(world: &mut World, player: Entity) -> Result<()> {
+        let rules = *world.resource::<DudoRules>()?;
+        let game_state = world.resource::<GameState>()?;
+        ensure!(
+            game_state.phase == GamePhase::Bidding
+                && world.resource::<TurnOrder>()?.current_player() == player,
+            "It is not player {}'s turn",
+            player.id
+        );
+
+        match rules.on_timeout {
+            TimeoutAction::CallDudo if game_state.current_bid.is_some() => {
+                ChallengeSystem::run(world, player)
+            }
+            TimeoutAction::LoseDie => {
+                remove_die_from_player(world, player)?;
+                end_round(world, player)
+            }
+            // Nothing outbids the highest possible bid, so it is called.
+            TimeoutAction::MinimumRaise | TimeoutAction::CallDudo => {
+                match game_state.minimum_raise(player, &rules) {
+                    Some(bid) => PlaceBidSystem::run(world, player, bid.quantity, bid.face),
+                    None => ChallengeSystem::run(world, player),
+                }
+            }
+        }
+    }
+}
+
+/// Whether processing `event` revealed every hand, given the round it was
+/// made in.
+pub fn revealed_hands(world: &World, event: &DudoEvent, round: u32) -> Result<bool> {
+    Ok(match event {
+        DudoEvent::ChallengeMade { .. } | DudoEvent::CalzaCalled { .. } => true,
+        DudoEvent::TurnTimedOut { .. } => {
+            world.resource::<DudoRules>()?.on_timeout != TimeoutAction::LoseDie
+                && world.resource::<GameState>()?.round > round
+        }
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::dice::Hand;
+    use crate::event_systems::process_events;
+    use crate::setup_game;
+
+    fn timed_world(on_timeout: TimeoutAction) -> (World, Entity) {
+        let rules = DudoRules {
+            turn_seconds: Some(30),
+            on_timeout,
+            ..DudoRules::perudo()
+        };
+        let names = vec!["Ana".to_string(), "Ben".to_string()];
+        let mut world = setup_game(names, rules, 4).unwrap();
+        for event in [DudoEvent::GameReady, DudoEvent::RollDice] {
+            world.emit_event(event, 0.0).unwrap();
+            process_events(&mut world).unwrap();
+        }
+        let player = world.resource::<TurnOrder>().unwrap().current_player();
+        (world, player)
+    }
+
+    /// Advances the clock and processes whatever timer event comes due.
+    fn tick(world: &mut World, elapsed: f64) -> Option<DudoEvent> {
+        let event = TurnTimerSystem::run(world, elapsed).unwrap()?;
+        world.emit_event(event.clone(), 0.0).unwrap();
+        process_events(world).unwrap();
+        Some(event)
+    }
+
+    #[test]
+    fn the_clock_warns_then_times_out() {
+        let (mut world, player) = timed_world(TimeoutAction::MinimumRaise);
+        assert!(tick(&mut world, 0.0).is_none());
+        assert!(tick(&mut world, 19.0).is_none());
+        assert!(matches!(
+            tick(&mut world, 1.5),
+            Some(DudoEvent::TurnWarning {
+                seconds_left: 10,
+                ..
+            })
+        ));
+        assert!(tick(&mut world, 5.0).is_none());
+        assert!(matches!(
+            tick(&mut world, 5.0),
+            Some(DudoEvent::TurnTimedOut { player: p }) if p == player
+        ));
+
+        let bid = world.resource::<GameState>().unwrap().current_bid.unwrap();
+        assert_eq!((bid.player, bid.quantity, bid.face), (player, 1, 1));
+        // The next player's turn is timed from when it is first seen.
+        assert!(tick(&mut world, 25.0).is_none());
+        assert!(tick(&mut world, 25.0).is_some());
+        assert_ne!(
+            world.resource::<TurnOrder>().unwrap().current_player(),
+            player
+        );
+    }
+
+    #[test]
+    fn timeouts_can_call_dudo_or_cost_a_die() {
+        let (mut world, opener) = timed_world(TimeoutAction::CallDudo);
+        tick(&mut world, 0.0);
+        tick(&mut world, 30.0);
+        assert!(world.resource::<GameState>().unwrap().current_bid.is_some());
+        let round = world.resource::<GameState>().unwrap().round;
+        tick(&mut world, 0.0);
+        let event = tick(&mut world, 30.0).unwrap();
+        assert!(revealed_hands(&world, &event, round).unwrap());
+        let outcome = world
+            .resource::<GameState>()
+            .unwrap()
+            .last_challenge
+            .clone();
+        assert_ne!(outcome.unwrap().challenger, opener);
+
+        let (mut world, player) = timed_world(TimeoutAction::LoseDie);
+        tick(&mut world, 0.0);
+        tick(&mut world, 30.0);
+        assert_eq!(world.component::<Hand>(player).unwrap().dice.len(), 4);
+        assert_eq!(
+            world.resource::<GameState>().unwrap().phase,
+            GamePhase::RoundStart
+        );
+    }
+
+    #[test]
+    fn a_timeout_with_nothing_left_to_raise_calls_dudo() {
+        let (mut world, opener) = timed_world(TimeoutAction::MinimumRaise);
+        world
+            .emit_event(
+                DudoEvent::BidMade {
+                    player: opener,
+                    quantity: u8::MAX,
+                    face: 1,
+                },
+                0.0,
+            )
+            .unwrap();
+        process_events(&mut world).unwrap();
+        let round = world.resource::<GameState>().unwrap().round;
+        tick(&mut world, 0.0);
+        let event = tick(&mut world, 30.0).unwrap();
+
+        assert!(matches!(event, DudoEvent::TurnTimedOut { player } if player != opener));
+        assert!(revealed_hands(&world, &event, round).unwrap());
+        let game_state = world.resource::<GameState>().unwrap();
+        assert!(game_state.round > round);
+        assert_eq!(
+            game_state.last_challenge.clone().unwrap().loser,
+            Some(opener)
+        );
+    }
+}
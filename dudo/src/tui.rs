@@ -0,0 +1,819 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use game_engine::{Entity, World};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::DudoEvent;
+use crate::bid::Bid;
+use crate::chat::{ChatBody, ChatCommand, ChatLog, ChatMessage, reaction};
+use crate::components::dice::Hand;
+use crate::components::player::{Controller, Gamertag};
+use crate::controller::{PlayerController, TurnAction};
+use crate::event_systems::process_events;
+use crate::events::emit;
+use crate::game_log::log_chat;
+use crate::resources::{
+    ChallengeKind, DudoRules, GameMetadata, GamePhase, GameState, TableOptions, TurnClock,
+    TurnOrder,
+};
+use crate::simulate::{StrategyCache, bot_for};
+use crate::systems::turn_timer::{TurnTimerSystem, revealed_hands};
+use crate::view::PlayerView;
+
+/// How long a bot's move stays on screen before the next one.
+const BOT_DELAY: Duration = Duration::from_millis(600);
+
+/// How long a reveal stays up at a table without humans.
+const REVEAL_DELAY: Duration = Duration::from_millis(2000);
+
+/// How often the countdown redraws while a human's turn is timed.
+const CLOCK_TICK: Duration = Duration::from_millis(250);
+
+/// One seat at the table as the TUI shows it.
+#[derive(Debug, Clone)]
+pub struct SeatView {
+    pub name: String,
+    pub dice: usize,
+    pub active: bool,
+}
+
+/// Everything the TUI draws, read out of a `PlayerView` once per frame.
+#[derive(Debug, Clone)]
+pub struct TableView {
+    pub round: u32,
+    pub palifico: bool,
+    pub seats: Vec<SeatView>,
+    pub current_bid: Option<(String, u8, u8)>,
+    pub history: Vec<String>,
+    /// Name and hand of the player whose dice may be shown.
+    pub hand: Option<(String, String)>,
+    pub winner: Option<String>,
+}
+
+impl TableView {
+    /// Reads the table from `view`, showing its hand only if `show_hand`.
+    pub fn from_view(view: &PlayerView, show_hand: bool) -> Self {
+        let game_state = &view.game_state;
+        let active = (game_state.phase == GamePhase::Bidding).then(|| view.current_player());
+
+        let seats = view
+            .seats
+            .iter()
+            .map(|seat| SeatView {
+                name: seat.name.clone(),
+                dice: seat.dice as usize,
+                active: active == Some(seat.player),
+            })
+            .collect();
+        let history = view
+            .bid_history
+            .bids
+            .iter()
+            .map(|bid| {
+                format!(
+                    "{} bids {} × {}",
+                    view.name(bid.player),
+                    bid.quantity,
+                    bid.face
+                )
+            })
+            .collect();
+        let hand = show_hand.then(|| (view.name(view.player).to_string(), view.hand.to_string()));
+        let winner = (game_state.phase == GamePhase::GameOver)
+            .then(|| view.name(view.current_player()).to_string());
+
+        Self {
+            round: game_state.round,
+            palifico: game_state.is_palifico(),
+            seats,
+            current_bid: game_state
+                .current_bid
+                .map(|bid| (view.name(bid.player).to_string(), bid.quantity, bid.face)),
+            history,
+            hand,
+            winner,
+        }
+    }
+}
+
+/// Input and on-screen messages that live outside the world.
+#[derive(Debug, Clone)]
+pub struct TuiState {
+    pub quantity: u8,
+    pub face: u8,
+    pub status: String,
+    /// Whole seconds left on a timed turn.
+    pub countdown: Option<u32>,
+    /// Lines of the last challenge's reveal while it is on screen.
+    pub reveal: Option<Vec<String>>,
+    pub chat: ChatLog,
+    /// The chat line being typed, while the chat box is open.
+    pub typing: Option<String>,
+    /// Name of the player to hand the device to in hot-seat mode.
+    pub pass_to: Option<String>,
+    /// Player the bid input was last reset for.
+    input_for: Option<Entity>,
+    /// Player who confirmed they have the device in hot-seat mode.
+    handed_to: Option<Entity>,
+}
+
+impl TuiState {
+    pub fn new() -> Self {
+        Self {
+            quantity: 1,
+            face: 2,
+            status: String::from("Welcome to Dudo!"),
+            countdown: None,
+            reveal: None,
+            chat: ChatLog::new(),
+            typing: None,
+            pass_to: None,
+            input_for: None,
+            handed_to: None,
+        }
+    }
+
+    /// Starts the bid input at the lowest bid the viewing player may make.
+    fn reset_input(&mut self, view: &PlayerView) {
+        let player = view.player;
+        let lowest = (1..=view.total_dice())
+            .flat_map(|quantity| [2, 3, 4, 5, 6, 1].map(|face| Bid::new(player, quantity, face)))
+            .find(|bid| view.game_state.is_valid_next_bid(bid, &view.rules));
+        if let Some(bid) = lowest {
+            self.quantity = bid.quantity;
+            self.face = bid.face;
+        }
+        self.input_for = Some(player);
+    }
+}
+
+impl Default for TuiState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Plays `world` in a full-screen terminal UI until the game ends, or hands
+/// it back if the players quit to the menu.
+pub fn run(world: World) -> Result<Option<World>> {
+    let mut terminal = ratatui::try_init()?;
+    let result = play(&mut terminal, world);
+    ratatui::try_restore()?;
+    result
+}
+
+fn play(terminal: &mut DefaultTerminal, mut world: World) -> Result<Option<World>> {
+    let seed = world.resource::<GameMetadata>()?.seed;
+    let mut bots: HashMap<Entity, Box<dyn PlayerController>> = HashMap::new();
+    let mut humans = Vec::new();
+    let mut strategies = StrategyCache::new();
+    for (&player, controller) in world.query_component::<Controller>()? {
+        match controller {
+            Controller::Human => humans.push(player),
+            bot => {
+                bots.insert(
+                    player,
+                    bot_for(bot, seed.wrapping_add(player.id), &mut strategies)?,
+                );
+            }
+        }
+    }
+    let hot_seat = world.resource::<TableOptions>()?.hot_seat && humans.len() > 1;
+    let bot_seats: Vec<Entity> = bots.keys().copied().collect();
+    let mut state = TuiState::new();
+    let timed = world.resource::<DudoRules>()?.turn_seconds.is_some();
+    let mut last_tick = Instant::now();
+
+    loop {
+        let now = Instant::now();
+        let elapsed = now.duration_since(last_tick).as_secs_f64();
+        last_tick = now;
+        tick_clock(&mut world, elapsed, &bot_seats, &mut state)?;
+
+        let phase = world.resource::<GameState>()?.phase;
+        let current = world.resource::<TurnOrder>()?.current_player();
+        let human_turn = phase == GamePhase::Bidding && humans.contains(&current);
+        state.pass_to = if hot_seat && human_turn && state.handed_to != Some(current) {
+            Some(world.component::<Gamertag>(current)?.name.clone())
+        } else {
+            None
+        };
+        // In hot-seat mode a hand only shows once its owner has the device.
+        let local = if hot_seat {
+            state
+                .handed_to
+                .filter(|&player| human_turn && player == current)
+        } else {
+            match humans.as_slice() {
+                [only] => Some(*only),
+                _ if humans.contains(&current) => Some(current),
+                _ => None,
+            }
+        };
+        let view = PlayerView::for_player(&world, local.unwrap_or(current))?;
+        let table = TableView::from_view(&view, local.is_some());
+        terminal.draw(|frame| render(frame, &table, &state))?;
+
+        if state.reveal.is_some() {
+            let delay = if humans.is_empty() {
+                Some(REVEAL_DELAY)
+            } else {
+                None
+            };
+            match next_key(delay)? {
+                Some(KeyCode::Char('q') | KeyCode::Esc) => return Ok(Some(world)),
+                _ => state.reveal = None,
+            }
+            continue;
+        }
+
+        match phase {
+            GamePhase::RoundStart => {
+                emit(&mut world, DudoEvent::RollDice)?;
+                process_events(&mut world)?;
+                state.status = format!(
+                    "Round {} — dice rolled",
+                    world.resource::<GameState>()?.round
+                );
+            }
+            GamePhase::GameOver => {
+                next_key(None)?;
+                return Ok(None);
+            }
+            GamePhase::Bidding => {
+                // A timed turn wakes up regularly to run the clock.
+                let wait = timed.then_some(CLOCK_TICK);
+                let action = match bots.get_mut(&current) {
+                    Some(bot) => {
+                        if let Some(KeyCode::Char('q') | KeyCode::Esc) = next_key(Some(BOT_DELAY))?
+                        {
+                            return Ok(Some(world));
+                        }
+                        bot_action(&world, current, bot.as_mut())?
+                    }
+                    None if state.pass_to.is_some() => match next_key(wait)? {
+                        Some(KeyCode::Char('q') | KeyCode::Esc) => return Ok(Some(world)),
+                        Some(KeyCode::Enter) => {
+                            state.handed_to = Some(current);
+                            None
+                        }
+                        _ => None,
+                    },
+                    None => {
+                        let view = PlayerView::for_player(&world, current)?;
+                        if state.input_for != Some(current) {
+                            state.reset_input(&view);
+                        }
+                        match next_key(wait)? {
+                            Some(key) if state.typing.is_some() => {
+                                if let Some(line) = handle_chat_key(key, &mut state) {
+                                    let from = view.name(current).to_string();
+                                    submit_chat(&mut world, from, &line, &mut state)?;
+                                }
+                                None
+                            }
+                            Some(KeyCode::Char('t')) => {
+                                state.typing = Some(String::new());
+                                None
+                            }
+                            Some(KeyCode::Char('q') | KeyCode::Esc) => return Ok(Some(world)),
+                            Some(key) => handle_key(key, &view, &mut state),
+                            None => None,
+                        }
+                    }
+                };
+                if let Some(action) = action {
+                    take_action(&mut world, current, action, &mut state)?;
+                    if state.reveal.is_some() {
+                        react_to_challenge(&mut world, &bot_seats, &mut state)?;
+                    }
+                }
+            }
+            GamePhase::Challenge | GamePhase::RoundEnd => process_events(&mut world)?,
+        }
+    }
+}
+
+/// Asks the bot in `seat` for its move. It sees its own hand only, never
+/// the one shown on screen.
+fn bot_action(
+    world: &World,
+    seat: Entity,
+    bot: &mut dyn PlayerController,
+) -> Result<Option<TurnAction>> {
+    bot.choose_action(&PlayerView::for_player(world, seat)?)
+}
+
+/// Runs the turn clock, applying a warning or timeout once it is due.
+fn tick_clock(
+    world: &mut World,
+    elapsed: f64,
+    bots: &[Entity],
+    state: &mut TuiState,
+) -> Result<()> {
+    let round = world.resource::<GameState>()?.round;
+    if let Some(event) = TurnTimerSystem::run(world, elapsed)? {
+        emit(world, event.clone())?;
+        process_events(world)?;
+        match event {
+            DudoEvent::TurnWarning {
+                player,
+                seconds_left,
+            } => {
+                let name = &world.component::<Gamertag>(player)?.name;
+                state.status = format!("⏱ {name}, {seconds_left}s left!");
+            }
+            DudoEvent::TurnTimedOut { player } => {
+                let name = &world.component::<Gamertag>(player)?.name;
+                state.status = format!("⌛ {name} ran out of time");
+                state.input_for = None;
+                state.handed_to = None;
+                if revealed_hands(world, &event, round)? {
+                    state.reveal = reveal_lines(&PlayerView::for_player(world, player)?);
+                    react_to_challenge(world, bots, state)?;
+                }
+            }
+            _ => {}
+        }
+    }
+    let rules = world.resource::<DudoRules>()?;
+    state.countdown = world
+        .resource::<TurnClock>()?
+        .remaining(rules)
+        .map(|left| left.ceil() as u32);
+    Ok(())
+}
+
+/// Waits for a key press, up to `timeout` if given.
+fn next_key(timeout: Option<Duration>) -> Result<Option<KeyCode>> {
+    if let Some(timeout) = timeout
+        && !event::poll(timeout)?
+    {
+        return Ok(None);
+    }
+    loop {
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            return Ok(Some(key.code));
+        }
+        if timeout.is_some() {
+            return Ok(None);
+        }
+    }
+}
+
+/// Applies a key to the bid input, returning the action it confirms.
+pub fn handle_key(key: KeyCode, view: &PlayerView, state: &mut TuiState) -> Option<TurnAction> {
+    let game_state = &view.game_state;
+    let has_bid = game_state.current_bid.is_some();
+
+    match key {
+        KeyCode::Up | KeyCode::Char('k') => {
+            state.quantity = state.quantity.saturating_add(1);
+            None
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            state.quantity = state.quantity.saturating_sub(1).max(1);
+            None
+        }
+        KeyCode::Right | KeyCode::Char('l') => {
+            state.face = state.face % 6 + 1;
+            None
+        }
+        KeyCode::Left | KeyCode::Char('h') => {
+            state.face = (state.face + 4) % 6 + 1;
+            None
+        }
+        KeyCode::Enter => {
+            let bid = Bid::new(view.player, state.quantity, state.face);
+            if game_state.is_valid_next_bid(&bid, &view.rules) {
+                Some(TurnAction::Bid {
+                    quantity: state.quantity,
+                    face: state.face,
+                })
+            } else {
+                state.status = if game_state.is_palifico() {
+                    "Palifico: raise the quantity and keep the face!".to_string()
+                } else {
+                    "Bid must be higher!".to_string()
+                };
+                None
+            }
+        }
+        KeyCode::Char('d') if has_bid => Some(TurnAction::Dudo),
+        KeyCode::Char('c') if has_bid && view.rules.calza => Some(TurnAction::Calza),
+        _ => None,
+    }
+}
+
+fn take_action(
+    world: &mut World,
+    player: Entity,
+    action: TurnAction,
+    state: &mut TuiState,
+) -> Result<()> {
+    let name = world.component::<Gamertag>(player)?.name.clone();
+    emit(world, action.into_event(player))?;
+    process_events(world)?;
+    state.input_for = None;
+    state.handed_to = None;
+
+    state.status = match action {
+        TurnAction::Bid { quantity, face } => format!("{name} bids {quantity} × {face}"),
+        TurnAction::Dudo => format!("{name} calls Dudo!"),
+        TurnAction::Calza => format!("{name} calls Calza!"),
+    };
+    if !matches!(action, TurnAction::Bid { .. }) {
+        state.reveal = reveal_lines(&PlayerView::for_player(world, player)?);
+    }
+    Ok(())
+}
+
+/// Edits the chat line being typed, returning it once Enter sends it.
+pub fn handle_chat_key(key: KeyCode, state: &mut TuiState) -> Option<String> {
+    let typing = state.typing.as_mut()?;
+    match key {
+        KeyCode::Char(c) => typing.push(c),
+        KeyCode::Backspace => {
+            typing.pop();
+        }
+        KeyCode::Esc => state.typing = None,
+        KeyCode::Enter => return state.typing.take(),
+        _ => {}
+    }
+    None
+}
+
+/// Says a typed line at the table, or applies the mute it asks for.
+fn submit_chat(world: &mut World, from: String, line: &str, state: &mut TuiState) -> Result<()> {
+    match line.parse::<ChatCommand>() {
+        Ok(ChatCommand::Say(body)) => post_chat(world, ChatMessage { from, body }, state)?,
+        Ok(ChatCommand::Mute(name)) => {
+            state.chat.set_muted(&name, true);
+            state.status = format!("Muted {name}");
+        }
+        Ok(ChatCommand::Unmute(name)) => {
+            state.chat.set_muted(&name, false);
+            state.status = format!("Unmuted {name}");
+        }
+        Err(err) => state.status = err.to_string(),
+    }
+    Ok(())
+}
+
+fn post_chat(world: &mut World, message: ChatMessage, state: &mut TuiState) -> Result<()> {
+    log_chat(world, &message)?;
+    state.chat.push(message);
+    Ok(())
+}
+
+/// Lets the bots that took part in the last challenge emote about it.
+fn react_to_challenge(world: &mut World, bots: &[Entity], state: &mut TuiState) -> Result<()> {
+    let Some(outcome) = world.resource::<GameState>()?.last_challenge.clone() else {
+        return Ok(());
+    };
+    for &bot in bots {
+        if let Some(emote) = reaction(&outcome, bot) {
+            let from = world.component::<Gamertag>(bot)?.name.clone();
+            let body = ChatBody::Emote(emote);
+            post_chat(world, ChatMessage { from, body }, state)?;
+        }
+    }
+    Ok(())
+}
+
+/// Every hand revealed by the last challenge and who lost a die, for the
+/// reveal panel.
+pub fn reveal_lines(view: &PlayerView) -> Option<Vec<String>> {
+    let outcome = view.game_state.last_challenge.as_ref()?;
+    let name = |player| view.name(player);
+    let bid = outcome.bid;
+
+    let mut lines = Vec::new();
+    for (player, faces) in &outcome.revealed {
+        lines.push(format!("{}: {}", name(*player), Hand::from_faces(faces)));
+    }
+    lines.push(String::new());
+    lines.push(format!(
+        "Total: {} dice showing {} (bid was {})",
+        outcome.total, bid.face, bid.quantity
+    ));
+    let caller = name(outcome.challenger);
+    lines.push(match (outcome.kind, outcome.loser) {
+        (ChallengeKind::Calza, None) => format!("Exactly right! {caller} regains a die."),
+        (ChallengeKind::Calza, Some(_)) => format!("Not exact! {caller} loses a die."),
+        (ChallengeKind::Dudo, Some(loser)) if loser == outcome.challenger => {
+            format!("Bid was correct! {caller} loses a die.")
+        }
+        (ChallengeKind::Dudo, _) => format!("Bid was too high! {} loses a die.", name(bid.player)),
+    });
+    lines.push(String::new());
+    lines.push("Press any key to continue".to_string());
+    Some(lines)
+}
+
+/// Draws the table, bid history, hand and input panels.
+pub fn render(frame: &mut Frame, view: &TableView, state: &TuiState) {
+    let [header, body, footer] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Min(8),
+        Constraint::Length(3),
+    ])
+    .areas(frame.area());
+    let [left, right] =
+        Layout::horizontal([Constraint::Percentage(55), Constraint::Percentage(45)]).areas(body);
+    let [table, hand] = Layout::vertical([Constraint::Min(5), Constraint::Length(7)]).areas(left);
+
+    let mut title = format!("🎲 DUDO · Round {}", view.round);
+    if view.palifico {
+        title.push_str(" · 🔒 PALIFICO");
+    }
+    frame.render_widget(
+        Paragraph::new(title.bold().red()).block(Block::default().borders(Borders::ALL)),
+        header,
+    );
+
+    let [history, chat] =
+        Layout::vertical([Constraint::Min(5), Constraint::Percentage(45)]).areas(right);
+    render_table(frame, table, view);
+    render_history(frame, history, view);
+    render_chat(frame, chat, state);
+    render_hand(frame, hand, view, state);
+
+    let status_title = match state.countdown {
+        Some(left) => format!(" Status · ⏱ {left}s "),
+        None => " Status ".to_string(),
+    };
+    frame.render_widget(
+        Paragraph::new(state.status.as_str())
+            .block(Block::default().borders(Borders::ALL).title(status_title)),
+        footer,
+    );
+
+    if let Some(lines) = &state.reveal {
+        render_popup(frame, " Reveal ", lines);
+    } else if let Some(name) = &state.pass_to {
+        render_popup(
+            frame,
+            " Hot seat ",
+            &[
+                format!("🔄 Pass the device to {name}"),
+                String::new(),
+                "Press Enter when nobody else is looking".into(),
+            ],
+        );
+    } else if let Some(winner) = &view.winner {
+        render_popup(
+            frame,
+            " Game over ",
+            &[
+                format!("🏆 {winner} wins the game!"),
+                String::new(),
+                "Press any key".into(),
+            ],
+        );
+    }
+}
+
+fn render_table(frame: &mut Frame, area: Rect, view: &TableView) {
+    let mut lines: Vec<Line> = view
+        .seats
+        .iter()
+        .map(|seat| {
+            let marker = if seat.active { "▶ " } else { "  " };
+            let text = if seat.dice == 0 {
+                format!("{marker}{:<16} out", seat.name)
+            } else {
+                format!("{marker}{:<16} 🎲 × {}", seat.name, seat.dice)
+            };
+            match (seat.active, seat.dice) {
+                (_, 0) => Line::from(text).style(Style::default().fg(Color::DarkGray)),
+                (true, _) => Line::from(text).style(
+                    Style::default()
+                        .fg(Color::Green)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                _ => Line::from(text),
+            }
+        })
+        .collect();
+    lines.push(Line::from(""));
+    lines.push(match &view.current_bid {
+        Some((bidder, quantity, face)) => {
+            Line::from(format!("Current bid: {quantity} × {face} by {bidder}")).yellow()
+        }
+        None => Line::from("No bid yet"),
+    });
+
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(" Table ")),
+        area,
+    );
+}
+
+fn render_history(frame: &mut Frame, area: Rect, view: &TableView) {
+    // Keep the newest bids in view once the panel fills up.
+    let visible = area.height.saturating_sub(2) as usize;
+    let skip = view.history.len().saturating_sub(visible);
+    let items: Vec<ListItem> = view
+        .history
+        .iter()
+        .skip(skip)
+        .map(|line| ListItem::new(line.as_str()))
+        .collect();
+    frame.render_widget(
+        List::new(items).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Bids this round "),
+        ),
+        area,
+    );
+}
+
+fn render_chat(frame: &mut Frame, area: Rect, state: &TuiState) {
+    let mut visible = area.height.saturating_sub(2) as usize;
+    let input = state.typing.as_ref().map(|typing| format!("> {typing}_"));
+    if input.is_some() {
+        visible = visible.saturating_sub(1);
+    }
+    let skip = state.chat.lines().len().saturating_sub(visible);
+    let mut items: Vec<ListItem> = state
+        .chat
+        .lines()
+        .skip(skip)
+        .map(|message| ListItem::new(message.to_string()))
+        .collect();
+    if let Some(input) = input {
+        items.push(ListItem::new(input).cyan());
+    }
+    frame.render_widget(
+        List::new(items).block(Block::default().borders(Borders::ALL).title(" Chat ")),
+        area,
+    );
+}
+
+fn render_hand(frame: &mut Frame, area: Rect, view: &TableView, state: &TuiState) {
+    let mut lines = match &view.hand {
+        Some((name, hand)) => vec![Line::from(format!("{name}: {hand}")).bold()],
+        None => vec![Line::from("Dice hidden").dim()],
+    };
+    lines.push(Line::from(""));
+    lines.push(Line::from(format!("Bid: {} × {}", state.quantity, state.face)).cyan());
+    lines.push(Line::from("↑↓ quantity · ←→ face · Enter bid").dim());
+    lines.push(Line::from("d Dudo · c Calza · t chat · q menu").dim());
+    frame.render_widget(
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(Block::default().borders(Borders::ALL).title(" Your dice ")),
+        area,
+    );
+}
+
+fn render_popup(frame: &mut Frame, title: &str, lines: &[String]) {
+    let area = frame.area();
+    let width = area.width.min(60);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+    let text: Vec<Line> = lines.iter().map(|line| Line::from(line.as_str())).collect();
+    frame.render_widget(ratatui::widgets::Clear, popup);
+    frame.render_widget(
+        Paragraph::new(text).block(Block::default().borders(Borders::ALL).title(title)),
+        popup,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::{BidHistory, DudoRules};
+    use crate::setup_game;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    fn world() -> (World, Vec<Entity>) {
+        let names = vec!["Ana".to_string(), "Ben".to_string()];
+        let mut world = setup_game(names, DudoRules::perudo(), 4).unwrap();
+        let players = world.resource::<TurnOrder>().unwrap().players.clone();
+        *world.component_mut::<Hand>(players[0]).unwrap() = Hand::from_faces(&[1, 2, 3, 4, 5]);
+        let game_state = world.resource_mut::<GameState>().unwrap();
+        game_state.phase = GamePhase::Bidding;
+        game_state.current_bid = Some(Bid::new(players[1], 3, 4));
+        world
+            .resource_mut::<BidHistory>()
+            .unwrap()
+            .push(Bid::new(players[1], 3, 4));
+        (world, players)
+    }
+
+    fn screen(view: &TableView, state: &TuiState) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        terminal.draw(|frame| render(frame, view, state)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer.content().iter().map(|cell| cell.symbol()).collect()
+    }
+
+    /// Remembers every view it is shown and never acts.
+    struct Spy(Vec<PlayerView>);
+
+    impl PlayerController for Spy {
+        fn choose_action(&mut self, view: &PlayerView) -> Result<Option<TurnAction>> {
+            self.0.push(view.clone());
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn bots_only_see_their_own_seat() {
+        let (world, players) = world();
+        let mut spy = Spy(Vec::new());
+        bot_action(&world, players[1], &mut spy).unwrap();
+
+        let faces = |hand: &Hand| hand.dice.iter().map(|d| d.face).collect::<Vec<_>>();
+        let ben = world.component::<Hand>(players[1]).unwrap();
+        assert_eq!(spy.0.len(), 1);
+        assert_eq!(spy.0[0].player, players[1]);
+        assert_eq!(faces(&spy.0[0].hand), faces(ben));
+    }
+
+    #[test]
+    fn table_shows_players_bid_history_and_local_hand() {
+        let (world, players) = world();
+        let view = PlayerView::for_player(&world, players[0]).unwrap();
+        let screen = screen(&TableView::from_view(&view, true), &TuiState::new());
+
+        assert!(screen.contains("Ana"));
+        assert!(screen.contains("Ben"));
+        assert!(screen.contains("Current bid: 3 × 4 by Ben"));
+        assert!(screen.contains("Ben bids 3 × 4"));
+        assert!(screen.contains("⚀"));
+    }
+
+    #[test]
+    fn hand_is_hidden_without_a_local_player() {
+        let (world, players) = world();
+        let view = PlayerView::for_player(&world, players[0]).unwrap();
+        let view = TableView::from_view(&view, false);
+        let screen = screen(&view, &TuiState::new());
+        assert!(screen.contains("Dice hidden"));
+        assert!(!screen.contains("⚀"));
+    }
+
+    #[test]
+    fn hot_seat_hand_off_hides_the_hand() {
+        let (world, players) = world();
+        let view = PlayerView::for_player(&world, players[0]).unwrap();
+        let view = TableView::from_view(&view, false);
+        let mut state = TuiState::new();
+        state.pass_to = Some("Ana".to_string());
+        let screen = screen(&view, &state);
+        assert!(screen.contains("Pass the device to Ana"));
+        assert!(!screen.contains("⚀"));
+    }
+
+    #[test]
+    fn keys_edit_the_bid_and_only_valid_bids_confirm() {
+        let (world, players) = world();
+        let mut state = TuiState::new();
+        let view = PlayerView::for_player(&world, players[0]).unwrap();
+        state.reset_input(&view);
+        // Switching to aces halves the quantity.
+        assert_eq!((state.quantity, state.face), (2, 1));
+
+        let press = |key, state: &mut TuiState| handle_key(key, &view, state);
+        press(KeyCode::Up, &mut state);
+        press(KeyCode::Left, &mut state);
+        press(KeyCode::Left, &mut state);
+        press(KeyCode::Left, &mut state);
+        press(KeyCode::Left, &mut state);
+        assert_eq!((state.quantity, state.face), (3, 3));
+        assert_eq!(press(KeyCode::Enter, &mut state), None);
+        assert_eq!(state.status, "Bid must be higher!");
+
+        press(KeyCode::Up, &mut state);
+        assert_eq!(
+            press(KeyCode::Enter, &mut state),
+            Some(TurnAction::Bid {
+                quantity: 4,
+                face: 3
+            })
+        );
+        assert_eq!(
+            press(KeyCode::Char('d'), &mut state),
+            Some(TurnAction::Dudo)
+        );
+    }
+}
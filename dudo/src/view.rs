@@ -0,0 +1,242 @@
+use anyhow::{Context, Result};
+use game_engine::{Entity, World};
+use serde::{Deserialize, Serialize};
+
+use crate::components::dice::Hand;
+use crate::components::player::{Gamertag, Player};
+use crate::fair_dice::RoundCommitments;
+use crate::opponent_model::HandBeliefs;
+use crate::resources::{BidHistory, DudoRules, GamePhase, GameState, TurnOrder};
+
+/// Everything one player is allowed to know: the public table and their own
+/// hand. Other hands only appear in `GameState::last_challenge` once a
+/// challenge has revealed them.
+///
+/// Bots, the UIs and the network all read the game through this, so hidden
+/// dice cannot leak by accident.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerView {
+    pub player: Entity,
+    pub rules: DudoRules,
+    pub game_state: GameState,
+    pub turn_order: TurnOrder,
+    pub bid_history: BidHistory,
+    /// Every player at the table in seat order, including those knocked out.
+    pub seats: Vec<PublicSeat>,
+    pub hand: Hand,
+    /// The seed commitments for the round, when its dice are verifiable.
+    #[serde(default)]
+    pub commitments: RoundCommitments,
+}
+
+/// What everyone can see of a seat.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicSeat {
+    pub player: Entity,
+    pub name: String,
+    pub dice: u8,
+}
+
+impl PlayerView {
+    /// Projects `world` down to what `player` may see.
+    pub fn for_player(world: &World, player: Entity) -> Result<Self> {
+        Ok(Self {
+            player,
+            rules: *world.resource::<DudoRules>()?,
+            game_state: world.resource::<GameState>()?.clone(),
+            turn_order: world.resource::<TurnOrder>()?.clone(),
+            bid_history: world.resource::<BidHistory>()?.clone(),
+            seats: public_seats(world)?,
+            hand: world
+                .component::<Hand>(player)
+                .context("only players have a view")?
+                .clone(),
+            commitments: world.resource::<RoundCommitments>()?.clone(),
+        })
+    }
+
+    pub fn seat(&self, player: Entity) -> Option<&PublicSeat> {
+        self.seats.iter().find(|seat| seat.player == player)
+    }
+
+    pub fn name(&self, player: Entity) -> &str {
+        self.seat(player).map_or("?", |seat| seat.name.as_str())
+    }
+
+    pub fn dice_count(&self, player: Entity) -> u8 {
+        self.seat(player).map_or(0, |seat| seat.dice)
+    }
+
+    /// Dice still in play across all seated players.
+    pub fn total_dice(&self) -> u8 {
+        self.turn_order
+            .players
+            .iter()
+            .map(|&player| self.dice_count(player))
+            .sum()
+    }
+
+    pub fn ones_are_wild(&self) -> bool {
+        self.game_state.ones_are_wild(&self.rules)
+    }
+
+    pub fn current_player(&self) -> Entity {
+        self.turn_order.current_player()
+    }
+
+    pub fn is_my_turn(&self) -> bool {
+        self.game_state.phase == GamePhase::Bidding && self.current_player() == self.player
+    }
+
+    /// This round's bids read as evidence about each bidder's hand.
+    pub fn beliefs(&self) -> HandBeliefs {
+        HandBeliefs::from_bids(
+            &self.bid_history.bids,
+            |player| self.dice_count(player),
+            self.ones_are_wild(),
+        )
+    }
+}
+
+/// What someone watching the table sees: the public table, and in an
+/// omniscient view every hand as well.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpectatorView {
+    pub rules: DudoRules,
+    pub game_state: GameState,
+    pub turn_order: TurnOrder,
+    pub bid_history: BidHistory,
+    pub seats: Vec<PublicSeat>,
+    /// Each seat's hand in seat order; empty unless the view is omniscient.
+    pub hands: Vec<(Entity, Hand)>,
+}
+
+impl SpectatorView {
+    /// Projects `world` down to what anyone at the table could see.
+    pub fn public(world: &World) -> Result<Self> {
+        Ok(Self {
+            rules: *world.resource::<DudoRules>()?,
+            game_state: world.resource::<GameState>()?.clone(),
+            turn_order: world.resource::<TurnOrder>()?.clone(),
+            bid_history: world.resource::<BidHistory>()?.clone(),
+            seats: public_seats(world)?,
+            hands: Vec::new(),
+        })
+    }
+
+    /// The public view plus every hand. Only for sending on after a delay.
+    pub fn omniscient(world: &World) -> Result<Self> {
+        let mut view = Self::public(world)?;
+        for seat in &view.seats {
+            let hand = world.component::<Hand>(seat.player)?.clone();
+            view.hands.push((seat.player, hand));
+        }
+        Ok(view)
+    }
+
+    pub fn is_omniscient(&self) -> bool {
+        !self.hands.is_empty()
+    }
+
+    pub fn name(&self, player: Entity) -> &str {
+        self.seats
+            .iter()
+            .find(|seat| seat.player == player)
+            .map_or("?", |seat| seat.name.as_str())
+    }
+}
+
+/// Every player at the table in seat order, as everyone can see them.
+fn public_seats(world: &World) -> Result<Vec<PublicSeat>> {
+    let mut players = world.query::<(Player, Gamertag, Hand)>();
+    players.sort_by_key(|player| player.id);
+    let mut seats = Vec::new();
+    for seat in players {
+        seats.push(PublicSeat {
+            player: seat,
+            name: world.component::<Gamertag>(seat)?.name.clone(),
+            dice: world.component::<Hand>(seat)?.dice.len() as u8,
+        });
+    }
+    Ok(seats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DudoEvent;
+    use crate::event_systems::process_events;
+    use crate::setup_game;
+
+    fn world() -> (World, Vec<Entity>) {
+        let names = vec!["Ana".to_string(), "Ben".to_string(), "Cid".to_string()];
+        let mut world = setup_game(names, DudoRules::perudo(), 8).unwrap();
+        let players = world.resource::<TurnOrder>().unwrap().players.clone();
+        for event in [DudoEvent::GameReady, DudoEvent::RollDice] {
+            world.emit_event(event, 0.0).unwrap();
+            process_events(&mut world).unwrap();
+        }
+        (world, players)
+    }
+
+    #[test]
+    fn view_holds_only_the_players_own_hand() {
+        let (mut world, players) = world();
+        for (player, faces) in players.iter().zip([[6; 5], [1, 2, 3, 4, 1], [5; 5]]) {
+            *world.component_mut::<Hand>(*player).unwrap() = Hand::from_faces(&faces);
+        }
+        let view = PlayerView::for_player(&world, players[1]).unwrap();
+        let json = serde_json::to_string(&view).unwrap();
+
+        assert!(json.contains(r#"{"face":4}"#));
+        assert!(!json.contains(r#"{"face":5}"#));
+        assert!(!json.contains(r#"{"face":6}"#));
+        assert_eq!(view.seats.len(), 3);
+        assert_eq!(view.total_dice(), 15);
+        assert_eq!(view.name(players[2]), "Cid");
+    }
+
+    #[test]
+    fn hands_are_revealed_only_after_a_challenge() {
+        let (mut world, players) = world();
+        let opener = world.resource::<TurnOrder>().unwrap().current_player();
+        let view = PlayerView::for_player(&world, players[0]).unwrap();
+        assert!(view.game_state.last_challenge.is_none());
+
+        let bid = DudoEvent::BidMade {
+            player: opener,
+            quantity: 1,
+            face: 2,
+        };
+        world.emit_event(bid, 0.0).unwrap();
+        process_events(&mut world).unwrap();
+        let challenger = world.resource::<TurnOrder>().unwrap().current_player();
+        world
+            .emit_event(DudoEvent::ChallengeMade { challenger }, 0.0)
+            .unwrap();
+        process_events(&mut world).unwrap();
+
+        let view = PlayerView::for_player(&world, players[0]).unwrap();
+        let outcome = view.game_state.last_challenge.unwrap();
+        assert_eq!(outcome.revealed.len(), 3);
+    }
+
+    #[test]
+    fn spectators_see_hands_only_in_an_omniscient_view() {
+        let (mut world, players) = world();
+        for (player, faces) in players.iter().zip([[6; 5], [4; 5], [5; 5]]) {
+            *world.component_mut::<Hand>(*player).unwrap() = Hand::from_faces(&faces);
+        }
+
+        let public = serde_json::to_string(&SpectatorView::public(&world).unwrap()).unwrap();
+        for face in [4, 5, 6] {
+            assert!(!public.contains(&format!(r#"{{"face":{face}}}"#)));
+        }
+        let omniscient = SpectatorView::omniscient(&world).unwrap();
+        assert!(omniscient.is_omniscient());
+        let (player, hand) = &omniscient.hands[1];
+        assert_eq!(*player, players[1]);
+        assert!(hand.dice.iter().all(|die| die.face == Some(4)));
+        assert_eq!(omniscient.name(players[2]), "Cid");
+    }
+}
@@ -0,0 +1,101 @@
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn dudo(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_dudo"))
+        .args(args)
+        .env("NO_COLOR", "1")
+        .output()
+        .expect("failed to run dudo")
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("dudo-cli-{}-{name}", std::process::id()))
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn bot_game_runs_to_the_end_and_replays_the_same() {
+    let log = temp_path("bots.jsonl");
+    let log = log.to_str().unwrap();
+    let profiles = temp_path("players.json");
+    let profiles = profiles.to_str().unwrap();
+    let args = [
+        "play",
+        "--bots",
+        "3",
+        "--seed",
+        "42",
+        "--log",
+        log,
+        "--profiles",
+        profiles,
+    ];
+
+    let first = dudo(&args);
+    assert_eq!(first.status.code(), Some(0), "{}", stdout(&first));
+    assert!(stdout(&first).contains("wins the game!"));
+
+    let second = dudo(&args);
+    assert_eq!(stdout(&first), stdout(&second));
+
+    let replay = dudo(&["replay", log, "--print"]);
+    assert_eq!(replay.status.code(), Some(0));
+    assert!(stdout(&replay).contains("GameOver"));
+    std::fs::remove_file(log).unwrap();
+    // Three of the same bot share a profile and have no one to be rated
+    // against, so nothing is recorded.
+    assert!(!std::path::Path::new(profiles).exists());
+}
+
+#[test]
+fn simulate_prints_json() {
+    let output = dudo(&[
+        "simulate",
+        "--games",
+        "3",
+        "--bots",
+        "easy,hard",
+        "--seed",
+        "1",
+        "--rules",
+        "classic",
+        "--json",
+    ]);
+    assert_eq!(output.status.code(), Some(0));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["games"], 3);
+    assert_eq!(report["rules"]["wild_ones"], false);
+    assert_eq!(report["entrants"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn rules_print_without_prompting() {
+    let output = dudo(&["rules"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout(&output).contains("PALIFICO"));
+}
+
+#[test]
+fn exit_codes_describe_the_failure() {
+    assert_eq!(dudo(&["play", "--bots", "9"]).status.code(), Some(2));
+    assert_eq!(dudo(&["play", "--rules", "yahtzee"]).status.code(), Some(2));
+    let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = closed.local_addr().unwrap().to_string();
+    drop(closed);
+    let output = dudo(&["join", &addr, "--name", "Ana"]);
+    assert_eq!(output.status.code(), Some(69));
+
+    let missing = temp_path("missing.jsonl");
+    let output = dudo(&["replay", missing.to_str().unwrap(), "--print"]);
+    assert_eq!(output.status.code(), Some(66));
+
+    let corrupt = temp_path("corrupt.jsonl");
+    std::fs::write(&corrupt, "not json\n").unwrap();
+    let output = dudo(&["replay", corrupt.to_str().unwrap(), "--print"]);
+    assert_eq!(output.status.code(), Some(65));
+    std::fs::remove_file(corrupt).unwrap();
+}
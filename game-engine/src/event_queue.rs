@@ -35,3 +35,9 @@ impl<T: GameEvent> EventQueue<T> {
         self.events.is_empty()
     }
 }
+
+impl<T: GameEvent> Default for EventQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -85,7 +85,7 @@ impl World {
         }
     }
 
-    pub fn spawn(&mut self) -> EntityBuilder {
+    pub fn spawn(&mut self) -> EntityBuilder<'_> {
         let entity = self.create_entity();
         EntityBuilder::new(self, entity)
     }
@@ -126,7 +126,7 @@ impl World {
 
         component_storage
             .get::<T>()
-            .ok_or_else(|| WorldStorageError::ComponentTypeMismatch { expected: typename })
+            .ok_or(WorldStorageError::ComponentTypeMismatch { expected: typename })
     }
 
     pub fn query_component_mut<T: Component>(
@@ -142,7 +142,7 @@ impl World {
 
         component_storage
             .get_mut::<T>()
-            .ok_or_else(|| WorldStorageError::ComponentTypeMismatch { expected: typename })
+            .ok_or(WorldStorageError::ComponentTypeMismatch { expected: typename })
     }
 
     pub fn component<T: Component>(&self, entity: Entity) -> Result<&T, WorldStorageError> {
@@ -219,6 +219,12 @@ impl World {
     }
 }
 
+impl Default for World {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use super::*;